@@ -0,0 +1,138 @@
+//! Checks registered solvers against a manifest of known answers, such as `input/2018/answers.txt`.
+//!
+//! Each non-comment line of a manifest reads `day part variant input answer`, where `variant` is
+//! `-` for the default solver, `input` is relative to the manifest and `\n` in `answer` is a newline.
+use crate::registry::{Registry, SolverKey};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Expectation {
+    pub day: u8,
+    pub part: u8,
+    pub variant: Option<String>,
+    pub input: PathBuf,
+    pub answer: String,
+}
+
+pub fn parse_manifest(manifest: &str, base: &Path) -> Result<Vec<Expectation>, String> {
+    let mut expectations = Vec::new();
+    for (index, line) in manifest.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.splitn(5, ' ').collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "line {}: expected `day part variant input answer`",
+                index + 1
+            ));
+        }
+        let number = |field: &str| {
+            field
+                .parse()
+                .map_err(|_| format!("line {}: invalid number `{}`", index + 1, field))
+        };
+        expectations.push(Expectation {
+            day: number(fields[0])?,
+            part: number(fields[1])?,
+            variant: match fields[2] {
+                "-" => None,
+                variant => Some(variant.to_owned()),
+            },
+            input: base.join(fields[3]),
+            answer: fields[4].replace("\\n", "\n"),
+        });
+    }
+    Ok(expectations)
+}
+
+pub fn load_manifest(path: &Path) -> Result<Vec<Expectation>, String> {
+    let manifest = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse_manifest(&manifest, path.parent().unwrap_or_else(|| Path::new("")))
+}
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub expectation: Expectation,
+    /// The solver's answer, or why there wasn't one.
+    pub actual: Result<String, String>,
+    pub elapsed: Duration,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.actual.as_ref() == Ok(&self.expectation.answer)
+    }
+}
+
+/// Runs the solver named by each expectation on its input.
+pub fn check(registry: &Registry, expectations: &[Expectation]) -> Vec<Outcome> {
+    expectations
+        .iter()
+        .map(|expectation| {
+            let start = Instant::now();
+            let actual = fs::read_to_string(&expectation.input)
+                .map_err(|e| format!("{}: {}", expectation.input.display(), e))
+                .and_then(|input| {
+                    registry
+                        .solve(
+                            expectation.day,
+                            expectation.part,
+                            expectation.variant.as_deref(),
+                            &input,
+                        )
+                        .map(|answer| answer.to_string())
+                        .map_err(|e| e.to_string())
+                });
+            Outcome {
+                expectation: expectation.clone(),
+                actual,
+                elapsed: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+/// Registered solvers the manifest has no answer for.
+pub fn unchecked<'a>(registry: &'a Registry, expectations: &[Expectation]) -> Vec<&'a SolverKey> {
+    registry
+        .keys()
+        .filter(|(day, part, variant)| {
+            !expectations
+                .iter()
+                .any(|e| e.day == *day && e.part == *part && e.variant.as_deref() == *variant)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::answers::*;
+
+    #[test]
+    fn manifest() {
+        let expectations = parse_manifest(
+            "# comment
+8 2 fast day8.txt 66
+10 1 - day10.txt \\n# #\\n",
+            Path::new("input"),
+        )
+        .unwrap();
+        assert_eq!(expectations[0].variant.as_deref(), Some("fast"));
+        assert_eq!(expectations[0].input, Path::new("input/day8.txt"));
+        assert_eq!(expectations[1].variant, None);
+        assert_eq!(expectations[1].answer, "\n# #\n");
+        assert!(parse_manifest("8 two - day8.txt 66", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn unchecked_solvers() {
+        let registry = Registry::new();
+        let expectations = parse_manifest("25 1 - day25.txt 2", Path::new("")).unwrap();
+        let missing = unchecked(&registry, &expectations);
+        assert_eq!(missing.len(), registry.keys().count() - 1);
+        assert!(!missing.contains(&&(25, 1, None)));
+    }
+}
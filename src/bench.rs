@@ -0,0 +1,292 @@
+//! Timing the generator and the solver of each part apart, over many runs, for keeping an eye
+//! on the heavy days.
+//!
+//! Allocations are only counted when the program installs `CountingAllocator` as its global
+//! allocator, as the `aoc2018` binary does; otherwise they are reported as unknown.
+use crate::registry::{Registry, SolverKey};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting every allocation and the bytes asked for. A reallocation
+/// counts as an allocation of its new size.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        // SAFETY: passed straight through from our caller
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: passed straight through from our caller
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        // SAFETY: passed straight through from our caller
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+fn allocated() -> (u64, u64) {
+    (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+/// Whether `CountingAllocator` is the global allocator.
+pub fn counting() -> bool {
+    let before = allocated().0;
+    drop(std::hint::black_box(Box::new(0_u8)));
+    allocated().0 != before
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stage {
+    /// Running the generator on the input.
+    Parse,
+    /// Running the part's solver on the generator's output.
+    Solve,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse => write!(f, "parse"),
+            Self::Solve => write!(f, "solve"),
+        }
+    }
+}
+
+/// Wall times over every run, with the median number of allocations and bytes allocated in
+/// a run when they were counted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+    pub allocations: Option<u64>,
+    pub bytes: Option<u64>,
+}
+
+fn median<T: Ord + Copy>(values: &mut [T]) -> T {
+    values.sort_unstable();
+    values[values.len() / 2]
+}
+
+impl Stats {
+    fn of(samples: &[(Duration, (u64, u64))], counted: bool) -> Self {
+        let mut times: Vec<Duration> = samples.iter().map(|sample| sample.0).collect();
+        let mut allocations: Vec<u64> = samples.iter().map(|sample| (sample.1).0).collect();
+        let mut bytes: Vec<u64> = samples.iter().map(|sample| (sample.1).1).collect();
+        Self {
+            runs: samples.len(),
+            min: *times.iter().min().unwrap(),
+            median: median(&mut times),
+            max: *times.iter().max().unwrap(),
+            allocations: Some(median(&mut allocations)).filter(|_| counted),
+            bytes: Some(median(&mut bytes)).filter(|_| counted),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Measurement {
+    pub key: SolverKey,
+    pub stage: Stage,
+    pub stats: Stats,
+}
+
+/// Runs `work` and returns how long it took and what it allocated.
+fn sample<T>(work: impl FnOnce() -> T) -> (T, (Duration, (u64, u64))) {
+    let (allocations, bytes) = allocated();
+    let start = Instant::now();
+    let result = work();
+    let elapsed = start.elapsed();
+    let after = allocated();
+    (result, (elapsed, (after.0 - allocations, after.1 - bytes)))
+}
+
+/// Times the generator and then the solver for `key` on `input`, `runs` times each. The
+/// solver is handed a freshly parsed input every run, and fails the bench if it fails.
+pub fn measure(
+    registry: &Registry,
+    key: &SolverKey,
+    input: &str,
+    runs: usize,
+) -> Result<[Measurement; 2], Box<dyn Error>> {
+    let (day, part, variant) = *key;
+    let solver = registry
+        .get(day, part, variant)
+        .ok_or_else(|| format!("no solver for day{} part{}", day, part))?;
+    let counted = counting();
+    let runs = runs.max(1);
+
+    let mut parsing = Vec::with_capacity(runs);
+    let mut solving = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let (solution, parsed) = sample(|| solver(input));
+        parsing.push(parsed);
+        let solution = solution?;
+        let (answer, solved) = sample(solution);
+        answer?;
+        solving.push(solved);
+    }
+    Ok([
+        Measurement {
+            key: *key,
+            stage: Stage::Parse,
+            stats: Stats::of(&parsing, counted),
+        },
+        Measurement {
+            key: *key,
+            stage: Stage::Solve,
+            stats: Stats::of(&solving, counted),
+        },
+    ])
+}
+
+fn micros(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1e6)
+}
+
+fn count(count: Option<u64>) -> String {
+    count.map_or_else(String::new, |count| count.to_string())
+}
+
+/// One line per measurement under a header, with times in microseconds and blank counts when
+/// allocations weren't counted.
+pub fn csv(measurements: &[Measurement]) -> String {
+    let mut csv =
+        String::from("day,part,variant,stage,runs,min_us,median_us,max_us,allocations,bytes\n");
+    for m in measurements {
+        let (day, part, variant) = m.key;
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            day,
+            part,
+            variant.unwrap_or(""),
+            m.stage,
+            m.stats.runs,
+            micros(m.stats.min),
+            micros(m.stats.median),
+            micros(m.stats.max),
+            count(m.stats.allocations),
+            count(m.stats.bytes)
+        ));
+    }
+    csv
+}
+
+/// A JSON array with an object per measurement, using the same names as the CSV columns and
+/// `null` for what's unknown.
+pub fn json(measurements: &[Measurement]) -> String {
+    let optional = |value: Option<u64>| value.map_or_else(|| "null".to_string(), |v| v.to_string());
+    let objects: Vec<String> = measurements
+        .iter()
+        .map(|m| {
+            let (day, part, variant) = m.key;
+            format!(
+                "  {{\"day\": {}, \"part\": {}, \"variant\": {}, \"stage\": \"{}\", \"runs\": {}, \
+                 \"min_us\": {}, \"median_us\": {}, \"max_us\": {}, \"allocations\": {}, \
+                 \"bytes\": {}}}",
+                day,
+                part,
+                variant.map_or_else(|| "null".to_string(), |v| format!("\"{}\"", v)),
+                m.stage,
+                m.stats.runs,
+                micros(m.stats.min),
+                micros(m.stats.median),
+                micros(m.stats.max),
+                optional(m.stats.allocations),
+                optional(m.stats.bytes)
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+/// Aligned columns for reading in a terminal, times in milliseconds.
+pub fn table(measurements: &[Measurement]) -> String {
+    let mut table = format!(
+        "{:<16} {:>5} {:>11} {:>11} {:>11} {:>11} {:>12}\n",
+        "solver", "runs", "min ms", "median ms", "max ms", "allocs", "bytes"
+    );
+    for m in measurements {
+        let (day, part, variant) = m.key;
+        let name = variant.map_or_else(
+            || format!("day{} p{} {}", day, part, m.stage),
+            |variant| format!("day{} p{} {} {}", day, part, variant, m.stage),
+        );
+        let millis = |duration: Duration| format!("{:.3}", duration.as_secs_f64() * 1000.0);
+        table.push_str(&format!(
+            "{:<16} {:>5} {:>11} {:>11} {:>11} {:>11} {:>12}\n",
+            name,
+            m.stats.runs,
+            millis(m.stats.min),
+            millis(m.stats.median),
+            millis(m.stats.max),
+            count(m.stats.allocations),
+            count(m.stats.bytes)
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn stats() {
+        let sample = |millis, allocations| (Duration::from_millis(millis), (allocations, 8));
+        let stats = Stats::of(&[sample(3, 5), sample(1, 7), sample(2, 6)], true);
+        assert_eq!(stats.runs, 3);
+        assert_eq!(
+            (stats.min, stats.median, stats.max),
+            (
+                Duration::from_millis(1),
+                Duration::from_millis(2),
+                Duration::from_millis(3)
+            )
+        );
+        assert_eq!((stats.allocations, stats.bytes), (Some(6), Some(8)));
+        assert_eq!(Stats::of(&[sample(1, 1)], false).allocations, None);
+    }
+
+    #[test]
+    fn measuring() {
+        assert!(counting());
+        let registry = Registry::new();
+        let input = "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2";
+        let [parse, solve] = measure(&registry, &(3, 2, None), input, 3).unwrap();
+        assert_eq!((parse.stage, solve.stage), (Stage::Parse, Stage::Solve));
+        assert_eq!(solve.stats.runs, 3);
+        assert!(parse.stats.allocations.unwrap() > 0);
+        assert!(solve.stats.min <= solve.stats.median && solve.stats.median <= solve.stats.max);
+        assert!(measure(&registry, &(3, 2, None), "#1 @ 1,3 4x4", 1).is_err());
+        assert!(measure(&registry, &(26, 1, None), input, 1).is_err());
+
+        let measurements = [parse, solve];
+        let csv = csv(&measurements);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(2).unwrap().starts_with("3,2,,solve,3,"));
+        assert!(json(&measurements).contains("\"stage\": \"parse\", \"runs\": 3"));
+        assert!(table(&measurements).contains("day3 p2 solve"));
+    }
+}
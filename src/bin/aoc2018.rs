@@ -0,0 +1,334 @@
+//! Solves a single puzzle from a file or stdin.
+//!
+//! aoc2018 --day 8 --part 2 [--variant fast] [--steps N] [--seconds S] [INPUT]
+//! aoc2018 --list
+//! aoc2018 --generate --day 3 [--seed N] [--size N]
+//! aoc2018 --json --day 15 [INPUT]
+//! aoc2018 --validate --day 7 [INPUT]
+//! aoc2018 --explain --day 4 --part 1 [INPUT]
+//! aoc2018 --bench [--day 15 [--part 2]] [--runs N] [--format table|csv|json] [INPUT]
+//!
+//! Reads the puzzle input from INPUT, or from stdin when INPUT is missing or `-`. Days 1, 3,
+//! 23 and 25 parse it as it's read, so a long generated input can be piped straight in.
+//! `--steps` and `--seconds` override the budget of the solvers that search until they
+//! find an answer. `--generate` prints a random input for the day instead of solving it, and
+//! `--json` prints the day's parsed input as JSON, when built with the `serde` feature.
+//! `--validate` lists every problem found with the input, one per line. Days 4, 7, 8, 16 and 20
+//! are always validated before solving, as their solvers can't tell a broken input from a
+//! good one.
+//! `--explain` prints what the answer was worked out from on the line after it, for the parts
+//! `witness::explains`.
+//! `--bench` times the generator and solver of each part apart, `--runs` times over, 10 by
+//! default. Without `--day` it benches every day on `dayN.txt` in `--inputs`, which is
+//! `input/2018` by default.
+use adventofcode_2018::bench::{self, CountingAllocator, Measurement};
+use adventofcode_2018::budget::Budget;
+use adventofcode_2018::gen;
+use adventofcode_2018::registry::Registry;
+use adventofcode_2018::validate;
+use adventofcode_2018::witness;
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::process;
+use std::time::Duration;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const USAGE: &str = "usage: aoc2018 --day <1-25> --part <1|2> [--variant <name>]
+                   [--steps <count>] [--seconds <limit>] [INPUT|-]
+       aoc2018 --list
+       aoc2018 --generate --day <1-25> [--seed <number>] [--size <number>]
+       aoc2018 --json --day <1-25> [INPUT|-]
+       aoc2018 --validate --day <1-25> [INPUT|-]
+       aoc2018 --explain --day <1-25> --part <1|2> [INPUT|-]
+       aoc2018 --bench [--day <1-25> [--part <1|2>] [--variant <name>]] [--runs <count>]
+               [--format <table|csv|json>] [--inputs <dir>] [INPUT|-]";
+
+struct Args {
+    list: bool,
+    generate: bool,
+    json: bool,
+    validate: bool,
+    explain: bool,
+    bench: Option<Bench>,
+    seed: u64,
+    size: usize,
+    day: u8,
+    part: u8,
+    variant: Option<String>,
+    budget: Budget,
+    input: Option<String>,
+}
+
+struct Bench {
+    runs: usize,
+    format: String,
+    inputs: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let (mut day, mut part, mut variant, mut input) = (None, None, None, None);
+    let (mut list, mut generate, mut json, mut bench) = (false, false, false, false);
+    let (mut validate, mut explain) = (false, false);
+    let (mut runs, mut format, mut inputs) = (10, "table".to_owned(), "input/2018".to_owned());
+    let (mut seed, mut size) = (0, 100);
+    let mut budget = Budget::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "-d" => day = args.next(),
+            "--part" | "-p" => part = args.next(),
+            "--variant" | "-v" => variant = args.next(),
+            "--steps" => {
+                let steps = args.next().and_then(|s| s.parse().ok());
+                budget.steps = Some(steps.ok_or_else(|| format!("invalid --steps\n{}", USAGE))?);
+            }
+            "--seconds" => {
+                let seconds = args.next().and_then(|s| s.parse().ok());
+                let seconds: f64 = seconds
+                    .filter(|s: &f64| s.is_finite() && *s >= 0.0)
+                    .ok_or_else(|| format!("invalid --seconds\n{}", USAGE))?;
+                budget.time = Some(Duration::from_secs_f64(seconds));
+            }
+            "--list" | "-l" => list = true,
+            "--generate" | "-g" => generate = true,
+            "--json" | "-j" => json = true,
+            "--validate" => validate = true,
+            "--explain" => explain = true,
+            "--bench" | "-b" => bench = true,
+            "--runs" => {
+                let parsed = args.next().and_then(|s| s.parse().ok());
+                runs = parsed.ok_or_else(|| format!("invalid --runs\n{}", USAGE))?;
+            }
+            "--format" => match args.next() {
+                Some(f) if ["table", "csv", "json"].contains(&f.as_str()) => format = f,
+                _ => return Err(format!("invalid --format\n{}", USAGE)),
+            },
+            "--inputs" => {
+                inputs = args
+                    .next()
+                    .ok_or_else(|| format!("missing --inputs\n{}", USAGE))?;
+            }
+            "--seed" => {
+                let parsed = args.next().and_then(|s| s.parse().ok());
+                seed = parsed.ok_or_else(|| format!("invalid --seed\n{}", USAGE))?;
+            }
+            "--size" => {
+                let parsed = args.next().and_then(|s| s.parse().ok());
+                size = parsed.ok_or_else(|| format!("invalid --size\n{}", USAGE))?;
+            }
+            "--help" | "-h" => return Err(USAGE.to_owned()),
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`\n{}", arg, USAGE)),
+        }
+    }
+    let bench = Some(Bench {
+        runs,
+        format,
+        inputs,
+    })
+    .filter(|_| bench);
+    if list || (bench.is_some() && day.is_none()) {
+        return Ok(Args {
+            list,
+            generate,
+            json,
+            validate,
+            explain,
+            bench,
+            seed,
+            size,
+            day: 0,
+            part: 0,
+            variant,
+            budget,
+            input,
+        });
+    }
+    let day = day
+        .and_then(|d| d.parse().ok())
+        .ok_or_else(|| format!("missing or invalid --day\n{}", USAGE))?;
+    let part = match part {
+        None if generate || json || validate || bench.is_some() => 0,
+        part => part
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(|| format!("missing or invalid --part\n{}", USAGE))?,
+    };
+    Ok(Args {
+        list,
+        generate,
+        json,
+        validate,
+        explain,
+        bench,
+        seed,
+        size,
+        day,
+        part,
+        variant,
+        budget,
+        input: input.filter(|i| i != "-"),
+    })
+}
+
+fn read_input(path: &Option<String>) -> io::Result<String> {
+    match path {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+/// Fails with every problem `validate` finds with `input`, one per line.
+fn check(day: u8, input: &str) -> Result<(), Box<dyn Error>> {
+    let errors = validate::validate(day, input)?;
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        return Err(errors.join("\n").into());
+    }
+    Ok(())
+}
+
+fn open_input(path: &Option<String>) -> io::Result<Box<dyn BufRead>> {
+    Ok(match path {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(io::stdin().lock()),
+    })
+}
+
+#[cfg(feature = "serde")]
+fn to_json(day: u8, input: &str) -> Result<String, Box<dyn Error>> {
+    adventofcode_2018::dump::to_json(day, input)
+}
+
+#[cfg(not(feature = "serde"))]
+fn to_json(_day: u8, _input: &str) -> Result<String, Box<dyn Error>> {
+    Err("--json needs the serde feature: cargo run --features serde".into())
+}
+
+/// Benches the solvers picked by `--day`, `--part` and `--variant`, or every solver on the
+/// inputs in `--inputs` when there's no `--day`.
+fn run_bench(
+    registry: &Registry,
+    args: &Args,
+    options: &Bench,
+) -> Result<Vec<Measurement>, Box<dyn Error>> {
+    let mut measurements = Vec::new();
+    let keys = registry.keys().filter(|(day, part, variant)| {
+        (args.day == 0 || *day == args.day)
+            && (args.part == 0 || *part == args.part)
+            && (args.variant.is_none() || variant.as_deref() == args.variant.as_deref())
+    });
+    let mut inputs: Vec<(u8, String)> = Vec::new();
+    for key in keys {
+        let day = key.0;
+        if inputs.last().is_none_or(|(d, _)| *d != day) {
+            let input = if args.day == 0 {
+                let path = format!("{}/day{}.txt", options.inputs, day);
+                fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?
+            } else {
+                read_input(&args.input)?
+            };
+            inputs.push((day, input));
+        }
+        let input = &inputs.last().unwrap().1;
+        measurements.extend_from_slice(&bench::measure(registry, key, input, options.runs)?);
+    }
+    if measurements.is_empty() {
+        return Err("nothing to bench".into());
+    }
+    Ok(measurements)
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(2);
+    });
+    let registry = Registry::with_budget(args.budget);
+    if args.list {
+        for (day, part, variant) in registry.keys() {
+            match variant {
+                Some(variant) => println!("day{} part{} {}", day, part, variant),
+                None => println!("day{} part{}", day, part),
+            }
+        }
+        return;
+    }
+    if args.generate {
+        match gen::generate(args.day, args.seed, args.size) {
+            Some(input) => print!("{}", input),
+            None => {
+                eprintln!("no generator for day{}", args.day);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(options) = &args.bench {
+        let report = run_bench(&registry, &args, options).map(|measurements| {
+            match options.format.as_str() {
+                "csv" => bench::csv(&measurements),
+                "json" => bench::json(&measurements),
+                _ => bench::table(&measurements),
+            }
+        });
+        match report {
+            Ok(report) => print!("{}", report),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let result: Result<String, Box<dyn Error>> = if args.json {
+        read_input(&args.input)
+            .map_err(|e| e.into())
+            .and_then(|input| to_json(args.day, &input))
+    } else if args.explain {
+        read_input(&args.input)
+            .map_err(|e| e.into())
+            .and_then(|input| {
+                if validate::checks(args.day) {
+                    check(args.day, &input)?;
+                }
+                let (answer, witness) = witness::solve_with_witness(args.day, args.part, &input)?;
+                Ok(format!("{}\n{}", answer, witness))
+            })
+    } else if args.validate || validate::checks(args.day) {
+        read_input(&args.input)
+            .map_err(|e| e.into())
+            .and_then(|input| {
+                check(args.day, &input)?;
+                if args.validate {
+                    return Ok("ok".to_owned());
+                }
+                let answer = registry.solve(args.day, args.part, args.variant.as_deref(), &input);
+                answer.map(|answer| answer.to_string())
+            })
+    } else {
+        open_input(&args.input)
+            .map_err(|e| e.into())
+            .and_then(|mut input| {
+                let variant = args.variant.as_deref();
+                registry.solve_reader(args.day, args.part, variant, &mut input)
+            })
+            .map(|answer| answer.to_string())
+    };
+    match result {
+        Ok(output) => println!("{}", output),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+}
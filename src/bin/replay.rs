@@ -0,0 +1,307 @@
+//! Replays a grid simulation in the terminal, one frame per step, or draws its frames as
+//! pictures.
+//!
+//! replay --day 15 [--delay MS] [--top ROW] [--rows N] [--plain] [INPUT]
+//! replay --day 15 --export DIR [--format png|ppm] [--cell PIXELS] [--palette SPEC]
+//!        [--from N] [--to N] [--every N] [INPUT]
+//!
+//! Works for day10's sky, day13's carts, day15's battle, day17's water and day18's acres.
+//! Reads the puzzle input from INPUT, or from stdin when INPUT is missing or `-`.
+//!
+//! `--export` writes frames `--from` to `--to` (0 to 1000 by default), every `--every`th
+//! one, as numbered files in DIR, stopping early at the last frame. Each character becomes a
+//! `--cell` pixel square, 4 by default, coloured by the day's palette with any changes from
+//! `--palette`, such as `#=ff0000,bg=000000`.
+//!
+//! Otherwise controls are typed as lines on the terminal, and the replay starts paused:
+//!
+//!   Enter or n   next frame        p   play or pause
+//!   b            previous frame    g N go to frame N
+//!   f / s        faster / slower   r N scroll so row N is at the top
+//!   q            quit
+//!
+//! With no terminal to read controls from, the replay plays to the end and stops.
+use adventofcode_2018::image::{Image, Palette};
+use adventofcode_2018::replay::{self, Frames};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str =
+    "usage: replay --day <10|13|15|17|18> [--delay <ms>] [--top <row>] [--rows <count>]
+              [--plain] [INPUT|-]
+       replay --day <10|13|15|17|18> --export <dir> [--format <png|ppm>] [--cell <pixels>]
+              [--palette <spec>] [--from <frame>] [--to <frame>] [--every <count>] [INPUT|-]";
+
+const CONTROLS: &str =
+    "[enter/n] next  [b] back  [p] play/pause  [g N] go to  [f/s] faster/slower  [r N] row  [q] quit";
+
+struct Args {
+    day: u8,
+    delay: Duration,
+    top: usize,
+    rows: Option<usize>,
+    plain: bool,
+    export: Option<Export>,
+    input: Option<String>,
+}
+
+struct Export {
+    dir: String,
+    png: bool,
+    cell: usize,
+    palette: Palette,
+    from: usize,
+    to: usize,
+    every: usize,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let (mut day, mut input) = (None, None);
+    let mut delay = Duration::from_millis(100);
+    let (mut top, mut rows, mut plain) = (0, None, false);
+    let (mut dir, mut png, mut cell, mut palette) = (None, true, 4, None);
+    let (mut from, mut to, mut every) = (0, 1000, 1);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| {
+            args.next()
+                .and_then(|s| s.parse::<u64>().ok())
+                .ok_or_else(|| format!("invalid {}\n{}", name, USAGE))
+        };
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(number("--day")?),
+            "--delay" => delay = Duration::from_millis(number("--delay")?),
+            "--top" => top = number("--top")? as usize,
+            "--rows" => rows = Some(number("--rows")? as usize),
+            "--plain" => plain = true,
+            "--export" => dir = args.next(),
+            "--format" => match args.next().as_deref() {
+                Some("png") => png = true,
+                Some("ppm") => png = false,
+                _ => return Err(format!("invalid --format\n{}", USAGE)),
+            },
+            "--cell" => cell = number("--cell")?.max(1) as usize,
+            "--palette" => palette = args.next(),
+            "--from" => from = number("--from")? as usize,
+            "--to" => to = number("--to")? as usize,
+            "--every" => every = number("--every")?.max(1) as usize,
+            "--help" | "-h" => return Err(USAGE.to_owned()),
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`\n{}", arg, USAGE)),
+        }
+    }
+    let day = day
+        .filter(|&d| d <= 25)
+        .ok_or_else(|| format!("missing or invalid --day\n{}", USAGE))? as u8;
+    let export = match dir {
+        Some(dir) => {
+            let mut colours = Palette::for_day(day);
+            colours.apply(palette.as_deref().unwrap_or(""))?;
+            Some(Export {
+                dir,
+                png,
+                cell,
+                palette: colours,
+                from,
+                to,
+                every,
+            })
+        }
+        None => None,
+    };
+    Ok(Args {
+        day,
+        delay,
+        top,
+        rows,
+        plain,
+        export,
+        input: input.filter(|i| i != "-"),
+    })
+}
+
+fn read_input(path: &Option<String>) -> io::Result<String> {
+    match path {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+/// Reads control lines on another thread, from the terminal if there is one, so playing can
+/// wait for either the next frame or a key.
+fn controls() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let source: Box<dyn BufRead> = match File::open("/dev/tty") {
+            Ok(tty) => Box::new(BufReader::new(tty)),
+            Err(_) => Box::new(BufReader::new(io::stdin())),
+        };
+        for line in source.lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+fn draw(frames: &dyn Frames, args: &Args, playing: bool) -> io::Result<()> {
+    let frame = if args.plain {
+        frames.render()
+    } else {
+        frames.render_ansi()
+    };
+    let rows = args.rows.unwrap_or(usize::MAX);
+    let last = frames
+        .last()
+        .map_or_else(|| "?".to_string(), |last| last.to_string());
+    let mut out = io::stdout().lock();
+    write!(out, "\x1b[H\x1b[2J")?;
+    writeln!(
+        out,
+        "day{}  frame {}/{}  {}  {}ms",
+        args.day,
+        frames.frame(),
+        last,
+        if playing { "playing" } else { "paused" },
+        args.delay.as_millis()
+    )?;
+    for line in frame.lines().skip(args.top).take(rows) {
+        writeln!(out, "{}", line)?;
+    }
+    writeln!(out, "{}", CONTROLS)?;
+    out.flush()
+}
+
+fn run(frames: &mut dyn Frames, mut args: Args) -> io::Result<()> {
+    let controls = controls();
+    let mut playing = false;
+    let mut interactive = true;
+    loop {
+        draw(frames, &args, playing)?;
+        let line = if !interactive {
+            None
+        } else if playing {
+            match controls.recv_timeout(args.delay) {
+                Ok(line) => Some(line),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    interactive = false;
+                    None
+                }
+            }
+        } else {
+            match controls.recv() {
+                Ok(line) => Some(line),
+                Err(_) => {
+                    interactive = false;
+                    playing = true;
+                    None
+                }
+            }
+        };
+
+        let current = frames.frame();
+        let line = match line {
+            Some(line) => line,
+            None => {
+                if frames.seek(current + 1) == current {
+                    if !interactive {
+                        return Ok(());
+                    }
+                    playing = false;
+                }
+                if !interactive {
+                    thread::sleep(args.delay);
+                }
+                continue;
+            }
+        };
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("n");
+        let number = words.next().and_then(|n| n.parse::<usize>().ok());
+        match command {
+            "n" => {
+                playing = false;
+                frames.seek(current + 1);
+            }
+            "b" => {
+                playing = false;
+                frames.seek(current.saturating_sub(1));
+            }
+            "p" => playing = !playing,
+            "g" => {
+                frames.seek(number.unwrap_or(0));
+            }
+            "f" => args.delay /= 2,
+            "s" => args.delay = (args.delay * 2).max(Duration::from_millis(1)),
+            "r" => args.top = number.unwrap_or(0),
+            "q" => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// Writes the frames as numbered pictures, returning how many were written. Reaching the last
+/// frame between two that were asked for writes the last frame too.
+fn export(frames: &mut dyn Frames, day: u8, export: &Export) -> io::Result<usize> {
+    fs::create_dir_all(&export.dir)?;
+    let extension = if export.png { "png" } else { "ppm" };
+    let mut written = 0;
+    for frame in (export.from..=export.to).step_by(export.every) {
+        let reached = frames.seek(frame);
+        if reached < frame && (written == 0 || reached + export.every <= frame) {
+            break;
+        }
+        let image = Image::from_text(&frames.cells(), export.cell, &export.palette);
+        let bytes = if export.png {
+            image.to_png()
+        } else {
+            image.to_ppm()
+        };
+        let path = format!("{}/day{}-{:05}.{}", export.dir, day, reached, extension);
+        fs::write(path, bytes)?;
+        written += 1;
+        if reached < frame {
+            break;
+        }
+    }
+    Ok(written)
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(2);
+    });
+    let frames = read_input(&args.input)
+        .map_err(|e| e.into())
+        .and_then(|input| replay::open(args.day, &input));
+    let mut frames = frames.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    if let Some(options) = &args.export {
+        match export(frames.as_mut(), args.day, options) {
+            Ok(written) => eprintln!("wrote {} frames to {}", written, options.dir),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Err(error) = run(frames.as_mut(), args) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
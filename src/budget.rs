@@ -0,0 +1,132 @@
+//! Limits for the searches that only stop once they find their answer, so input that has no
+//! answer is reported rather than looping forever.
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How far an open-ended search may go.
+///
+/// What a step is depends on the solver: a pass through day1's changes, a second of day10's
+/// sky, a state stepped looking for day12's, day18's or day21's cycle, a round of day16's
+/// deductions, a cube split in day23 or a boost tried in day24.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Budget {
+    pub steps: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            steps: Some(1_000_000),
+            time: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+impl Budget {
+    pub const fn unlimited() -> Self {
+        Self {
+            steps: None,
+            time: None,
+        }
+    }
+
+    pub const fn steps(steps: u64) -> Self {
+        Self {
+            steps: Some(steps),
+            time: None,
+        }
+    }
+
+    pub const fn time(time: Duration) -> Self {
+        Self {
+            steps: None,
+            time: Some(time),
+        }
+    }
+
+    pub fn start(self) -> Meter {
+        Meter {
+            budget: self,
+            steps: 0,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Spends a budget one step at a time.
+#[derive(Clone, Debug)]
+pub struct Meter {
+    budget: Budget,
+    steps: u64,
+    started: Instant,
+}
+
+impl Meter {
+    /// Records a step, failing once the step count or time limit has been passed.
+    pub fn tick(&mut self) -> Result<(), Unsolved> {
+        self.steps += 1;
+        let elapsed = self.started.elapsed();
+        let over_steps = self.budget.steps.is_some_and(|steps| self.steps > steps);
+        let over_time = self.budget.time.is_some_and(|time| elapsed > time);
+        if over_steps || over_time {
+            Err(Unsolved::BudgetExceeded {
+                steps: self.steps - 1,
+                elapsed,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Why a solver stopped without an answer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Unsolved {
+    /// The input can never produce an answer.
+    NoSolution(&'static str),
+    /// The search gave up after spending its whole budget.
+    BudgetExceeded { steps: u64, elapsed: Duration },
+}
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoSolution(reason) => write!(f, "no solution: {}", reason),
+            Self::BudgetExceeded { steps, elapsed } => write!(
+                f,
+                "gave up after {} steps in {:.3}s",
+                steps,
+                elapsed.as_secs_f64()
+            ),
+        }
+    }
+}
+
+impl Error for Unsolved {}
+
+#[cfg(test)]
+mod tests {
+    use crate::budget::*;
+
+    #[test]
+    fn steps() {
+        let mut meter = Budget::steps(2).start();
+        assert!(meter.tick().is_ok());
+        assert!(meter.tick().is_ok());
+        match meter.tick() {
+            Err(Unsolved::BudgetExceeded { steps, .. }) => assert_eq!(steps, 2),
+            result => panic!("expected to run out of budget, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn time() {
+        let mut meter = Budget::time(Duration::from_secs(0)).start();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(meter.tick().is_err());
+        let mut meter = Budget::unlimited().start();
+        assert!((0..10_000).all(|_| meter.tick().is_ok()));
+    }
+}
@@ -0,0 +1,199 @@
+//! Finding where a long-running simulation starts repeating itself, so the state a huge number
+//! of steps in can be worked out from the first time round the loop.
+//!
+//! Every finder takes the starting state, a `step` from one state to the next and a `key`
+//! projecting the part of a state that decides its future. Two states with equal keys must
+//! go on to produce equal keys forever, but can otherwise differ, such as day12's pots
+//! shifting along as the pattern repeats. States that never repeat would keep a finder going
+//! forever, so each one ticks `meter` for every step it takes.
+use crate::budget::{Meter, Unsolved};
+use hashbrown::HashMap;
+use std::hash::Hash;
+
+/// The first `prefix` states are visited once; after that the states repeat every `period` steps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The step before the end of the first period that has the same key as step `n`.
+    pub const fn equivalent(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+
+    /// How many whole periods lie between step `n` and its equivalent step.
+    pub const fn periods(&self, n: usize) -> usize {
+        if n < self.prefix {
+            0
+        } else {
+            (n - self.prefix) / self.period
+        }
+    }
+
+    /// The state at step `n`, found by stepping only as far as its equivalent step.
+    pub fn state_at<S>(&self, start: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+        (0..self.equivalent(n)).fold(start, |state, _| step(&state))
+    }
+}
+
+/// Floyd's tortoise and hare: constant memory, about three steps per state visited.
+pub fn floyd<S: Clone, K: Eq>(
+    start: &S,
+    meter: &mut Meter,
+    mut step: impl FnMut(&S) -> S,
+    key: impl Fn(&S) -> K,
+) -> Result<Cycle, Unsolved> {
+    let mut step = |state: &S| meter.tick().map(|_| step(state));
+    let mut tortoise = step(start)?;
+    let mut hare = step(&tortoise)?;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        hare = step(&hare)?;
+    }
+
+    let mut prefix = 0;
+    tortoise = start.clone();
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        prefix += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise)?;
+    while key(&tortoise) != key(&hare) {
+        hare = step(&hare)?;
+        period += 1;
+    }
+    Ok(Cycle { prefix, period })
+}
+
+/// Brent's teleporting tortoise: constant memory, and fewer steps than `floyd`.
+pub fn brent<S: Clone, K: Eq>(
+    start: &S,
+    meter: &mut Meter,
+    mut step: impl FnMut(&S) -> S,
+    key: impl Fn(&S) -> K,
+) -> Result<Cycle, Unsolved> {
+    let mut step = |state: &S| meter.tick().map(|_| step(state));
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start)?;
+    while key(&tortoise) != key(&hare) {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare)?;
+        period += 1;
+    }
+
+    let mut prefix = 0;
+    tortoise = start.clone();
+    hare = (0..period).try_fold(start.clone(), |state, _| step(&state))?;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        prefix += 1;
+    }
+    Ok(Cycle { prefix, period })
+}
+
+/// Remembers the key of every state, so each state is stepped exactly once.
+/// Also returns every state up to the end of the first period, in order, so the state at
+/// step `n` is `states[cycle.equivalent(n)]`.
+pub fn memo<S, K: Hash + Eq>(
+    start: S,
+    meter: &mut Meter,
+    mut step: impl FnMut(&S) -> S,
+    key: impl Fn(&S) -> K,
+) -> Result<(Cycle, Vec<S>), Unsolved> {
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = start;
+    loop {
+        let projected = key(&state);
+        if let Some(&prefix) = seen.get(&projected) {
+            let period = states.len() - prefix;
+            return Ok((Cycle { prefix, period }, states));
+        }
+        seen.insert(projected, states.len());
+        meter.tick()?;
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::budget::Budget;
+    use crate::cycle::*;
+
+    fn meter() -> Meter {
+        Budget::unlimited().start()
+    }
+
+    // 3, 10, 19, 34, 9, then 0, 1, 2, 5, 26, 21, 32 over and over
+    fn step(n: &u32) -> u32 {
+        (n * n + 1) % 41
+    }
+
+    #[test]
+    fn finders_agree() {
+        let expected = Cycle {
+            prefix: 5,
+            period: 7,
+        };
+        assert_eq!(memo(3, &mut meter(), step, |&n| n).unwrap().0, expected);
+        assert_eq!(floyd(&3, &mut meter(), step, |&n| n), Ok(expected));
+        assert_eq!(brent(&3, &mut meter(), step, |&n| n), Ok(expected));
+    }
+
+    #[test]
+    fn never_repeats() {
+        let count = |&n: &u64| n + 1;
+        let mut meter = Budget::steps(100).start();
+        assert!(memo(0, &mut meter, count, |&n| n).is_err());
+        let mut meter = Budget::steps(100).start();
+        assert!(floyd(&0, &mut meter, count, |&n| n).is_err());
+        let mut meter = Budget::steps(100).start();
+        assert!(brent(&0, &mut meter, count, |&n| n).is_err());
+    }
+
+    #[test]
+    fn extrapolate() {
+        let (cycle, states) = memo(3, &mut meter(), step, |&n| n).unwrap();
+        let n = 1_000_000_007;
+        assert_eq!(states[cycle.equivalent(n)], 1);
+        assert_eq!(cycle.state_at(3, step, n), 1);
+        assert_eq!(cycle.equivalent(1), 1);
+        assert_eq!(cycle.periods(cycle.prefix + cycle.period * 3), 3);
+    }
+
+    #[test]
+    fn projection() {
+        // a walker drifting right while its pattern repeats every two steps
+        let start = (0, false);
+        let step = |&(x, odd): &(u64, bool)| (x + 5, !odd);
+        let cycle = brent(&start, &mut meter(), step, |&(_, odd)| odd).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                prefix: 0,
+                period: 2
+            }
+        );
+        let (x, _) = cycle.state_at(start, step, 11);
+        assert_eq!(x + 5 * 2 * cycle.periods(11) as u64, 55);
+    }
+}
@@ -1,9 +1,33 @@
+//! Chronal Calibration: a list of frequency changes.
+//!
+//! `input_generator` parses the changes, and `read_input` and `read_changes` read them from a
+//! stream instead. Part one sums them, and part two finds the first frequency reached twice
+//! while the list repeats: `first_repeat` works it out from the first pass alone, and
+//! `solve_part2_within` walks the passes. `trajectory`, `visited` and `repeated_within` say
+//! more about where the frequency goes than the two answers do.
+use crate::budget::{Budget, Unsolved};
+use crate::error::{lines, read_lines, ParseError, ReadError};
 use aoc_runner_derive::{aoc, aoc_generator};
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::BufRead;
 
 #[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<i32> {
-    input.lines().map(|s| s.parse().unwrap()).collect()
+pub fn input_generator(input: &str) -> Result<Vec<i32>, ParseError> {
+    lines(1, input).map(|line| line.parse(line.text)).collect()
+}
+
+/// The frequency changes read one at a time from `reader`, for summing a list too long to
+/// hold.
+pub fn read_changes<R: BufRead>(reader: R) -> impl Iterator<Item = Result<i32, ReadError>> {
+    read_lines(1, reader, |line| line.parse(line.text))
+}
+
+/// The same as `input_generator`, reading the list from `reader`.
+pub fn read_input<R: BufRead>(reader: R) -> Result<Vec<i32>, ReadError> {
+    read_changes(reader).collect()
 }
 
 #[aoc(day1, part1)]
@@ -11,19 +35,249 @@ pub fn solve_part1(input: &[i32]) -> i32 {
     input.iter().sum()
 }
 
+/// Where part two's first repeated frequency is reached: on which pass through the list,
+/// counting from 0, and after which change in it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Repeat {
+    pub frequency: i64,
+    pub pass: usize,
+    pub index: usize,
+}
+
+/// The first frequency reached twice, found without walking the passes. `None` when no
+/// frequency ever is, and an `Overflow` when the first pass goes beyond an `i64`.
+///
+/// Each pass reaches the first pass's frequencies shifted by the drift, the sum of all the
+/// changes. So `f` from the first pass comes round again `k` passes later if `f + k * drift` is
+/// also among them, which needs the two to have the same residue modulo the drift. Sorting
+/// each residue's frequencies in the direction of the drift, the first repeat is the soonest
+/// of each frequency reaching the next one.
+pub fn first_repeat(input: &[i32]) -> Result<Option<Repeat>, Overflow> {
+    Ok(repeat_among(&first_pass(input)?))
+}
+
+/// The frequencies the first pass reaches.
+fn first_pass(input: &[i32]) -> Result<Vec<i64>, Overflow> {
+    let mut frequency: i64 = 0;
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, &change)| {
+            frequency = frequency
+                .checked_add(i64::from(change))
+                .ok_or(Overflow { pass: 0, index })?;
+            Ok(frequency)
+        })
+        .collect()
+}
+
+/// The frequency after the first `count` changes.
+fn sum_of(input: &[i32], count: usize) -> Result<i64, Overflow> {
+    input[..count]
+        .iter()
+        .enumerate()
+        .try_fold(0_i64, |frequency, (index, &change)| {
+            frequency
+                .checked_add(i64::from(change))
+                .ok_or(Overflow { pass: 0, index })
+        })
+}
+
+/// `first_repeat` given the first pass's `frequencies`.
+fn repeat_among(frequencies: &[i64]) -> Option<Repeat> {
+    let drift = frequencies.last().copied().unwrap_or(0);
+
+    let mut seen = HashSet::new();
+    if let Some(index) = frequencies.iter().position(|&f| !seen.insert(f)) {
+        return Some(Repeat {
+            frequency: frequencies[index],
+            pass: 0,
+            index,
+        });
+    }
+    if drift == 0 {
+        // every pass is the same as the first
+        return frequencies.first().map(|&frequency| Repeat {
+            frequency,
+            pass: 1,
+            index: 0,
+        });
+    }
+
+    let mut residues: HashMap<i64, Vec<usize>> = HashMap::new();
+    for (index, frequency) in frequencies.iter().enumerate() {
+        residues
+            .entry(frequency.rem_euclid(drift))
+            .or_default()
+            .push(index);
+    }
+    residues
+        .values_mut()
+        .flat_map(|indices| {
+            indices.sort_by_key(|&index| frequencies[index]);
+            if drift < 0 {
+                indices.reverse();
+            }
+            indices
+                .windows(2)
+                .map(|pair| Repeat {
+                    frequency: frequencies[pair[1]],
+                    pass: ((frequencies[pair[1]] - frequencies[pair[0]]) / drift) as usize,
+                    index: pair[0],
+                })
+                .collect::<Vec<_>>()
+        })
+        .min_by_key(|repeat| (repeat.pass, repeat.index))
+}
+
 #[aoc(day1, part2)]
-pub fn solve_part2(input: &[i32]) -> i32 {
-    let mut frequency = 0;
+pub fn solve_part2(input: &[i32]) -> Result<i32, Unsolved> {
+    if input.is_empty() {
+        return Err(Unsolved::NoSolution("no frequency changes"));
+    }
+    let repeat = first_repeat(input)
+        .map_err(|_| Unsolved::NoSolution("the frequency overflows an i64"))?
+        .ok_or(Unsolved::NoSolution("no frequency is ever reached twice"))?;
+    i32::try_from(repeat.frequency)
+        .map_err(|_| Unsolved::NoSolution("the repeated frequency is beyond an i32"))
+}
+
+/// Part two by walking pass after pass until a frequency comes round again, giving up after
+/// `budget` passes through the list of changes.
+pub fn solve_part2_within(input: &[i32], budget: Budget) -> Result<i32, Unsolved> {
+    if input.is_empty() {
+        return Err(Unsolved::NoSolution("no frequency changes"));
+    }
+    // every pass shifts the frequencies by the same drift, so a frequency can only come
+    // round again if two of the first pass's frequencies are the same modulo that drift
+    let frequencies =
+        first_pass(input).map_err(|_| Unsolved::NoSolution("the frequency overflows an i64"))?;
+    let drift = frequencies.last().copied().unwrap_or(0);
+    if drift != 0 {
+        let mut residues = HashSet::new();
+        let repeats = frequencies
+            .into_iter()
+            .any(|frequency| !residues.insert(frequency.rem_euclid(drift)));
+        if !repeats {
+            return Err(Unsolved::NoSolution("no frequency is ever reached twice"));
+        }
+    }
+
+    let mut meter = budget.start();
+    let mut frequency: i64 = 0;
     let mut frequencies = HashSet::new();
+    loop {
+        meter.tick()?;
+        for &value in input {
+            frequency = frequency
+                .checked_add(i64::from(value))
+                .ok_or(Unsolved::NoSolution("the frequency overflows an i64"))?;
+            if !frequencies.insert(frequency) {
+                return i32::try_from(frequency)
+                    .map_err(|_| Unsolved::NoSolution("the repeated frequency is beyond an i32"));
+            }
+        }
+    }
+}
+
+/// A frequency beyond what an `i64` holds, reached at change `index` of pass `pass`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Overflow {
+    pub pass: usize,
+    pub index: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "frequency overflows an i64 at change {} of pass {}",
+            self.index, self.pass
+        )
+    }
+}
+
+impl Error for Overflow {}
 
-    for value in input.iter().cycle() {
-        frequency += value;
-        if !frequencies.insert(frequency) {
-            break;
+/// Where the frequency goes over the first pass through the changes, and where it first comes
+/// round again.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Trajectory {
+    /// The lowest frequency reached after any change of the first pass.
+    pub min: i64,
+    /// The highest frequency reached after any change of the first pass.
+    pub max: i64,
+    /// How far each pass moves the frequency: the sum of the changes.
+    pub drift: i64,
+    pub first_repeat: Option<Repeat>,
+}
+
+impl Trajectory {
+    /// How many passes are started before a frequency is reached twice, counting the one it
+    /// happens in.
+    pub fn passes_until_repeat(&self) -> Option<usize> {
+        self.first_repeat.map(|repeat| repeat.pass + 1)
+    }
+}
+
+/// Sums up the first pass through `input`, or `None` for an empty list.
+pub fn trajectory(input: &[i32]) -> Result<Option<Trajectory>, Overflow> {
+    let frequencies = first_pass(input)?;
+    let summary = || {
+        Some(Trajectory {
+            min: *frequencies.iter().min()?,
+            max: *frequencies.iter().max()?,
+            drift: *frequencies.last()?,
+            first_repeat: repeat_among(&frequencies),
+        })
+    };
+    Ok(summary())
+}
+
+/// The frequency after change `index` of pass `pass`, worked out without the passes before.
+/// `None` if `index` is past the end of `input`.
+pub fn frequency_at(input: &[i32], pass: usize, index: usize) -> Result<Option<i64>, Overflow> {
+    if index >= input.len() {
+        return Ok(None);
+    }
+    let drift = sum_of(input, input.len())?;
+    let within = sum_of(input, index + 1)?;
+    i64::try_from(pass)
+        .ok()
+        .and_then(|pass| drift.checked_mul(pass))
+        .and_then(|shift| shift.checked_add(within))
+        .map(Some)
+        .ok_or(Overflow { pass, index })
+}
+
+/// Every frequency the first `passes` passes reach, in order.
+pub fn visited(input: &[i32], passes: usize) -> Result<Vec<i64>, Overflow> {
+    let mut frequencies = Vec::new();
+    let mut frequency: i64 = 0;
+    for pass in 0..passes {
+        for (index, &change) in input.iter().enumerate() {
+            frequency = frequency
+                .checked_add(i64::from(change))
+                .ok_or(Overflow { pass, index })?;
+            frequencies.push(frequency);
         }
     }
+    Ok(frequencies)
+}
 
-    frequency
+/// Every frequency reached at least twice within the first `passes` passes, in the order of
+/// their second visits.
+pub fn repeated_within(input: &[i32], passes: usize) -> Result<Vec<i64>, Overflow> {
+    let mut visits: HashMap<i64, usize> = HashMap::new();
+    let mut repeated = Vec::new();
+    for frequency in visited(input, passes)? {
+        let times = visits.entry(frequency).or_default();
+        *times += 1;
+        if *times == 2 {
+            repeated.push(frequency);
+        }
+    }
+    Ok(repeated)
 }
 
 #[cfg(test)]
@@ -37,7 +291,8 @@ mod tests {
 -2
 +3
 +1",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&test_input);
         assert_eq!(result, 3);
     }
@@ -49,8 +304,98 @@ mod tests {
 -2
 +3
 +1",
-        );
-        let result = solve_part2(&test_input);
+        )
+        .unwrap();
+        let result = solve_part2(&test_input).unwrap();
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn part2_unsolvable() {
+        let test_input = input_generator("+1\n+1").unwrap();
+        assert_eq!(
+            solve_part2(&test_input),
+            Err(Unsolved::NoSolution("no frequency is ever reached twice"))
+        );
+        let test_input = input_generator("+7\n+7\n-2\n-7\n-4").unwrap();
+        assert!(solve_part2_within(&test_input, Budget::steps(2)).is_err());
+        assert_eq!(solve_part2(&test_input), Ok(14));
+    }
+
+    #[test]
+    fn repeats() {
+        let repeat = |input: &str| first_repeat(&input_generator(input).unwrap()).unwrap();
+        let at = |frequency, pass, index| {
+            Some(Repeat {
+                frequency,
+                pass,
+                index,
+            })
+        };
+        assert_eq!(repeat("+1\n-2\n+3\n+1"), at(2, 1, 1));
+        assert_eq!(repeat("+3\n+3\n+4\n-2\n-4"), at(10, 1, 1));
+        assert_eq!(repeat("-6\n+3\n+8\n+5\n-6"), at(5, 2, 1));
+        assert_eq!(repeat("+7\n+7\n-2\n-7\n-4"), at(14, 2, 2));
+        assert_eq!(repeat("+1\n-1"), at(1, 1, 0));
+        assert_eq!(repeat("+2\n-1\n+1"), at(2, 0, 2));
+        assert_eq!(repeat("-1\n-2\n+1"), at(-3, 1, 0));
+        // a million passes in
+        assert_eq!(repeat("+1000000\n-999999"), at(1000000, 999_999, 1));
+        assert_eq!(repeat("+1\n+1"), None);
+        assert_eq!(first_repeat(&[]), Ok(None));
+    }
+
+    #[test]
+    fn repeats_agree_with_walking() {
+        let inputs: [&[i32]; 6] = [
+            &[1, -2, 3, 1],
+            // beyond an i32 partway through the pass
+            &[i32::MAX, i32::MAX, -i32::MAX],
+            &[5, -3, -4, 7, 2, -6],
+            &[-10, 4, 3, 2],
+            &[100, -99, 50, -50],
+            &[3, -7, 2, 5, -4, 1],
+        ];
+        for input in &inputs {
+            let walked = solve_part2_within(input, Budget::unlimited()).ok();
+            let repeated = first_repeat(input)
+                .unwrap()
+                .map(|repeat| repeat.frequency as i32);
+            assert_eq!(repeated, walked);
+        }
+    }
+
+    #[test]
+    fn trajectories() {
+        let input = input_generator("+3\n+3\n+4\n-2\n-4\n-7").unwrap();
+        let summary = trajectory(&input).unwrap().unwrap();
+        assert_eq!((summary.min, summary.max), (-3, 10));
+        assert_eq!(summary.drift, -3);
+        assert_eq!(summary.first_repeat.map(|repeat| repeat.frequency), Some(3));
+        assert_eq!(summary.passes_until_repeat(), Some(2));
+        assert_eq!(
+            visited(&input, 2).unwrap(),
+            vec![3, 6, 10, 8, 4, -3, 0, 3, 7, 5, 1, -6]
+        );
+        assert_eq!(repeated_within(&input, 2).unwrap(), vec![3]);
+        assert_eq!(repeated_within(&input, 3).unwrap(), vec![3, -3, 0, 4]);
+        assert_eq!(frequency_at(&input, 2, 4), Ok(Some(-2)));
+        assert_eq!(frequency_at(&input, 2, 6), Ok(None));
+        assert_eq!(trajectory(&[]), Ok(None));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(
+            frequency_at(&[i32::MAX], 1 << 40, 0),
+            Err(Overflow {
+                pass: 1 << 40,
+                index: 0
+            })
+        );
+        assert_eq!(
+            frequency_at(&[i32::MIN, 1], 1 << 31, 0),
+            Ok(Some(-(1 << 62) + (1 << 31) + i64::from(i32::MIN)))
+        );
+    }
 }
@@ -1,107 +1,189 @@
+//! The Stars Align: moving `Point`s of light that spell out a message.
+//!
+//! `Sky` moves them a second at a time. Part one draws the message and part two says how many
+//! seconds it takes to appear; the `_within` solvers give up after a `Budget`.
+use crate::budget::{Budget, Unsolved};
+use crate::error::{lines, ParseError};
+use crate::geometry::{Bounds, Point2};
+use crate::simulation::Simulation;
 use aoc_runner_derive::{aoc, aoc_generator};
 use regex::Regex;
+use std::fmt;
 
+/// A point of light: where it is and how far it moves each second.
 #[derive(Clone)]
-pub struct Coord {
-    x: isize,
-    y: isize,
-}
-
-#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Point {
-    position: Coord,
-    velocity: Coord,
+    position: Point2,
+    velocity: Point2,
 }
 
 impl Point {
-    fn new(coords: &[isize]) -> Self {
+    const fn new(coords: &[isize]) -> Self {
         Self {
-            position: Coord {
-                x: coords[0],
-                y: coords[1],
-            },
-            velocity: Coord {
-                x: coords[2],
-                y: coords[3],
-            },
+            position: Point2::new([coords[0], coords[1]]),
+            velocity: Point2::new([coords[2], coords[3]]),
         }
     }
 
+    pub const fn position(&self) -> Point2 {
+        self.position
+    }
+
+    pub const fn velocity(&self) -> Point2 {
+        self.velocity
+    }
+
     fn step(&mut self, delta: isize) {
-        self.position.x += delta * self.velocity.x;
-        self.position.y += delta * self.velocity.y;
+        self.position = self.position + self.velocity * delta;
     }
 }
 
-fn get_bounds(points: &[Point]) -> (isize, isize, isize, isize) {
-    let left = points.iter().map(|p| p.position.x).min().unwrap();
-    let right = points.iter().map(|p| p.position.x).max().unwrap();
-    let top = points.iter().map(|p| p.position.y).min().unwrap();
-    let bottom = points.iter().map(|p| p.position.y).max().unwrap();
-    (left, right, top, bottom)
+fn get_bounds(points: &[Point]) -> Bounds<2> {
+    Bounds::of(points.iter().map(|p| p.position)).unwrap()
+}
+
+/// Draws the points as `#` in their bounding box, each character standing for a `scale` by
+/// `scale` square of sky.
+fn draw(points: &[Point], scale: usize) -> String {
+    let bounds = get_bounds(points);
+    let (width, height) = (
+        bounds.span(0).div_ceil(scale),
+        bounds.span(1).div_ceil(scale),
+    );
+    let mut canvas = vec![vec![b' '; width]; height];
+    for point in points {
+        let offset = point.position - bounds.min;
+        canvas[offset.y() as usize / scale][offset.x() as usize / scale] = b'#';
+    }
+    let rows: Vec<String> = canvas
+        .into_iter()
+        .map(|row| row.into_iter().map(char::from).collect())
+        .collect();
+    rows.join("\n")
+}
+
+/// The sky a second at a time, finishing on the second the points are closest together, which
+/// is when they spell out the message.
+#[derive(Clone)]
+pub struct Sky {
+    points: Vec<Point>,
+    seconds: usize,
+}
+
+impl Sky {
+    pub fn new(points: &[Point]) -> Self {
+        Self {
+            points: points.to_vec(),
+            seconds: 0,
+        }
+    }
+
+    pub const fn seconds(&self) -> usize {
+        self.seconds
+    }
+}
+
+impl Simulation for Sky {
+    type Snapshot = Vec<Point>;
+
+    fn step(&mut self) {
+        if !self.is_finished() {
+            for point in &mut self.points {
+                point.step(1);
+            }
+            self.seconds += 1;
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        if self.points.is_empty() {
+            return true;
+        }
+        let mut next = self.points.clone();
+        for point in &mut next {
+            point.step(1);
+        }
+        get_bounds(&next).span(1) > get_bounds(&self.points).span(1)
+    }
+
+    fn snapshot(&self) -> Vec<Point> {
+        self.points.clone()
+    }
+}
+
+/// The points drawn no more than 100 characters across, shrinking the sky to fit while they
+/// are still far apart.
+impl fmt::Display for Sky {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.points.is_empty() {
+            return Ok(());
+        }
+        let bounds = get_bounds(&self.points);
+        let scale = bounds.span(0).max(bounds.span(1)).div_ceil(100).max(1);
+        write!(f, "{}", draw(&self.points, scale))
+    }
 }
 
 #[aoc_generator(day10)]
-pub fn input_generator(input: &str) -> Vec<Point> {
+pub fn input_generator(input: &str) -> Result<Vec<Point>, ParseError> {
     let re = Regex::new(r"position=<[ ]?(.+), [ ]?(.+)> velocity=<[ ]?(.+), [ ]?(.+)>").unwrap();
-    input
-        .lines()
+    lines(10, input)
         .map(|line| {
-            let cap = re.captures(line).unwrap();
-            Point::new(
+            let cap = line.captures(&re)?;
+            Ok(Point::new(
                 &cap.iter()
                     .skip(1)
-                    .map(|c| c.unwrap().as_str().parse::<isize>().unwrap())
-                    .collect::<Vec<isize>>(),
-            )
+                    .map(|c| line.parse(c.unwrap().as_str()))
+                    .collect::<Result<Vec<isize>, ParseError>>()?,
+            ))
         })
         .collect()
 }
 
 #[aoc(day10, part1)]
-pub fn solve_part1(points: &[Point]) -> String {
-    solve(points).0
+pub fn solve_part1(points: &[Point]) -> Result<String, Unsolved> {
+    solve_part1_within(points, Budget::default())
 }
 
 #[aoc(day10, part2)]
-pub fn solve_part2(points: &[Point]) -> usize {
-    solve(points).1
+pub fn solve_part2(points: &[Point]) -> Result<usize, Unsolved> {
+    solve_part2_within(points, Budget::default())
+}
+
+pub fn solve_part1_within(points: &[Point], budget: Budget) -> Result<String, Unsolved> {
+    Ok(solve(points, budget)?.0)
+}
+
+pub fn solve_part2_within(points: &[Point], budget: Budget) -> Result<usize, Unsolved> {
+    Ok(solve(points, budget)?.1)
 }
 
-fn solve(points: &[Point]) -> (String, usize) {
+/// Steps the sky forward until the points stop drawing together, giving up after `budget`
+/// seconds of sky time. Returns the message and when it appeared.
+fn solve(points: &[Point], budget: Budget) -> Result<(String, usize), Unsolved> {
+    if points.is_empty() {
+        return Err(Unsolved::NoSolution("no points of light"));
+    }
+    let mut meter = budget.start();
     let mut output = String::from("\n");
 
     let mut last_delta = 0;
     let mut last_points: Vec<Point> = Vec::new();
 
     for step in 0.. {
+        meter.tick()?;
         let mut mut_points = points.to_vec();
         for point in &mut mut_points {
             point.step(step);
         }
 
-        let (_, _, top, bottom) = get_bounds(&mut_points);
-        let delta = bottom - top;
+        let delta = get_bounds(&mut_points).span(1);
 
         if last_delta != 0 && delta > last_delta {
-            let (left, right, top, bottom) = get_bounds(&last_points);
-            let x = ((right - left) + 1) as usize;
-            let y = ((bottom - top) + 1) as usize;
-
-            let mut canvas = vec![vec![b' '; x]; y];
-            for point in last_points {
-                let y = (point.position.y - top) as usize;
-                let x = (point.position.x - left) as usize;
-                canvas[y][x] = b'#';
-            }
-
-            for row in canvas {
-                for cell in row {
-                    output.push(cell as char);
-                }
-                output.push('\n');
-            }
-            return (output, (step - 1) as usize);
+            output.push_str(&draw(&last_points, 1));
+            output.push('\n');
+            return Ok((output, (step - 1) as usize));
         }
 
         last_delta = delta;
@@ -150,8 +232,9 @@ position=<-6,  0> velocity=< 2,  0>
 position=< 5,  9> velocity=< 1, -2>
 position=<14,  7> velocity=<-2,  0>
 position=<-3,  6> velocity=< 2, -1>",
-        );
-        let result = solve_part1(&input);
+        )
+        .unwrap();
+        let result = solve_part1(&input).unwrap();
         assert_eq!(result, "\n#   #  ###\n#   #   # \n#   #   # \n#####   # \n#   #   # \n#   #   # \n#   #   # \n#   #  ###\n");
     }
 
@@ -189,8 +272,21 @@ position=<-6,  0> velocity=< 2,  0>
 position=< 5,  9> velocity=< 1, -2>
 position=<14,  7> velocity=<-2,  0>
 position=<-3,  6> velocity=< 2, -1>",
-        );
-        let result = solve_part2(&input);
+        )
+        .unwrap();
+        let result = solve_part2(&input).unwrap();
         assert_eq!(result, 3);
+        assert!(solve(&input, Budget::steps(3)).is_err());
+
+        let mut sky = Sky::new(&input);
+        assert_eq!(
+            sky.to_string().lines().next(),
+            Some("        #             ")
+        );
+        while !sky.is_finished() {
+            sky.step();
+        }
+        assert_eq!(sky.seconds(), 3);
+        assert_eq!(format!("\n{}\n", sky), solve_part1(&input).unwrap());
     }
 }
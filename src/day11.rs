@@ -1,7 +1,23 @@
+//! Chronal Charge: a grid of fuel cells whose power comes from a serial number.
+//!
+//! The solvers take the serial number as text, which `parse_serial` reads. Part one is the
+//! top-left cell of the 3x3 square with the most power, and part two the same for a square of
+//! any size, followed by the size. The `_with_witness` solvers also return that `Square` and
+//! its power.
+use crate::error::{lines, ParseError};
+use crate::parallel;
 use aoc_runner_derive::aoc;
+use std::fmt;
 
 const GRID_SIZE: usize = 300;
 
+pub fn parse_serial(input: &str) -> Result<usize, ParseError> {
+    let line = lines(11, input)
+        .next()
+        .ok_or_else(|| ParseError::new(11, 1, 1, "", "missing serial number"))?;
+    line.parse(line.text.trim())
+}
+
 fn get_power(x: usize, y: usize, serial: usize) -> isize {
     let rack_id = x + 10;
     let mut power = rack_id * y;
@@ -11,9 +27,34 @@ fn get_power(x: usize, y: usize, serial: usize) -> isize {
     power as isize - 5
 }
 
+/// A square of fuel cells, by its top-left cell, and the power of the cells in it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Square {
+    pub x: usize,
+    pub y: usize,
+    pub size: usize,
+    pub power: isize,
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the {}x{} square at {},{} has {} power",
+            self.size, self.size, self.x, self.y, self.power
+        )
+    }
+}
+
 #[aoc(day11, part1)]
-pub fn solve_part1(input: &str) -> String {
-    let serial: usize = input.parse().unwrap();
+pub fn solve_part1(input: &str) -> Result<String, ParseError> {
+    let (answer, _) = solve_part1_with_witness(input)?;
+    Ok(answer)
+}
+
+/// Part one's answer along with the square it names.
+pub fn solve_part1_with_witness(input: &str) -> Result<(String, Square), ParseError> {
+    let serial = parse_serial(input)?;
     let size = 3;
     let mut grid = [[0_isize; GRID_SIZE]; GRID_SIZE];
     for (x, x_val) in grid.iter_mut().enumerate().take(GRID_SIZE) {
@@ -35,13 +76,26 @@ pub fn solve_part1(input: &str) -> String {
             }
         }
     }
-    format!("{},{}", result.0, result.1)
+    let square = Square {
+        x: result.0,
+        y: result.1,
+        size,
+        power: best_power,
+    };
+    Ok((format!("{},{}", result.0, result.1), square))
 }
 
-/// https://en.wikipedia.org/wiki/Summed-area_table
 #[aoc(day11, part2)]
-pub fn solve_part2(input: &str) -> String {
-    let serial: usize = input.parse().unwrap();
+pub fn solve_part2(input: &str) -> Result<String, ParseError> {
+    let (answer, _) = solve_part2_with_witness(input)?;
+    Ok(answer)
+}
+
+/// Part two's answer along with the square it names.
+///
+/// <https://en.wikipedia.org/wiki/Summed-area_table>
+pub fn solve_part2_with_witness(input: &str) -> Result<(String, Square), ParseError> {
+    let serial = parse_serial(input)?;
     let mut grid = [[0_isize; GRID_SIZE]; GRID_SIZE];
     for (x, x_val) in grid.iter_mut().enumerate().take(GRID_SIZE) {
         for (y, y_val) in x_val.iter_mut().enumerate().take(GRID_SIZE) {
@@ -61,9 +115,11 @@ pub fn solve_part2(input: &str) -> String {
         }
     }
 
-    let mut best_sum = 0;
-    let mut result = (0, 0, 0);
-    for x in 1..GRID_SIZE {
+    // the best square for each column, keeping the first of any ties as a serial scan would
+    let columns: Vec<usize> = (1..GRID_SIZE).collect();
+    let best = parallel::map(&columns, |&x| {
+        let mut best_sum = 0;
+        let mut result = (0, 0, 0);
         for y in 1..GRID_SIZE {
             let min_size = (GRID_SIZE - (x + 1)).min(GRID_SIZE - (y + 1));
             for size in 1..=min_size {
@@ -76,8 +132,22 @@ pub fn solve_part2(input: &str) -> String {
                 }
             }
         }
-    }
-    format!("{},{},{}", result.0, result.1, result.2)
+        (best_sum, result)
+    });
+    let (power, result) = best.into_iter().fold((0, (0, 0, 0)), |best, column| {
+        if column.0 > best.0 {
+            column
+        } else {
+            best
+        }
+    });
+    let square = Square {
+        x: result.0,
+        y: result.1,
+        size: result.2,
+        power,
+    };
+    Ok((format!("{},{},{}", result.0, result.1, result.2), square))
 }
 
 #[cfg(test)]
@@ -87,12 +157,31 @@ mod tests {
     #[test]
     fn part1() {
         let result = solve_part1("42");
-        assert_eq!(result, "21,61");
+        assert_eq!(result.unwrap(), "21,61");
     }
 
     #[test]
     fn part2() {
         let result = solve_part2("18");
-        assert_eq!(result, "90,269,16");
+        assert_eq!(result.unwrap(), "90,269,16");
+    }
+
+    #[test]
+    fn witnesses() {
+        let (_, square) = solve_part1_with_witness("18").unwrap();
+        assert_eq!(
+            square,
+            Square {
+                x: 33,
+                y: 45,
+                size: 3,
+                power: 29
+            }
+        );
+        let (_, square) = solve_part2_with_witness("42").unwrap();
+        assert_eq!(
+            square.to_string(),
+            "the 12x12 square at 232,251 has 119 power"
+        );
     }
 }
@@ -1,18 +1,34 @@
+//! Subterranean Sustainability: a row of pots and the rules for which ones grow plants next.
+//!
+//! `Pots` steps the row a generation at a time. Part one sums the numbers of the pots with
+//! plants after 20 generations, and part two after fifty billion, once the pattern only
+//! drifts.
+use crate::budget::{Budget, Unsolved};
+use crate::cycle::floyd;
+use crate::error::{lines, ParseError};
+use crate::simulation::{Runner, Simulation};
 use aoc_runner_derive::{aoc, aoc_generator};
 use hashbrown::HashSet;
 use regex::Regex;
 use std::str;
 
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Data {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::dump::sorted"))]
     pub state: HashSet<isize>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::dump::sorted"))]
     pub rules: HashSet<Vec<u8>>,
 }
 
 #[aoc_generator(day12)]
-pub fn input_generator(input: &str) -> Box<Data> {
-    let mut re = Regex::new("initial state: (.+)+\n").unwrap();
-    let caps = re.captures(input).unwrap();
+pub fn input_generator(input: &str) -> Result<Box<Data>, ParseError> {
+    let mut lines = lines(12, input);
+    let mut re = Regex::new("initial state: ([#.]+)").unwrap();
+    let line = lines
+        .next()
+        .ok_or_else(|| ParseError::new(12, 1, 1, "", "missing initial state"))?;
+    let caps = line.captures(&re)?;
 
     let mut data = Data::default();
     for (index, pot) in caps[1].chars().enumerate() {
@@ -21,22 +37,25 @@ pub fn input_generator(input: &str) -> Box<Data> {
         }
     }
 
-    re = Regex::new("(.+)+ => (#|.)").unwrap();
-    for rule in input.lines().skip(1) {
-        if rule.is_empty() {
+    re = Regex::new("^([#.]{5}) => ([#.])$").unwrap();
+    for rule in lines {
+        if rule.text.is_empty() {
             continue;
         }
-        let caps = re.captures(rule).unwrap();
+        let caps = rule.captures(&re)?;
         if &caps[2] == "#" {
             data.rules.insert(caps[1].as_bytes().to_vec());
         }
     }
-    Box::new(data)
+    Ok(Box::new(data))
 }
 
 fn next_state(state: &HashSet<isize>, rules: &HashSet<Vec<u8>>) -> HashSet<isize> {
-    let min = *state.iter().min().unwrap() as isize - 3;
-    let max = *state.iter().max().unwrap() as isize + 3;
+    let (min, max) = match (state.iter().min(), state.iter().max()) {
+        (Some(min), Some(max)) => (min - 3, max + 3),
+        // with no plants left, none can grow back
+        _ => return HashSet::new(),
+    };
 
     let mut set: HashSet<isize> = HashSet::new();
     for i in min..=max {
@@ -57,39 +76,86 @@ fn next_state(state: &HashSet<isize>, rules: &HashSet<Vec<u8>>) -> HashSet<isize
     set
 }
 
-fn normalize(state: &HashSet<isize>) -> HashSet<isize> {
-    let min = state.iter().min().unwrap();
-    let mut new_state = HashSet::new();
-    for value in state {
-        new_state.insert(value - min);
-    }
-    new_state
+/// The pots a generation at a time. Finished once no pot has a plant, as nothing can grow
+/// back from there.
+#[derive(Clone)]
+pub struct Pots<'a> {
+    state: HashSet<isize>,
+    rules: &'a HashSet<Vec<u8>>,
+    generation: usize,
 }
 
-#[aoc(day12, part1)]
-pub fn solve_part1(data: &Data) -> usize {
-    let mut state = data.state.clone();
-    for _ in 0..20 {
-        state = next_state(&state, &data.rules);
+impl<'a> Pots<'a> {
+    pub fn new(data: &'a Data) -> Self {
+        Self {
+            state: data.state.clone(),
+            rules: &data.rules,
+            generation: 0,
+        }
+    }
+
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// The sum of the numbers of the pots with plants.
+    pub fn sum(&self) -> isize {
+        self.state.iter().sum()
     }
-    state.iter().sum::<isize>() as usize
 }
 
-#[aoc(day12, part2)]
-pub fn solve_part2(data: &Data) -> usize {
-    let mut state = data.state.clone();
-    let mut last_state = HashSet::new();
-    for generation in 0_usize..50_000_000_000 {
-        state = next_state(&state, &data.rules);
-        if !last_state.is_empty() && normalize(&last_state) == normalize(&state) {
-            let a: usize = state.iter().map(|&x| x as usize).sum();
-            let b: usize = last_state.iter().map(|&x| x as usize).sum();
-            let diff = a - b;
-            return a + diff * (50_000_000_000 - generation) - diff;
+impl Simulation for Pots<'_> {
+    /// The numbers of the pots with plants, in order.
+    type Snapshot = Vec<isize>;
+
+    fn step(&mut self) {
+        if !self.is_finished() {
+            self.state = next_state(&self.state, self.rules);
+            self.generation += 1;
         }
-        last_state = state.clone();
     }
-    unreachable!();
+
+    fn is_finished(&self) -> bool {
+        self.state.is_empty()
+    }
+
+    fn snapshot(&self) -> Vec<isize> {
+        let mut pots: Vec<isize> = self.state.iter().cloned().collect();
+        pots.sort();
+        pots
+    }
+}
+
+/// The pattern of pots, wherever it has drifted to.
+fn normalize(state: &HashSet<isize>) -> Vec<isize> {
+    let min = state.iter().min().copied().unwrap_or_default();
+    let mut pattern: Vec<isize> = state.iter().map(|value| value - min).collect();
+    pattern.sort();
+    pattern
+}
+
+#[aoc(day12, part1)]
+pub fn solve_part1(data: &Data) -> isize {
+    Runner::new(Pots::new(data)).run_for(20).sum()
+}
+
+#[aoc(day12, part2)]
+pub fn solve_part2(data: &Data) -> Result<isize, Unsolved> {
+    solve_part2_within(data, Budget::default())
+}
+
+/// Part two, giving up after stepping `budget` generations without the pattern repeating.
+pub fn solve_part2_within(data: &Data, budget: Budget) -> Result<isize, Unsolved> {
+    let generations = 50_000_000_000;
+    let step = |state: &HashSet<isize>| next_state(state, &data.rules);
+    let cycle = floyd(&data.state, &mut budget.start(), step, normalize)?;
+
+    // the pattern repeats but drifts along, so the sum grows by the same amount each period
+    let state = cycle.state_at(data.state.clone(), step, generations);
+    let later = (0..cycle.period).fold(state.clone(), |state, _| step(&state));
+    let sum: isize = state.iter().sum();
+    let drift = later.iter().sum::<isize>() - sum;
+    Ok(sum + drift * cycle.periods(generations) as isize)
 }
 
 #[cfg(test)]
@@ -114,11 +180,41 @@ mod tests {
 ###.. => #
 ###.# => #
 ####. => #",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 325);
     }
 
+    #[test]
+    fn generations() {
+        let input = input_generator(
+            "initial state: #..#.#..##......###...###
+...## => #
+..#.. => #
+.#... => #
+.#.#. => #
+.#.## => #
+.##.. => #
+.#### => #
+#.#.# => #
+#.### => #
+##.#. => #
+##.## => #
+###.. => #
+###.# => #
+####. => #",
+        )
+        .unwrap();
+        let mut counts = Vec::new();
+        let pots = Runner::new(Pots::new(&input))
+            .observe(|_, pots| counts.push(pots.snapshot().len()))
+            .run_for(2);
+        assert_eq!(pots.generation(), 2);
+        assert_eq!(pots.snapshot(), vec![0, 1, 4, 5, 9, 10, 15, 18, 21, 24, 25]);
+        assert_eq!(counts, vec![11, 7, 11]);
+    }
+
     #[test]
     fn part_2() {
         let input = input_generator(
@@ -137,8 +233,31 @@ mod tests {
 ###.. => #
 ###.# => #
 ####. => #",
-        );
+        )
+        .unwrap();
         let result = solve_part2(&input);
-        assert_eq!(result, 999999999374);
+        assert_eq!(result, Ok(999999999374));
+    }
+
+    #[test]
+    fn dying_out() {
+        let input = input_generator("initial state: #..#\n\n..... => .").unwrap();
+        assert_eq!(solve_part1(&input), 0);
+        assert_eq!(solve_part2(&input), Ok(0));
+    }
+
+    #[test]
+    fn never_settling() {
+        // any plant within one pot spreads, so the row only ever grows
+        let mut input = "initial state: #\n".to_owned();
+        for pattern in 0..32 {
+            let pots: String = (0..5)
+                .map(|i| if pattern >> i & 1 == 1 { '#' } else { '.' })
+                .collect();
+            let grows = pots[1..4].contains('#');
+            input.push_str(&format!("\n{} => {}", pots, if grows { '#' } else { '.' }));
+        }
+        let input = input_generator(&input).unwrap();
+        assert!(solve_part2_within(&input, Budget::steps(1000)).is_err());
     }
 }
@@ -1,7 +1,15 @@
+//! Mine Cart Madness: carts on a `TileMap` of track.
+//!
+//! `Carts` moves them a tick at a time. Part one is where the first crash happens, and part
+//! two where the last cart is once every crashed cart has been removed.
+use crate::error::ParseError;
+use crate::grid::Grid;
+use crate::simulation::{Runner, Simulation};
 use aoc_runner_derive::{aoc, aoc_generator};
-use hashbrown::HashMap;
+use std::fmt;
 
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 enum Direction {
     North,
     East,
@@ -10,6 +18,7 @@ enum Direction {
 }
 
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 enum Turn {
     Left,
     Straight,
@@ -17,7 +26,8 @@ enum Turn {
 }
 
 #[derive(Clone, PartialEq)]
-struct Cart {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Cart {
     x: usize,
     y: usize,
     direction: Direction,
@@ -35,50 +45,45 @@ impl Cart {
     }
 }
 
-type TileMap = HashMap<usize, HashMap<usize, u8>>;
-type CartVec = Vec<Cart>;
+pub type TileMap = Grid<u8>;
+pub type CartVec = Vec<Cart>;
 
 #[aoc_generator(day13)]
-fn input_generator(_input: &str) -> (CartVec, TileMap) {
-    let input = include_str!("../input/2018/day13.txt"); // cargo-aoc trims input
+pub fn input_generator(input: &str) -> Result<(CartVec, TileMap), ParseError> {
     let mut carts: CartVec = Vec::new();
-    let mut tiles: TileMap = HashMap::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, byte) in line.as_bytes().iter().enumerate() {
-            let tile = match byte {
-                b'^' => {
-                    carts.push(Cart::new(x, y, Direction::North, Turn::Left));
-                    b'|'
-                }
-                b'<' => {
-                    carts.push(Cart::new(x, y, Direction::West, Turn::Left));
-                    b'-'
-                }
-                b'v' => {
-                    carts.push(Cart::new(x, y, Direction::South, Turn::Left));
-                    b'|'
-                }
-                b'>' => {
-                    carts.push(Cart::new(x, y, Direction::East, Turn::Left));
-                    b'-'
-                }
-                b' ' => continue,
-                _ => *byte,
-            };
-            let entry = tiles.entry(x).or_default();
-            entry.insert(y, tile);
-        }
-    }
-    (carts, tiles)
+    let tiles = Grid::parse(13, input, "unknown track piece", |(x, y), c| {
+        let tile = match c {
+            '^' => {
+                carts.push(Cart::new(x, y, Direction::North, Turn::Left));
+                b'|'
+            }
+            '<' => {
+                carts.push(Cart::new(x, y, Direction::West, Turn::Left));
+                b'-'
+            }
+            'v' => {
+                carts.push(Cart::new(x, y, Direction::South, Turn::Left));
+                b'|'
+            }
+            '>' => {
+                carts.push(Cart::new(x, y, Direction::East, Turn::Left));
+                b'-'
+            }
+            ' ' | '|' | '-' | '/' | '\\' | '+' => c as u8,
+            _ => return None,
+        };
+        Some(tile)
+    })?;
+    Ok((carts, tiles))
 }
 
 #[aoc(day13, part1)]
-fn solve_part1((carts, tiles): &(CartVec, TileMap)) -> String {
+pub fn solve_part1((carts, tiles): &(CartVec, TileMap)) -> String {
     solve(carts, tiles, false)
 }
 
 #[aoc(day13, part2)]
-fn solve_part2((carts, tiles): &(CartVec, TileMap)) -> String {
+pub fn solve_part2((carts, tiles): &(CartVec, TileMap)) -> String {
     solve(carts, tiles, true)
 }
 
@@ -112,9 +117,39 @@ fn get_direction(direction: &Direction, byte: u8) -> Direction {
     }
 }
 
-fn solve(carts: &[Cart], tiles: &TileMap, should_remove: bool) -> String {
-    let mut carts = carts.to_owned();
-    loop {
+/// The carts a tick at a time. In the first part they run until the first crash; when
+/// crashed carts are removed, they run until only one is left, or none if they all crash.
+#[derive(Clone)]
+pub struct Carts {
+    carts: CartVec,
+    tiles: TileMap,
+    remove_crashed: bool,
+    /// Where the first crash happened, or where the last cart is.
+    outcome: Option<(usize, usize)>,
+    ticks: usize,
+}
+
+impl Carts {
+    pub fn new(carts: &[Cart], tiles: &TileMap, remove_crashed: bool) -> Self {
+        Self {
+            carts: carts.to_owned(),
+            tiles: tiles.to_owned(),
+            remove_crashed,
+            outcome: None,
+            ticks: 0,
+        }
+    }
+
+    pub const fn outcome(&self) -> Option<(usize, usize)> {
+        self.outcome
+    }
+
+    pub const fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    fn tick(&mut self) {
+        let carts = &mut self.carts;
         carts.sort_by_key(|cart| cart.y);
         for (index, cart) in carts.clone().into_iter().enumerate() {
             if carts[index].x == std::usize::MAX {
@@ -128,7 +163,7 @@ fn solve(carts: &[Cart], tiles: &TileMap, should_remove: bool) -> String {
                 Direction::West => (cart.x - 1, cart.y),
             };
 
-            let (direction, next_turn) = match tiles[&x][&y] {
+            let (direction, next_turn) = match self.tiles[(x, y)] {
                 b'/' => (get_direction(&cart.direction, b'/'), cart.next_turn.clone()),
                 b'\\' => (
                     get_direction(&cart.direction, b'\\'),
@@ -144,12 +179,13 @@ fn solve(carts: &[Cart], tiles: &TileMap, should_remove: bool) -> String {
 
             for (other_index, other_cart) in carts.iter_mut().enumerate() {
                 if index != other_index && other_cart.x == x && other_cart.y == y {
-                    if should_remove {
+                    if self.remove_crashed {
                         other_cart.x = std::usize::MAX;
                         x = std::usize::MAX;
                         break;
                     } else {
-                        return format!("{},{}", x, y);
+                        self.outcome = Some((x, y));
+                        return;
                     }
                 }
             }
@@ -157,13 +193,112 @@ fn solve(carts: &[Cart], tiles: &TileMap, should_remove: bool) -> String {
             carts[index] = Cart::new(x, y, direction, next_turn);
         }
 
-        carts = carts
-            .into_iter()
-            .filter(|cart| cart.x != std::usize::MAX)
-            .collect();
+        carts.retain(|cart| cart.x != std::usize::MAX);
+        if self.remove_crashed && carts.len() == 1 {
+            self.outcome = Some((carts[0].x, carts[0].y));
+        }
+    }
+}
+
+impl Simulation for Carts {
+    /// The carts still on the track.
+    type Snapshot = CartVec;
+
+    fn step(&mut self) {
+        if !self.is_finished() {
+            self.tick();
+            self.ticks += 1;
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.outcome.is_some() || self.carts.is_empty()
+    }
+
+    fn snapshot(&self) -> CartVec {
+        self.carts
+            .iter()
+            .filter(|cart| cart.x != usize::MAX)
+            .cloned()
+            .collect()
+    }
+}
 
-        if should_remove && carts.len() == 1 {
-            return format!("{},{}", carts[0].x, carts[0].y);
+/// The track with the carts drawn on it, and an `X` where the first crash happened.
+impl fmt::Display for Carts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut tiles = self.tiles.clone();
+        for cart in self.snapshot() {
+            tiles[(cart.x, cart.y)] = match cart.direction {
+                Direction::North => b'^',
+                Direction::East => b'>',
+                Direction::South => b'v',
+                Direction::West => b'<',
+            };
+        }
+        if let (false, Some(crash)) = (self.remove_crashed, self.outcome) {
+            tiles[crash] = b'X';
         }
+        write!(f, "{}", tiles.render(|&tile| tile as char))
+    }
+}
+
+fn solve(carts: &[Cart], tiles: &TileMap, remove_crashed: bool) -> String {
+    match Runner::new(Carts::new(carts, tiles, remove_crashed))
+        .run()
+        .outcome()
+    {
+        Some((x, y)) => format!("{},{}", x, y),
+        None => "every cart crashed".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day13::*;
+
+    #[test]
+    fn part1() {
+        let input = input_generator(
+            r"/->-\        
+|   |  /----\
+| /-+--+-\  |
+| | |  | v  |
+\-+-/  \-+--/
+  \------/   ",
+        )
+        .unwrap();
+        assert_eq!(solve_part1(&input), "7,3");
+    }
+
+    #[test]
+    fn part2() {
+        let input = input_generator(
+            r"/>-<\  
+|   |  
+| /<+-\
+| | | v
+\>+</ |
+  |   ^
+  \<->/",
+        )
+        .unwrap();
+        assert_eq!(solve_part2(&input), "6,4");
+    }
+
+    #[test]
+    fn ticks() {
+        let tiles = Grid::parse(13, "------", "", |_, c| Some(c as u8)).unwrap();
+        let carts = vec![
+            Cart::new(1, 0, Direction::East, Turn::Left),
+            Cart::new(4, 0, Direction::West, Turn::Left),
+        ];
+        let mut rendered = Vec::new();
+        let carts = Runner::new(Carts::new(&carts, &tiles, false))
+            .observe(|_, carts| rendered.push(carts.to_string()))
+            .run();
+        assert_eq!(carts.outcome(), Some((3, 0)));
+        assert_eq!(carts.ticks(), 2);
+        assert_eq!(rendered, vec!["->--<-", "--><--", "-->X--"]);
     }
 }
@@ -1,14 +1,22 @@
+//! Chocolate Charts: two elves making recipes and writing their scores on a scoreboard.
+//!
+//! `input_generator` reads the puzzle's number. Part one is the ten scores after that many
+//! recipes, and part two how many recipes come before its digits appear.
+use crate::error::{lines, ParseError};
 use aoc_runner_derive::{aoc, aoc_generator};
 
 const NUM_RECIPES: usize = 10;
 
 #[aoc_generator(day14)]
-fn input_generator(input: &str) -> usize {
-    input.parse().unwrap()
+pub fn input_generator(input: &str) -> Result<usize, ParseError> {
+    let line = lines(14, input)
+        .next()
+        .ok_or_else(|| ParseError::new(14, 1, 1, "", "missing recipe count"))?;
+    line.parse(line.text.trim())
 }
 
 #[aoc(day14, part1)]
-fn solve_part1(input: &usize) -> String {
+pub fn solve_part1(input: &usize) -> String {
     let mut recipes = vec![3, 7];
     let (mut elf_one, mut elf_two) = (0, 1);
     while recipes.len() < (input + NUM_RECIPES) {
@@ -34,7 +42,7 @@ fn solve_part1(input: &usize) -> String {
 }
 
 #[aoc(day14, part2)]
-fn solve_part2(input: &usize) -> usize {
+pub fn solve_part2(input: &usize) -> usize {
     let mut recipes = vec![3, 7];
     let (mut elf_one, mut elf_two) = (0, 1);
     let target_recipe = input
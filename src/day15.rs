@@ -1,33 +1,47 @@
+//! Beverage Bandits: elves and goblins fighting across the `Game`'s cave map.
+//!
+//! `Battle` is the combat engine, fighting a game a round at a time with any attack power for
+//! the elves. Part one is the outcome with the usual attack of 3, and part two the outcome
+//! with the least attack that keeps every elf alive.
+use crate::budget::Budget;
+use crate::error::ParseError;
+use crate::grid::{reading_order, Grid, Position};
+use crate::parallel;
+use crate::simulation::{Runner, Simulation};
 use aoc_runner_derive::{aoc, aoc_generator};
 use hashbrown::{HashMap, HashSet};
 use std::collections::VecDeque;
+use std::fmt;
 
-#[derive(Clone, Hash, Eq, PartialEq, Debug, PartialOrd, Ord)]
-struct Point(isize, isize);
-impl Point {
-    fn neighbours(&self) -> Vec<Point> {
-        let &Point(x, y) = self;
-        vec![
-            Point(x, y - 1),
-            Point(x - 1, y),
-            Point(x + 1, y),
-            Point(x, y + 1),
-        ]
-    }
-}
-
-#[derive(Clone, Debug, Default)]
-struct Game {
-    units: HashMap<Point, usize>,
-    world: HashMap<Point, u8>,
+/// The cave map with every unit's hit points: `#` for walls, `.` for open cavern, and `G`
+/// and `E` for goblins and elves.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Game {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::dump::entries"))]
+    units: HashMap<Position, usize>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::dump::tiles"))]
+    world: Grid<u8>,
 }
 
 impl Game {
-    fn attack_unit_at_point(&mut self, point: &Point, attack: usize) -> bool {
+    /// Every unit still standing, as its position, `G` or `E`, and hit points, in reading
+    /// order.
+    pub fn units(&self) -> Vec<(Position, char, usize)> {
+        let mut units: Vec<_> = self
+            .units
+            .iter()
+            .map(|(&position, &health)| (position, self.world[position] as char, health))
+            .collect();
+        units.sort_by(|a, b| reading_order(&a.0, &b.0));
+        units
+    }
+
+    fn attack_unit_at_point(&mut self, point: &Position, attack: usize) -> bool {
         let unit_health = self.units.get_mut(point).unwrap();
         if *unit_health <= attack {
             self.units.remove_entry(point);
-            self.world.insert(point.clone(), b'.');
+            self.world[*point] = b'.';
             true
         } else {
             *unit_health -= attack;
@@ -35,246 +49,273 @@ impl Game {
         }
     }
 
-    fn find_direct_target(&self, point: &Point) -> Option<Point> {
+    fn find_direct_target(&self, point: &Position) -> Option<Position> {
         let mut lowest_health = std::usize::MAX;
-        let mut target: Option<Point> = None;
-        let source_tile = self.world.get(point).unwrap();
-        for position in point.neighbours() {
-            if !self.units.contains_key(&position) {
-                continue;
-            }
-            let target_tile = self.world.get(&position).unwrap();
-            if source_tile == target_tile {
+        let mut target: Option<Position> = None;
+        let source_tile = self.world[*point];
+        for position in self.world.neighbours4(*point) {
+            let health = match self.units.get(&position) {
+                Some(health) => *health,
+                None => continue,
+            };
+            if source_tile == self.world[position] {
                 continue; // Not an enemy
             }
-            let health = self.units.get(&position).unwrap();
-            if target.is_none() || *health < lowest_health {
-                lowest_health = *health;
+            if target.is_none() || health < lowest_health {
+                lowest_health = health;
                 target = Some(position);
             }
         }
         target
     }
 
-    fn find_next_point(&self, source: &Point) -> Option<Point> {
-        let units: Vec<Point> = self.units.keys().cloned().filter(|k| k != source).collect();
-        if units.is_empty() {
-            return None;
-        }
-
+    fn find_next_point(&self, source: &Position) -> Option<Position> {
         // get all the points where we want to end up on
         let mut goals = HashSet::new();
-        let source_tile = self.world.get(source).unwrap();
-        let enemies = units
-            .into_iter()
-            .filter(|k| self.world.get(k).unwrap() != source_tile);
+        let source_tile = self.world[*source];
+        let enemies = self
+            .units
+            .keys()
+            .filter(|&k| k != source && self.world[*k] != source_tile);
         for enemy in enemies {
-            for neighbour in enemy.neighbours() {
-                if self.world.contains_key(&neighbour)
-                    && *self.world.get(&neighbour).unwrap() == b'.'
-                {
-                    goals.insert(neighbour.clone());
+            for neighbour in self.world.neighbours4(*enemy) {
+                if self.world[neighbour] == b'.' {
+                    goals.insert(neighbour);
                 }
             }
         }
+        if goals.is_empty() {
+            return None;
+        }
 
         // traverse outwards from our source point
         let mut queue = VecDeque::new();
-        let mut parents: HashMap<Point, Point> = HashMap::new();
-        queue.push_back(source.clone());
-        parents.insert(source.clone(), Point(-1, -1));
+        let mut parents: Grid<Option<Position>> =
+            Grid::new(self.world.width(), self.world.height(), None);
+        queue.push_back(*source);
+        parents[*source] = Some(*source);
         while let Some(point) = queue.pop_front() {
-            for neighbour in point.neighbours() {
-                if parents.contains_key(&neighbour)
-                    || (self.world.contains_key(&neighbour)
-                        && *self.world.get(&neighbour).unwrap() != b'.')
-                {
+            for neighbour in self.world.neighbours4(point) {
+                if parents[neighbour].is_some() || self.world[neighbour] != b'.' {
                     continue;
                 }
-                queue.push_back(neighbour.clone());
-                parents.insert(neighbour.clone(), point.clone());
+                queue.push_back(neighbour);
+                parents[neighbour] = Some(point);
             }
         }
 
-        // find paths that lead to our goals
-        let mut possible_paths: Vec<Vec<Point>> = goals
-            .iter()
-            .filter(|k| parents.contains_key(k))
-            .map(|k| {
-                let mut steps = Vec::new();
-                let mut point = k.clone();
-                while point != *source {
-                    steps.push(point.clone());
-                    point = parents.get(&point).unwrap().clone();
+        // find the nearest goal, by reading order, and the first step towards it
+        goals
+            .into_iter()
+            .filter(|&goal| parents[goal].is_some())
+            .map(|goal| {
+                let mut length = 1;
+                let mut point = goal;
+                while let Some(parent) = parents[point].filter(|parent| parent != source) {
+                    point = parent;
+                    length += 1;
                 }
-                steps.reverse();
-                steps
+                (length, goal, point)
             })
-            .collect();
-        if possible_paths.is_empty() {
-            return None;
-        }
-
-        // grab the best path by reading order
-        possible_paths.sort_by_key(|p| p.len());
-        let min_length = possible_paths[0].len();
-        let mut best_paths: Vec<Vec<Point>> = possible_paths
-            .into_iter()
-            .filter(|p| p.len() == min_length)
-            .collect();
-        best_paths.sort_by_key(|p| Point(p.last().unwrap().1, p.last().unwrap().0));
-        let best_path = best_paths[0].clone();
-        Some(best_path[0].clone())
+            .min_by(|a, b| a.0.cmp(&b.0).then_with(|| reading_order(&a.1, &b.1)))
+            .map(|(_, _, step)| step)
     }
 
-    fn move_from(&mut self, source: &Point, target: &Point) {
+    fn move_from(&mut self, source: &Position, target: &Position) {
         let (_, health) = self.units.remove_entry(source).unwrap();
-        let unit_type = self.world.get(source).unwrap();
-        self.world.insert(target.clone(), *unit_type);
-        self.world.insert(source.clone(), b'.');
-        self.units.insert(target.clone(), health);
+        self.world[*target] = self.world[*source];
+        self.world[*source] = b'.';
+        self.units.insert(*target, health);
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.world.render(|&tile| tile as char))
     }
 }
 
 #[aoc_generator(day15)]
-fn input_generator(input: &str) -> Game {
-    let mut state = Game::default();
-    for (y, line) in input.lines().enumerate() {
-        for (x, symbol) in line.as_bytes().iter().enumerate() {
-            let tile = match symbol {
-                b'G' | b'E' => {
-                    state.units.insert(Point(x as isize, y as isize), 200);
-                    symbol
+pub fn input_generator(input: &str) -> Result<Game, ParseError> {
+    let mut units = HashMap::new();
+    let world = Grid::parse(15, input, "unknown map tile", |position, symbol| {
+        match symbol {
+            'G' | 'E' => {
+                units.insert(position, 200);
+            }
+            '#' | '.' => {}
+            _ => return None,
+        };
+        Some(symbol as u8)
+    })?;
+    Ok(Game { units, world })
+}
+
+/// How a battle ended.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// One side is left, with this outcome: full rounds times the hit points remaining.
+    Won(usize),
+    /// An elf died when elves weren't allowed to.
+    ElfDied,
+}
+
+/// The battle a round at a time. A unit that finds no enemies left ends the battle part way
+/// through a round, which then doesn't count.
+#[derive(Clone)]
+pub struct Battle {
+    game: Game,
+    elf_attack: usize,
+    spare_elves: bool,
+    rounds: usize,
+    outcome: Option<Outcome>,
+}
+
+impl Battle {
+    /// Goblins always attack with 3. With `spare_elves`, the battle is lost as soon as an
+    /// elf dies.
+    pub fn new(game: &Game, elf_attack: usize, spare_elves: bool) -> Self {
+        Self {
+            game: game.to_owned(),
+            elf_attack,
+            spare_elves,
+            rounds: 0,
+            outcome: None,
+        }
+    }
+
+    /// How the battle ended, once it has.
+    pub const fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// The cave as the battle has left it so far.
+    pub const fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// How many full rounds have been fought.
+    pub const fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Attacks the weakest enemy next to `point`, if there is one.
+    fn attack_from(&mut self, point: &Position, dead_positions: &mut HashSet<Position>) {
+        let state = &mut self.game;
+        let attack = match state.world[*point] {
+            b'E' => self.elf_attack,
+            _ => 3,
+        };
+        if let Some(position) = state.find_direct_target(point) {
+            let is_enemy_elf = state.world[position] == b'E';
+            if state.attack_unit_at_point(&position, attack) {
+                dead_positions.insert(position);
+                if is_enemy_elf && self.spare_elves {
+                    self.outcome = Some(Outcome::ElfDied);
                 }
-                _ => symbol,
-            };
-            state.world.insert(Point(x as isize, y as isize), *tile);
+            }
         }
     }
-    state
-}
 
-#[aoc(day15, part1)]
-fn solve_part1(state: &Game) -> usize {
-    let mut round_count = 0;
-    let mut state = state.to_owned();
-    loop {
-        let mut dead_positions: HashSet<Point> = HashSet::new();
-        let mut keys: Vec<Point> = state.units.keys().cloned().collect();
-        keys.sort_by_key(|point| Point(point.1, point.0));
-        for point in keys.clone().into_iter() {
-            let unit = state.units.get_mut(&point);
+    fn round(&mut self) -> Option<Outcome> {
+        let mut dead_positions: HashSet<Position> = HashSet::new();
+        let mut keys: Vec<Position> = self.game.units.keys().cloned().collect();
+        keys.sort_by(reading_order);
+        for point in keys {
             // Unit was killed in previous iteration
-            if unit.is_none() || dead_positions.contains(&point) {
+            if !self.game.units.contains_key(&point) || dead_positions.contains(&point) {
                 continue;
             }
 
             // Can we attack?
-            let target_position = state.find_direct_target(&point);
-            if let Some(position) = target_position {
-                if state.attack_unit_at_point(&position, 3) {
-                    dead_positions.insert(position);
-                }
-                continue;
-            }
-
-            // We didn't attack, so can we move?
-            if let Some(position) = state.find_next_point(&point) {
-                state.move_from(&point, &position);
-                // We can attack after moving...
-                let target_position = state.find_direct_target(&position);
-                if let Some(position) = target_position {
-                    if state.attack_unit_at_point(&position, 3) {
-                        dead_positions.insert(position);
-                    }
-                }
+            if self.game.find_direct_target(&point).is_some() {
+                self.attack_from(&point, &mut dead_positions);
+            } else if let Some(position) = self.game.find_next_point(&point) {
+                // We didn't attack, so can we move? We can attack after moving...
+                self.game.move_from(&point, &position);
+                self.attack_from(&position, &mut dead_positions);
             } else {
-                let source_tile = state.world.get(&point).unwrap();
-                let enemy_units: Vec<Point> = state
-                    .units
-                    .keys()
-                    .cloned()
-                    .filter(|k| state.world.get(k).unwrap() != source_tile)
-                    .collect();
-                if !enemy_units.is_empty() {
-                    continue;
+                let state = &self.game;
+                let source_tile = state.world[point];
+                if state.units.keys().all(|k| state.world[*k] == source_tile) {
+                    let health = state.units.values().sum::<usize>();
+                    return Some(Outcome::Won(self.rounds * health));
                 }
-                return round_count * state.units.values().sum::<usize>();
+            }
+            if self.outcome.is_some() {
+                return self.outcome;
             }
         }
-        round_count += 1;
+        self.rounds += 1;
+        None
     }
 }
 
-#[aoc(day15, part2)]
-fn solve_part2(game_state: &Game) -> usize {
-    let mut round_count = 0;
-    let mut elf_attack = 4;
-    loop {
-        let mut state = game_state.to_owned();
-        'inner: loop {
-            let mut dead_positions: HashSet<Point> = HashSet::new();
-            let mut keys: Vec<Point> = state.units.keys().cloned().collect();
-            keys.sort_by_key(|point| Point(point.1, point.0));
-            for point in keys.clone().into_iter() {
-                let unit = state.units.get_mut(&point);
-                // Unit was killed in previous iteration
-                if unit.is_none() || dead_positions.contains(&point) {
-                    continue;
-                }
+/// The map with each row's units and their hit points beside it, as in the puzzle.
+impl fmt::Display for Battle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let world = &self.game.world;
+        for y in 0..world.height() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            let row: String = (0..world.width()).map(|x| world[(x, y)] as char).collect();
+            let units: Vec<String> = (0..world.width())
+                .filter_map(|x| {
+                    let health = self.game.units.get(&(x, y))?;
+                    Some(format!("{}({})", world[(x, y)] as char, health))
+                })
+                .collect();
+            write!(f, "{}", row)?;
+            if !units.is_empty() {
+                write!(f, "   {}", units.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
 
-                let attack = match state.world.get(&point).unwrap() {
-                    b'E' => elf_attack,
-                    _ => 3,
-                };
-
-                // Can we attack?
-                let target_position = state.find_direct_target(&point);
-                if let Some(position) = target_position {
-                    let is_enemy_elf = *state.world.get(&position).unwrap() == b'E';
-                    if state.attack_unit_at_point(&position, attack) {
-                        dead_positions.insert(position);
-                        if is_enemy_elf {
-                            break 'inner;
-                        }
-                    }
-                    continue;
-                }
+impl Simulation for Battle {
+    type Snapshot = Game;
 
-                // We didn't attack, so can we move?
-                if let Some(position) = state.find_next_point(&point) {
-                    state.move_from(&point, &position);
-                    // We can attack after moving...
-                    let target_position = state.find_direct_target(&position);
-                    if let Some(position) = target_position {
-                        let is_enemy_elf = *state.world.get(&position).unwrap() == b'E';
-                        if state.attack_unit_at_point(&position, attack) {
-                            dead_positions.insert(position);
-                            if is_enemy_elf {
-                                break 'inner;
-                            }
-                        }
-                    }
-                } else {
-                    let goblins: Vec<Point> = state
-                        .units
-                        .keys()
-                        .cloned()
-                        .filter(|k| *state.world.get(k).unwrap() == b'G')
-                        .collect();
-                    if !goblins.is_empty() {
-                        continue;
-                    }
-                    return round_count * state.units.values().sum::<usize>();
-                }
-            }
-            round_count += 1;
+    fn step(&mut self) {
+        if !self.is_finished() {
+            self.outcome = self.round();
         }
-        round_count = 0;
-        elf_attack += 1;
+    }
+
+    fn is_finished(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    fn snapshot(&self) -> Game {
+        self.game.clone()
+    }
+}
+
+#[aoc(day15, part1)]
+pub fn solve_part1(state: &Game) -> usize {
+    match Runner::new(Battle::new(state, 3, false)).run().outcome() {
+        Some(Outcome::Won(outcome)) => outcome,
+        _ => unreachable!(),
     }
 }
 
+#[aoc(day15, part2)]
+pub fn solve_part2(game_state: &Game) -> usize {
+    let mut meter = Budget::unlimited().start();
+    parallel::find_first(4, &mut meter, |elf_attack| {
+        match Runner::new(Battle::new(game_state, elf_attack, true))
+            .run()
+            .outcome()
+        {
+            Some(Outcome::Won(outcome)) => Some(outcome),
+            _ => None,
+        }
+    })
+    .unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::day15::*;
@@ -289,11 +330,45 @@ mod tests {
 #..G#E#
 #.....#
 #######",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 27730);
     }
 
+    #[test]
+    fn rounds() {
+        let input = input_generator(
+            "#######
+#.G...#
+#...EG#
+#.#.#G#
+#..G#E#
+#.....#
+#######",
+        )
+        .unwrap();
+        let mut units = input.units.len();
+        let battle = Runner::new(Battle::new(&input, 3, false))
+            .observe(|_, battle| {
+                assert!(battle.snapshot().units.len() <= units);
+                units = battle.snapshot().units.len();
+            })
+            .run();
+        assert_eq!(battle.rounds(), 47);
+        assert_eq!(battle.outcome(), Some(Outcome::Won(27730)));
+        assert_eq!(
+            battle.to_string(),
+            "#######
+#G....#   G(200)
+#.G...#   G(131)
+#.#.#G#   G(59)
+#...#.#
+#....G#   G(200)
+#######"
+        );
+    }
+
     #[test]
     fn part2() {
         let mut input = input_generator(
@@ -304,7 +379,8 @@ mod tests {
 #..G#E#
 #.....#
 #######",
-        );
+        )
+        .unwrap();
         let mut result = solve_part2(&input);
         assert_eq!(result, 4988);
 
@@ -316,7 +392,8 @@ mod tests {
 #G..#.#
 #..E#.#
 #######",
-        );
+        )
+        .unwrap();
         result = solve_part2(&input);
         assert_eq!(result, 31284);
 
@@ -328,7 +405,8 @@ mod tests {
 #G..#.#
 #...E.#
 #######",
-        );
+        )
+        .unwrap();
         result = solve_part2(&input);
         assert_eq!(result, 3478);
 
@@ -340,7 +418,8 @@ mod tests {
 #E#G#G#
 #...#G#
 #######",
-        );
+        )
+        .unwrap();
         result = solve_part2(&input);
         assert_eq!(result, 6474);
 
@@ -354,7 +433,8 @@ mod tests {
 #.G...G.#
 #.....G.#
 #########",
-        );
+        )
+        .unwrap();
         result = solve_part2(&input);
         assert_eq!(result, 1140);
     }
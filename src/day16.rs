@@ -1,180 +1,273 @@
+//! Chronal Classification: samples of what ElfCode instructions did to the registers, and a
+//! test program with its opcodes as numbers.
+//!
+//! Part one counts the samples that behave like three or more opcodes. Part two works out
+//! which number is which opcode and runs the program, and `validate` reports samples that no
+//! opcode explains or that leave a number as more than one opcode.
+use crate::budget::{Budget, Unsolved};
+use crate::elfcode::{Instruction, Machine, Opcode};
+use crate::error::{lines, Line, ParseError};
 use aoc_runner_derive::{aoc, aoc_generator};
 use hashbrown::HashMap;
 use regex::Regex;
 
-#[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Clone)]
-enum Opcode {
-    addr,
-    addi,
-    mulr,
-    muli,
-    banr,
-    bani,
-    borr,
-    bori,
-    setr,
-    seti,
-    gtir,
-    gtri,
-    gtrr,
-    eqir,
-    eqri,
-    eqrr,
-}
-
-impl Opcode {
-    fn values() -> Vec<Opcode> {
-        vec![
-            Opcode::addr,
-            Opcode::addi,
-            Opcode::mulr,
-            Opcode::muli,
-            Opcode::banr,
-            Opcode::bani,
-            Opcode::borr,
-            Opcode::bori,
-            Opcode::setr,
-            Opcode::seti,
-            Opcode::gtir,
-            Opcode::gtri,
-            Opcode::gtrr,
-            Opcode::eqir,
-            Opcode::eqri,
-            Opcode::eqrr,
-        ]
-    }
+/// (before Before, instruction, before After)
+pub type Sample = (Vec<usize>, Vec<usize>, Vec<usize>);
+pub type AOCResult = (Vec<Sample>, Vec<Vec<usize>>);
+
+/// Does executing the sample's instruction as `opcode` turn Before into After?
+fn behaves_like(opcode: Opcode, (before, instruction, after): &Sample) -> bool {
+    let mut machine = Machine::new(4);
+    machine.registers = before.to_owned();
+    machine.execute(&Instruction::new(
+        opcode,
+        instruction[1],
+        instruction[2],
+        instruction[3],
+    ));
+    machine.registers == *after
 }
 
-/// (before Before, instruction, before After)
-type Sample = (Vec<usize>, Vec<usize>, Vec<usize>);
-type AOCResult = (Vec<Sample>, Vec<Vec<usize>>);
-
-fn handle_opcode(opcode: Opcode, before: &[usize], instruction: &[usize]) -> usize {
-    match opcode {
-        Opcode::addr => before[instruction[1]] + before[instruction[2]],
-        Opcode::addi => before[instruction[1]] + instruction[2],
-        Opcode::mulr => before[instruction[1]] * before[instruction[2]],
-        Opcode::muli => before[instruction[1]] * instruction[2],
-        Opcode::banr => before[instruction[1]] & before[instruction[2]],
-        Opcode::bani => before[instruction[1]] & instruction[2],
-        Opcode::borr => before[instruction[1]] | before[instruction[2]],
-        Opcode::bori => before[instruction[1]] | instruction[2],
-        Opcode::setr => before[instruction[1]],
-        Opcode::seti => instruction[1],
-        Opcode::gtir => {
-            if instruction[1] > before[instruction[2]] {
-                1
-            } else {
-                0
-            }
-        }
-        Opcode::gtri => {
-            if before[instruction[1]] > instruction[2] {
-                1
-            } else {
-                0
-            }
-        }
-        Opcode::gtrr => {
-            if before[instruction[1]] > before[instruction[2]] {
-                1
-            } else {
-                0
-            }
-        }
-        Opcode::eqir => {
-            if instruction[1] == before[instruction[2]] {
-                1
-            } else {
-                0
-            }
-        }
-        Opcode::eqri => {
-            if before[instruction[1]] == instruction[2] {
-                1
-            } else {
-                0
-            }
-        }
-        Opcode::eqrr => {
-            if before[instruction[1]] == before[instruction[2]] {
-                1
-            } else {
-                0
-            }
-        }
+fn parse_registers(line: &Line, re: &Regex) -> Result<Vec<usize>, ParseError> {
+    let groups = line.captures(re)?;
+    groups
+        .iter()
+        .skip(1)
+        .take(4)
+        .map(|v| line.parse(v.unwrap().as_str()))
+        .collect()
+}
+
+fn parse_instruction(line: &Line) -> Result<Vec<usize>, ParseError> {
+    let instruction = line
+        .text
+        .split(' ')
+        .map(|v| line.parse(v))
+        .collect::<Result<Vec<usize>, ParseError>>()?;
+    if instruction.len() != 4 {
+        return Err(line.error(line.text, "expected `opcode a b c`"));
     }
+    Ok(instruction)
 }
 
-#[aoc_generator(day16)]
-fn input_generator(input: &str) -> AOCResult {
+/// The samples and the test program, each with the line its instruction is on.
+type Parsed<'a> = (Vec<(Sample, Line<'a>)>, Vec<(Vec<usize>, Line<'a>)>);
+
+fn parse(input: &str) -> Result<Parsed<'_>, ParseError> {
     let re: Regex = Regex::new(r".+:\s{1,2}\[(\d{1}), (\d{1}), (\d{1}), (\d{1})\]").unwrap();
-    let mut samples: Vec<Sample> = Vec::new();
-    let mut instructions: Vec<Vec<usize>> = Vec::new();
-    let lines: Vec<&str> = input.lines().collect();
+    let mut samples = Vec::new();
+    let mut instructions = Vec::new();
+    let lines: Vec<Line> = lines(16, input).collect();
 
     // Get Samples
     for lines in lines.chunks(4) {
-        if !lines[0].contains("Before:") {
+        if !lines[0].text.contains("Before:") {
             break;
         }
+        if lines.len() < 3 {
+            return Err(lines[0].error(lines[0].text, "incomplete sample"));
+        }
 
-        let before_groups = re.captures(lines[0]).unwrap();
-        let before: Vec<usize> = before_groups
-            .iter()
-            .skip(1)
-            .take(4)
-            .map(|v| v.unwrap().as_str().parse::<usize>().unwrap())
-            .collect();
+        let before = parse_registers(&lines[0], &re)?;
+        let instruction = parse_instruction(&lines[1])?;
+        let after = parse_registers(&lines[2], &re)?;
+        samples.push(((before, instruction, after), lines[1]));
+    }
 
-        let instruction: Vec<usize> = lines[1]
-            .split(' ')
-            .map(|v| v.parse::<usize>().unwrap())
-            .collect();
+    // Get Test Code
+    for line in lines.iter().skip(samples.len() * 4) {
+        if line.text.is_empty() {
+            continue;
+        }
+        instructions.push((parse_instruction(line)?, *line));
+    }
+
+    Ok((samples, instructions))
+}
+
+/// Fails on the first sample or instruction naming an opcode or a register the machine doesn't
+/// have, which the solvers couldn't run.
+#[aoc_generator(day16)]
+pub fn input_generator(input: &str) -> Result<AOCResult, ParseError> {
+    let (samples, instructions) = parse(input)?;
+    let numbered = samples
+        .iter()
+        .map(|((_, instruction, _), line)| (instruction, line))
+        .chain(
+            instructions
+                .iter()
+                .map(|(instruction, line)| (instruction, line)),
+        );
+    for (instruction, line) in numbered {
+        if let Some(reason) = unrunnable(instruction) {
+            return Err(line.error(line.text, &reason));
+        }
+    }
+    Ok((
+        samples.into_iter().map(|(sample, _)| sample).collect(),
+        instructions
+            .into_iter()
+            .map(|(instruction, _)| instruction)
+            .collect(),
+    ))
+}
+
+/// Whether the machine has every register `instruction` uses as `opcode`.
+fn in_range(opcode: Opcode, instruction: &[usize]) -> bool {
+    let (a, b) = opcode.reads_registers();
+    (!a || instruction[1] < 4) && (!b || instruction[2] < 4) && instruction[3] < 4
+}
 
-        let after_groups = re.captures(lines[2]).unwrap();
-        let after: Vec<usize> = after_groups
+/// Like `behaves_like`, but false rather than a panic when the sample uses a register that
+/// doesn't exist.
+fn could_be(opcode: Opcode, sample: &Sample) -> bool {
+    in_range(opcode, &sample.1) && behaves_like(opcode, sample)
+}
+
+/// Why the machine can't run `instruction`, if it can't.
+fn unrunnable(instruction: &[usize]) -> Option<String> {
+    if instruction[0] > 15 {
+        Some(format!(
+            "opcode {} is not one of the sixteen",
+            instruction[0]
+        ))
+    } else if instruction[3] > 3 {
+        Some(format!("writes register {} of only four", instruction[3]))
+    } else {
+        None
+    }
+}
+
+/// For each opcode, the numbers whose samples all behave like it. A number without samples
+/// could be any opcode.
+fn candidates<'a>(samples: impl Iterator<Item = &'a Sample> + Clone) -> Vec<Vec<(usize, Opcode)>> {
+    Opcode::values()
+        .into_iter()
+        .map(|opcode| {
+            (0..16)
+                .filter(|&n| {
+                    samples
+                        .clone()
+                        .filter(|s| s.1[0] == n)
+                        .all(|s| could_be(opcode, s))
+                })
+                .map(|n| (n, opcode))
+                .collect()
+        })
+        .collect()
+}
+
+/// One round of deduction: every opcode with only one number left that isn't known yet gets
+/// that number. Returns whether any did.
+fn narrow(candidates: &[Vec<(usize, Opcode)>], opcodes: &mut HashMap<usize, Opcode>) -> bool {
+    let known = opcodes.len();
+    for possibilities in candidates {
+        let new: Vec<(usize, Opcode)> = possibilities
             .iter()
-            .skip(1)
-            .take(4)
-            .map(|v| v.unwrap().as_str().parse::<usize>().unwrap())
+            .cloned()
+            .filter(|p| !opcodes.contains_key(&p.0))
             .collect();
-
-        samples.push((before, instruction, after));
+        if new.len() == 1 {
+            opcodes.insert(new[0].0, new[0].1);
+        }
     }
+    opcodes.len() > known
+}
 
-    // Get Test Code
-    for lines in lines.iter().skip(samples.len() * 4) {
-        if lines.is_empty() {
-            continue;
+/// Reports what would stop part two: samples and instructions that can't be run, and numbers
+/// in the test program the samples don't pin to exactly one opcode.
+///
+/// That covers samples that behave like none of the sixteen opcodes, numbers whose samples
+/// agree on no opcode or leave more than one, and instructions naming an opcode or register
+/// that doesn't exist.
+pub fn validate(input: &str) -> Vec<ParseError> {
+    let (samples, instructions) = match parse(input) {
+        Ok(parsed) => parsed,
+        Err(error) => return vec![error],
+    };
+    let mut errors = Vec::new();
+    let mut runnable = Vec::new();
+    for (sample, line) in &samples {
+        if let Some(reason) = unrunnable(&sample.1) {
+            errors.push(line.error(line.text, &reason));
+        } else if Opcode::values()
+            .into_iter()
+            .any(|opcode| could_be(opcode, sample))
+        {
+            runnable.push((sample, line));
+        } else {
+            errors.push(line.error(line.text, "behaves like none of the sixteen opcodes"));
+        }
+    }
+    for n in 0..16 {
+        let numbered: Vec<_> = runnable.iter().filter(|(s, _)| s.1[0] == n).collect();
+        let agree = Opcode::values()
+            .into_iter()
+            .any(|opcode| numbered.iter().all(|(sample, _)| could_be(opcode, sample)));
+        if let (false, Some((_, line))) = (agree, numbered.first()) {
+            let reason = format!(
+                "the samples of opcode {} don't all behave like any one opcode",
+                n
+            );
+            errors.push(line.error(line.text, &reason));
         }
+    }
+    if errors.is_empty() {
+        errors.extend(ambiguities(&samples, &instructions));
+    }
+    for (instruction, line) in &instructions {
+        if let Some(reason) = unrunnable(instruction) {
+            errors.push(line.error(line.text, &reason));
+        }
+    }
+    errors
+}
 
-        let instruction: Vec<usize> = lines
-            .split(' ')
-            .map(|v| v.parse::<usize>().unwrap())
+/// The opcode numbers in the test program that part two's deduction can't narrow down to one
+/// opcode, at the first sample or instruction with that number.
+fn ambiguities(samples: &[(Sample, Line)], instructions: &[(Vec<usize>, Line)]) -> Vec<ParseError> {
+    let candidates = candidates(samples.iter().map(|(sample, _)| sample));
+    let mut opcodes = HashMap::new();
+    while opcodes.len() < 16 && narrow(&candidates, &mut opcodes) {}
+    let mut errors = Vec::new();
+    let used = |n: &usize| {
+        instructions
+            .iter()
+            .any(|(instruction, _)| instruction[0] == *n)
+    };
+    for n in (0..16).filter(|n| used(n) && !opcodes.contains_key(n)) {
+        let options: Vec<String> = candidates
+            .iter()
+            .flatten()
+            .filter(|&&(number, opcode)| number == n && !opcodes.values().any(|&o| o == opcode))
+            .map(|(_, opcode)| format!("{:?}", opcode))
             .collect();
-        instructions.push(instruction);
+        let reason = format!("opcode {} could be any of {}", n, options.join(", "));
+        let first = samples
+            .iter()
+            .map(|((_, instruction, _), line)| (instruction, line))
+            .chain(
+                instructions
+                    .iter()
+                    .map(|(instruction, line)| (instruction, line)),
+            )
+            .find(|(instruction, _)| instruction[0] == n);
+        if let Some((_, line)) = first {
+            errors.push(line.error(line.text, &reason));
+        }
     }
-
-    (samples, instructions)
+    errors
 }
 
 /// how many samples in your puzzle input behave like three or more opcodes?
 #[aoc(day16, part1)]
-fn solve_part1(input: &AOCResult) -> usize {
+pub fn solve_part1(input: &AOCResult) -> usize {
     let mut total_count = 0;
     for sample in &input.0 {
-        let mut sample_count = 0;
-        let (before, instruction, after) = sample;
-        for opcode in 0..16 {
-            let result = handle_opcode(Opcode::values()[opcode].clone(), before, instruction);
-            let mut new_after = before.to_owned();
-            new_after[instruction[3]] = result;
-            if new_after == *after {
-                sample_count += 1;
-            }
-        }
+        let sample_count = Opcode::values()
+            .into_iter()
+            .filter(|&opcode| could_be(opcode, sample))
+            .count();
         if sample_count >= 3 {
             total_count += 1;
         }
@@ -184,43 +277,80 @@ fn solve_part1(input: &AOCResult) -> usize {
 
 /// What value is contained in register 0 after executing the test program?
 #[aoc(day16, part2)]
-fn solve_part2(input: &AOCResult) -> usize {
-    let mut candidates = Vec::new();
-    for opcode in &Opcode::values() {
-        let mut possibilities = Vec::new();
-        for n in 0..16 {
-            let missed = input.0.iter().filter(|s| s.1[0] == n).any(|s| {
-                let result = handle_opcode(opcode.clone(), &s.0, &s.1);
-                let mut new_after = s.0.to_owned();
-                new_after[s.1[3]] = result;
-                new_after != s.2
-            });
-            if !missed {
-                possibilities.push((n, opcode.clone()));
-            }
-        }
-        candidates.push(possibilities);
-    }
+pub fn solve_part2(input: &AOCResult) -> Result<usize, Unsolved> {
+    solve_part2_within(input, Budget::default())
+}
+
+/// Part two, giving up after `budget` rounds of deducing which number is which opcode. Only
+/// the numbers the test program uses need working out.
+pub fn solve_part2_within(input: &AOCResult, budget: Budget) -> Result<usize, Unsolved> {
+    let mut meter = budget.start();
+    let candidates = candidates(input.0.iter());
     let mut opcodes = HashMap::new();
-    while opcodes.len() < 16 {
-        for possibilities in &candidates {
-            let new: Vec<(usize, Opcode)> = possibilities
-                .iter()
-                .cloned()
-                .filter(|p| !opcodes.contains_key(&p.0))
-                .collect();
-            if new.len() == 1 {
-                opcodes.insert(new[0].0, new[0].1.clone());
-            }
+    while input
+        .1
+        .iter()
+        .any(|instruction| !opcodes.contains_key(&instruction[0]))
+    {
+        meter.tick()?;
+        if !narrow(&candidates, &mut opcodes) {
+            return Err(Unsolved::NoSolution(
+                "the samples don't narrow every opcode the program uses down to one number",
+            ));
         }
     }
 
-    let mut registers = vec![0, 0, 0, 0];
+    let mut machine = Machine::new(4);
     for instruction in &input.1 {
-        if let Some(opcode) = opcodes.get(&instruction[0]) {
-            let result = handle_opcode(opcode.clone(), &registers, instruction);
-            registers[instruction[3]] = result;
+        let opcode = opcodes[&instruction[0]];
+        if !in_range(opcode, instruction) {
+            return Err(Unsolved::NoSolution(
+                "the test program reads a register that doesn't exist",
+            ));
         }
+        machine.execute(&Instruction::new(
+            opcode,
+            instruction[1],
+            instruction[2],
+            instruction[3],
+        ));
+    }
+    Ok(machine.registers[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day16::*;
+
+    #[test]
+    fn part2_unsolvable() {
+        // a single sample can't tell sixteen opcodes apart
+        let input = input_generator(
+            "Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [3, 2, 2, 1]
+
+
+
+9 2 1 2",
+        )
+        .unwrap();
+        assert_eq!(solve_part1(&input), 1);
+        assert!(matches!(solve_part2(&input), Err(Unsolved::NoSolution(_))));
+    }
+
+    #[test]
+    fn out_of_range() {
+        // reading register 7 rules out the opcodes that would read it, rather than panicking
+        let input = input_generator("Before: [3, 2, 1, 1]\n9 7 1 2\nAfter:  [3, 2, 2, 1]").unwrap();
+        assert_eq!(solve_part1(&input), 0);
+        let error = input_generator("Before: [3, 2, 1, 1]\n9 2 1 5\nAfter:  [3, 2, 2, 1]")
+            .err()
+            .unwrap();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.reason, "writes register 5 of only four");
+        let error = input_generator("\n\n\n1 0 0 0\n16 0 0 0").err().unwrap();
+        assert_eq!(error.line, 5);
+        assert_eq!(error.reason, "opcode 16 is not one of the sixteen");
     }
-    registers[0]
 }
@@ -1,36 +1,71 @@
+//! Reservoir Research: veins of clay in the ground below a spring of water.
+//!
+//! `World` is the scanned ground and `Flow` lets the water into it a drip at a time. Part one
+//! counts every tile the water reaches, and part two the water that settles.
+use crate::error::{lines, ParseError};
+use crate::grid::Grid;
+use crate::simulation::{Runner, Simulation};
 use aoc_runner_derive::{aoc, aoc_generator};
-use hashbrown::{HashMap, HashSet};
 use regex::Regex;
+use std::fmt;
 
-#[derive(Hash, PartialEq, Eq, Ord, PartialOrd, Clone)]
+/// A column and row of the grid, rather than of the scan.
+#[derive(Clone, Copy)]
 struct Point(usize, usize);
 
+/// The most tiles the grid may hold, far more than any scan needs but few enough to allocate.
+const MAX_TILES: usize = 1 << 24;
+
+/// The scanned slice of ground: `+` for the spring, `#` for clay, `|` for flowing and `~` for
+/// settled water.
+/// The grid keeps a spare column either side of the clay, so water can spill past it.
 #[derive(Clone)]
-struct World {
-    clay: HashSet<Point>,
-    water: HashMap<Point, u8>,
-    points_handled: HashSet<Point>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct World {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::dump::tiles"))]
+    ground: Grid<u8>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handled: Grid<bool>,
+    /// The scan's x of the grid's second column, the first being the spare one.
+    x_offset: usize,
+    y_min: usize,
 }
 
 impl World {
-    fn drip(&mut self, point: &Point) {
-        if self.points_handled.contains(point) {
-            return;
+    fn at(&self, point: Point) -> u8 {
+        self.ground[(point.0, point.1)]
+    }
+
+    fn set(&mut self, point: Point, tile: u8) {
+        self.ground[(point.0, point.1)] = tile;
+    }
+
+    /// The grid column of the scan's `x`, if it's on the grid.
+    const fn column(&self, x: usize) -> Option<usize> {
+        (x + 1).checked_sub(self.x_offset)
+    }
+
+    /// Lets water fall from `point`, filling any basin it lands in, and returns the points
+    /// where it spills over the sides to fall again, left first.
+    fn drip(&mut self, point: &Point) -> Vec<Point> {
+        let handled = &mut self.handled[(point.0, point.1)];
+        if *handled {
+            return Vec::new();
         }
-        self.points_handled.insert(point.clone());
+        *handled = true;
 
-        let y_max = self.clay.iter().max_by_key(|p| p.1).unwrap().1;
-        let has_clay_below = (point.1..=y_max).find(|y| self.clay.contains(&Point(point.0, *y)));
+        let y_max = self.ground.height() - 1;
+        let has_clay_below = (point.1..=y_max).find(|y| self.at(Point(point.0, *y)) == b'#');
         if has_clay_below.is_none() {
             for y in point.1..=y_max {
-                self.water.insert(Point(point.0, y), b'|');
+                self.set(Point(point.0, y), b'|');
             }
-            return;
+            return Vec::new();
         }
 
         let clay_y = has_clay_below.unwrap();
         for y in point.1..clay_y {
-            self.water.insert(Point(point.0, y), b'|');
+            self.set(Point(point.0, y), b'|');
         }
 
         let mut current = clay_y - 1;
@@ -40,19 +75,20 @@ impl World {
 
             if left_is_wall && right_is_wall {
                 for x in left_x + 1..right_x {
-                    self.water.insert(Point(x, current), b'~');
+                    self.set(Point(x, current), b'~');
                 }
             } else {
                 for x in left_x + 1..right_x {
-                    self.water.insert(Point(x, current), b'|');
+                    self.set(Point(x, current), b'|');
                 }
+                let mut spills = Vec::new();
                 if !left_is_wall {
-                    self.drip(&Point(left_x, current));
+                    spills.push(Point(left_x, current));
                 }
                 if !right_is_wall {
-                    self.drip(&Point(right_x, current));
+                    spills.push(Point(right_x, current));
                 }
-                break;
+                return spills;
             }
             current -= 1;
         }
@@ -61,71 +97,172 @@ impl World {
     fn peek_at(&self, point: &Point, change: isize) -> (bool, usize) {
         let mut current = Point((point.0 as isize + change) as usize, point.1);
         loop {
-            if self.clay.contains(&current) {
+            if self.at(current) == b'#' {
                 return (true, current.0);
-            } else if !self.clay.contains(&Point(current.0, current.1 + 1)) {
-                if self.water.contains_key(&Point(current.0, current.1 + 1))
-                    && *self.water.get(&Point(current.0, current.1 + 1)).unwrap() == b'~'
-                {
-                    current = Point((current.0 as isize + change) as usize, current.1);
-                    continue;
-                }
+            }
+            let below = self.at(Point(current.0, current.1 + 1));
+            if below != b'#' && below != b'~' {
                 return (false, current.0);
             }
             current = Point((current.0 as isize + change) as usize, current.1);
         }
     }
+
+    /// The tile at `(x, y)` in the scan's coordinates, if it's on the grid.
+    pub fn tile(&self, x: usize, y: usize) -> Option<char> {
+        self.ground
+            .get((self.column(x)?, y))
+            .map(|&tile| tile as char)
+    }
+
+    /// Counts the tiles in the scanned range of y values that hold any of `tiles`, such as
+    /// `b"~"` for the settled water.
+    pub fn count(&self, tiles: &[u8]) -> usize {
+        self.ground
+            .iter()
+            .filter(|&((_, y), tile)| y >= self.y_min && tiles.contains(tile))
+            .count()
+    }
+}
+
+impl fmt::Display for World {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ground.render(|&tile| tile as char))
+    }
+}
+
+/// The water a drip at a time, from the spring until every spill has been followed.
+#[derive(Clone)]
+pub struct Flow {
+    world: World,
+    /// Where water still has to fall from, the next drip last.
+    pending: Vec<Point>,
+    drips: usize,
+}
+
+impl Flow {
+    /// Starts the water falling from the spring at x=500.
+    pub fn new(world: &World) -> Self {
+        Self {
+            world: world.to_owned(),
+            pending: vec![Point(world.column(500).unwrap(), world.y_min)],
+            drips: 0,
+        }
+    }
+
+    /// How many times water has fallen from a point so far.
+    pub const fn drips(&self) -> usize {
+        self.drips
+    }
+
+    pub const fn world(&self) -> &World {
+        &self.world
+    }
+}
+
+impl Simulation for Flow {
+    type Snapshot = World;
+
+    fn step(&mut self) {
+        if let Some(point) = self.pending.pop() {
+            let spills = self.world.drip(&point);
+            self.pending.extend(spills.into_iter().rev());
+            self.drips += 1;
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    fn snapshot(&self) -> World {
+        self.world.clone()
+    }
+}
+
+impl fmt::Display for Flow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.world)
+    }
 }
 
 #[aoc_generator(day17)]
-fn input_generator(input: &str) -> World {
-    let mut clay: HashSet<Point> = HashSet::new();
-    let re = Regex::new(r"[xy]=(\d+),\s[x|y]=(\d+..\d+)").unwrap();
-    for line in input.lines() {
-        let caps = re.captures(line).unwrap();
-        let a = caps[1].parse::<usize>().unwrap();
-        let b: Vec<usize> = caps[2]
-            .split("..")
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
-        match line.as_bytes()[0] {
-            b'x' => {
-                for y in b[0]..=b[1] {
-                    clay.insert(Point(a, y));
-                }
-            }
-            b'y' => {
-                for x in b[0]..=b[1] {
-                    clay.insert(Point(x, a));
-                }
-            }
+pub fn input_generator(input: &str) -> Result<World, ParseError> {
+    // each vein as its x and y ranges, with its line
+    let mut veins = Vec::new();
+    let re = Regex::new(r"^([xy])=(\d+),\s[xy]=(\d+)\.\.(\d+)").unwrap();
+    for line in lines(17, input) {
+        let caps = line.captures(&re)?;
+        let a = line.parse::<usize>(&caps[2])?;
+        let (from, to) = (
+            line.parse::<usize>(&caps[3])?,
+            line.parse::<usize>(&caps[4])?,
+        );
+        if from > to {
+            return Err(line.error(&caps[3], "range runs backwards"));
+        }
+        match &caps[1] {
+            "x" => veins.push(((a, a), (from, to), line)),
+            "y" => veins.push(((from, to), (a, a), line)),
             _ => unreachable!(),
         };
     }
-    World {
-        clay,
-        water: HashMap::new(),
-        points_handled: HashSet::new(),
+    if veins.is_empty() {
+        return Err(ParseError::new(17, 1, 1, "", "no clay in scan"));
+    }
+    // the spring at x=500 has to be on the grid too
+    let x_min = veins.iter().map(|v| (v.0).0).min().unwrap().min(500);
+    let x_max = veins.iter().map(|v| (v.0).1).max().unwrap().max(500);
+    let y_min = veins.iter().map(|v| (v.1).0).min().unwrap();
+    let y_max = veins.iter().map(|v| (v.1).1).max().unwrap();
+    let (width, height) = (x_max - x_min + 3, y_max + 1);
+    if width.saturating_mul(height) > MAX_TILES {
+        // blame the vein furthest from the spring
+        let (_, _, line) = veins
+            .iter()
+            .max_by_key(|((left, right), (_, bottom), _)| {
+                (500 - left.min(&500))
+                    .max(right.saturating_sub(500))
+                    .max(*bottom)
+            })
+            .unwrap();
+        let reason = format!(
+            "clay spans {}x{} tiles, more than the {} a scan may",
+            width, height, MAX_TILES
+        );
+        return Err(line.error(line.text, &reason));
     }
+    let mut ground = Grid::new(width, height, b'.');
+    for ((left, right), (top, bottom), _) in veins {
+        for x in left..=right {
+            for y in top..=bottom {
+                ground[(x + 1 - x_min, y)] = b'#';
+            }
+        }
+    }
+    if y_min > 0 {
+        ground[(500 + 1 - x_min, 0)] = b'+';
+    }
+    let handled = Grid::new(ground.width(), ground.height(), false);
+    Ok(World {
+        ground,
+        handled,
+        x_offset: x_min,
+        y_min,
+    })
 }
 
 /// How many tiles can the water reach within the range of y values in your scan?
 #[aoc(day17, part1)]
-fn solve_part1(world: &World) -> usize {
-    let y_min = world.clay.iter().min_by_key(|p| p.1).unwrap().1;
-    let mut our_world = world.to_owned();
-    our_world.drip(&Point(500, y_min));
-    our_world.water.len()
+pub fn solve_part1(world: &World) -> usize {
+    Runner::new(Flow::new(world)).run().world.count(b"|~")
 }
 
 /// How many water tiles are left after the water spring stops producing water and
 /// all remaining water not at rest has drained?
 #[aoc(day17, part2)]
-fn solve_part2(world: &World) -> usize {
-    let y_min = world.clay.iter().min_by_key(|p| p.1).unwrap().1;
-    let mut our_world = world.to_owned();
-    our_world.drip(&Point(500, y_min));
-    our_world.water.iter().filter(|&(_, v)| *v == b'~').count()
+pub fn solve_part2(world: &World) -> usize {
+    Runner::new(Flow::new(world)).run().world.count(b"~")
 }
 
 #[cfg(test)]
@@ -143,7 +280,8 @@ x=506, y=1..2
 x=498, y=10..13
 x=504, y=10..13
 y=13, x=498..504",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 57);
     }
@@ -159,8 +297,68 @@ x=506, y=1..2
 x=498, y=10..13
 x=504, y=10..13
 y=13, x=498..504",
-        );
+        )
+        .unwrap();
         let result = solve_part2(&input);
         assert_eq!(result, 29);
     }
+
+    #[test]
+    fn render() {
+        let world = input_generator(
+            "x=495, y=2..7
+y=7, x=495..501
+x=501, y=3..7
+x=498, y=2..4
+x=506, y=1..2
+x=498, y=10..13
+x=504, y=10..13
+y=13, x=498..504",
+        )
+        .unwrap();
+        let mut water = Vec::new();
+        let flow = Runner::new(Flow::new(&world))
+            .observe(|_, flow| water.push(flow.world().count(b"|~")))
+            .run();
+        assert_eq!(flow.drips(), 4);
+        assert!(water.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            flow.world().to_string(),
+            "......+.......
+......|.....#.
+.#..#||||...#.
+.#..#~~#|.....
+.#..#~~#|.....
+.#~~~~~#|.....
+.#~~~~~#|.....
+.#######|.....
+........|.....
+...|||||||||..
+...|#~~~~~#|..
+...|#~~~~~#|..
+...|#~~~~~#|..
+...|#######|.."
+        );
+    }
+
+    #[test]
+    fn edges() {
+        // water runs off the clay at x=0 into the spare column
+        let input = input_generator("x=501, y=2..4\ny=4, x=0..501").unwrap();
+        assert_eq!(input.tile(0, 4), Some('#'));
+        assert_eq!(solve_part2(&input), 0);
+        assert!(solve_part1(&input) > 500);
+
+        let error = input_generator("x=495, y=2..7\ny=7, x=501..495")
+            .err()
+            .unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "range runs backwards")
+        );
+        let error = input_generator("x=495, y=2..7\nx=5000000, y=1..2")
+            .err()
+            .unwrap();
+        assert_eq!(error.line, 2);
+    }
 }
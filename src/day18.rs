@@ -1,162 +1,132 @@
+//! Settlers of The North Pole: `Acres` of open ground, trees and lumberyards.
+//!
+//! `Lumber` changes the acres a minute at a time. Both parts are the resource value, after 10
+//! minutes and after a billion, which is found by spotting where the acres start repeating.
+use crate::budget::{Budget, Unsolved};
+use crate::cycle::brent;
+use crate::error::ParseError;
+use crate::grid::Grid;
+use crate::simulation::{Runner, Simulation};
 use aoc_runner_derive::{aoc, aoc_generator};
-use hashbrown::HashMap;
+use std::fmt;
 
-#[derive(Clone, Eq, PartialEq)]
-enum Tile {
+#[derive(Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Tile {
     Ground,
     Tree,
     Lumberyard,
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
-struct Point(isize, isize);
-impl Point {
-    fn adjacents(&self) -> Vec<Point> {
-        let Point(x, y) = *self;
-        vec![
-            Point(x - 1, y - 1),
-            Point(x, y - 1),
-            Point(x + 1, y - 1),
-            Point(x - 1, y),
-            Point(x + 1, y),
-            Point(x - 1, y + 1),
-            Point(x, y + 1),
-            Point(x + 1, y + 1),
-        ]
-    }
-}
+pub type Acres = Grid<Tile>;
 
-type Acres = HashMap<Point, Tile>;
-
-fn mutate_acres(current: &Acres, mutable: &mut Acres) {
-    for (key, value) in current {
-        match value {
-            Tile::Ground => {
-                let mut tree_count = 0;
-                for point in key.adjacents() {
-                    if let Some(tile) = current.get(&point) {
-                        if *tile == Tile::Tree {
-                            tree_count += 1;
-                        }
-                    }
-                }
-                if tree_count >= 3 {
-                    mutable.insert(key.clone(), Tile::Tree);
-                }
-            }
-            Tile::Tree => {
-                let mut lumber_count = 0;
-                for point in key.adjacents() {
-                    if let Some(tile) = current.get(&point) {
-                        if *tile == Tile::Lumberyard {
-                            lumber_count += 1;
-                        }
-                    }
-                }
-                if lumber_count >= 3 {
-                    mutable.insert(key.clone(), Tile::Lumberyard);
-                }
-            }
-            Tile::Lumberyard => {
-                let mut lumber_count = 0;
-                let mut tree_count = 0;
-                for point in key.adjacents() {
-                    if let Some(tile) = current.get(&point) {
-                        match tile {
-                            Tile::Tree => tree_count += 1,
-                            Tile::Lumberyard => lumber_count += 1,
-                            _ => {}
-                        };
-                    }
-                }
-                if lumber_count == 0 || tree_count == 0 {
-                    mutable.insert(key.clone(), Tile::Ground);
-                }
+fn mutate_acres(current: &Acres) -> Acres {
+    Grid::from_fn(current.width(), current.height(), |position| {
+        let count = |tile: Tile| {
+            current
+                .neighbours8(position)
+                .filter(|&neighbour| current[neighbour] == tile)
+                .count()
+        };
+        match current[position] {
+            Tile::Ground if count(Tile::Tree) >= 3 => Tile::Tree,
+            Tile::Tree if count(Tile::Lumberyard) >= 3 => Tile::Lumberyard,
+            Tile::Lumberyard if count(Tile::Lumberyard) == 0 || count(Tile::Tree) == 0 => {
+                Tile::Ground
             }
+            ref tile => tile.clone(),
         }
-    }
+    })
 }
 
-#[aoc_generator(day18)]
-fn input_generator(input: &str) -> Acres {
-    let mut acres: Acres = HashMap::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, tile) in line.as_bytes().iter().enumerate() {
-            let tile = match tile {
-                b'.' => Tile::Ground,
-                b'|' => Tile::Tree,
-                b'#' => Tile::Lumberyard,
-                _ => unreachable!(),
-            };
-            acres.insert(Point(x as isize, y as isize), tile);
+/// The acres a minute at a time. The forest never settles, so this never finishes.
+#[derive(Clone)]
+pub struct Lumber {
+    acres: Acres,
+    minutes: usize,
+}
+
+impl Lumber {
+    pub fn new(acres: &Acres) -> Self {
+        Self {
+            acres: acres.to_owned(),
+            minutes: 0,
         }
     }
-    acres
+
+    pub const fn minutes(&self) -> usize {
+        self.minutes
+    }
+
+    pub fn resource_value(&self) -> usize {
+        resource_value(&self.acres)
+    }
 }
 
-/// What will the total resource value of the lumber collection area be after 10 minutes?
-#[aoc(day18, part1)]
-fn solve_part1(acres: &Acres) -> usize {
-    let mut mutable_acres = acres.to_owned();
-    for _ in 0..10 {
-        let current_acres = mutable_acres.to_owned();
-        mutate_acres(&current_acres, &mut mutable_acres);
+impl fmt::Display for Lumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rendered = self.acres.render(|tile| match tile {
+            Tile::Ground => '.',
+            Tile::Tree => '|',
+            Tile::Lumberyard => '#',
+        });
+        write!(f, "{}", rendered)
     }
+}
+
+impl Simulation for Lumber {
+    type Snapshot = Acres;
 
-    let wooded_acres = mutable_acres
-        .iter()
-        .filter(|&(_, v)| *v == Tile::Tree)
-        .count();
-    let lumber_acres = mutable_acres
-        .iter()
-        .filter(|&(_, v)| *v == Tile::Lumberyard)
-        .count();
+    fn step(&mut self) {
+        self.acres = mutate_acres(&self.acres);
+        self.minutes += 1;
+    }
+
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    fn snapshot(&self) -> Acres {
+        self.acres.clone()
+    }
+}
+
+fn resource_value(acres: &Acres) -> usize {
+    let wooded_acres = acres.values().filter(|&t| *t == Tile::Tree).count();
+    let lumber_acres = acres.values().filter(|&t| *t == Tile::Lumberyard).count();
     wooded_acres * lumber_acres
 }
 
+#[aoc_generator(day18)]
+pub fn input_generator(input: &str) -> Result<Acres, ParseError> {
+    Grid::parse(18, input, "unknown acre", |_, tile| match tile {
+        '.' => Some(Tile::Ground),
+        '|' => Some(Tile::Tree),
+        '#' => Some(Tile::Lumberyard),
+        _ => None,
+    })
+}
+
+/// What will the total resource value of the lumber collection area be after 10 minutes?
+#[aoc(day18, part1)]
+pub fn solve_part1(acres: &Acres) -> usize {
+    Runner::new(Lumber::new(acres)).run_for(10).resource_value()
+}
+
 /// What will the total resource value of the lumber collection area be after 1000000000 minutes?
 #[aoc(day18, part2)]
-fn solve_part2(acres: &Acres) -> usize {
-    let mut pattern_start = 0;
-    let mut pattern_end = 0;
-    let mut patterns: HashMap<String, usize> = HashMap::new();
-
-    let mut mutable_acres = acres.to_owned();
-    for n in 0..1_000_000_000 {
-        let current_acres = mutable_acres.to_owned();
-        mutate_acres(&current_acres, &mut mutable_acres);
-        let grid_id: String = mutable_acres
-            .values()
-            .map(|t| match t {
-                Tile::Ground => '.',
-                Tile::Tree => '|',
-                Tile::Lumberyard => '#',
-            })
-            .collect();
-        if patterns.contains_key(&grid_id) {
-            pattern_start = *patterns.get(&grid_id).unwrap();
-            pattern_end = n + 1;
-            break;
-        } else {
-            patterns.insert(grid_id, n + 1);
-        }
-    }
+pub fn solve_part2(acres: &Acres) -> Result<usize, Unsolved> {
+    solve_part2_within(acres, Budget::default())
+}
 
-    let mut mutable_acres = acres.to_owned();
-    for _ in 0..(pattern_start + ((1_000_000_000 - pattern_start) % (pattern_end - pattern_start)))
-    {
-        let current_acres = mutable_acres.to_owned();
-        mutate_acres(&current_acres, &mut mutable_acres);
-    }
-    let wooded_acres = mutable_acres
-        .iter()
-        .filter(|&(_, v)| *v == Tile::Tree)
-        .count();
-    let lumber_acres = mutable_acres
-        .iter()
-        .filter(|&(_, v)| *v == Tile::Lumberyard)
-        .count();
-    wooded_acres * lumber_acres
+/// Part two, giving up after `budget` minutes without the area repeating itself.
+pub fn solve_part2_within(acres: &Acres, budget: Budget) -> Result<usize, Unsolved> {
+    let cycle = brent(acres, &mut budget.start(), mutate_acres, Acres::clone)?;
+    Ok(resource_value(&cycle.state_at(
+        acres.to_owned(),
+        mutate_acres,
+        1_000_000_000,
+    )))
 }
 
 #[cfg(test)]
@@ -176,8 +146,46 @@ mod tests {
 ||...#|.#|
 |.||||..|.
 ...#.|..|.",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 1147);
     }
+
+    #[test]
+    fn minutes() {
+        let input = input_generator(
+            ".#.#...|#.
+.....#|##|
+.|..|...#.
+..|#.....#
+#.#|||#|#|
+...#.||...
+.|....|...
+||...#|.#|
+|.||||..|.
+...#.|..|.",
+        )
+        .unwrap();
+        let mut values = Vec::new();
+        let lumber = Runner::new(Lumber::new(&input))
+            .observe(|minute, lumber| values.push((minute, lumber.resource_value())))
+            .run_for(10);
+        assert_eq!(lumber.minutes(), 10);
+        assert_eq!(values.len(), 11);
+        assert_eq!(values[10], (10, 1147));
+        assert_eq!(
+            lumber.to_string(),
+            ".||##.....
+||###.....
+||##......
+|##.....##
+|##.....##
+|##....##|
+||##.####|
+||#####|||
+||||#|||||
+||||||||||"
+        );
+    }
 }
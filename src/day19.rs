@@ -1,178 +1,49 @@
+//! Go With The Flow: an ElfCode `Program` with its instruction pointer bound to a register.
+//!
+//! Part one runs the program. With register 0 starting at 1 it would run for far too long, so
+//! part two lets it set up the number it means to sum the divisors of and sums them itself.
+use crate::budget::Unsolved;
+use crate::elfcode::{parse_program, Machine, Program};
+use crate::error::ParseError;
 use aoc_runner_derive::{aoc, aoc_generator};
-use regex::Regex;
-
-#[allow(non_camel_case_types)]
-#[derive(Debug, Clone)]
-enum Opcode {
-    addr,
-    addi,
-    mulr,
-    muli,
-    banr,
-    bani,
-    borr,
-    bori,
-    setr,
-    seti,
-    gtir,
-    gtri,
-    gtrr,
-    eqir,
-    eqri,
-    eqrr,
-}
-
-#[derive(Debug, Clone)]
-struct Program {
-    registers: [usize; 6],
-    instruction_pointer: usize,
-    instructions: Vec<(Opcode, [usize; 3])>,
-}
-
-impl Program {
-    fn handle_opcode(&self, opcode: &Opcode, a: usize, b: usize) -> usize {
-        match opcode {
-            Opcode::addr => self.registers[a] + self.registers[b],
-            Opcode::addi => self.registers[a] + b,
-            Opcode::mulr => self.registers[a] * self.registers[b],
-            Opcode::muli => self.registers[a] * b,
-            Opcode::banr => self.registers[a] & self.registers[b],
-            Opcode::bani => self.registers[a] & b,
-            Opcode::borr => self.registers[a] | self.registers[b],
-            Opcode::bori => self.registers[a] | b,
-            Opcode::setr => self.registers[a],
-            Opcode::seti => a,
-            Opcode::gtir => {
-                if a > self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::gtri => {
-                if self.registers[a] > b {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::gtrr => {
-                if self.registers[a] > self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::eqir => {
-                if a == self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::eqri => {
-                if self.registers[a] == b {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::eqrr => {
-                if self.registers[a] == self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
-        }
-    }
-}
 
 #[aoc_generator(day19)]
-fn input_generator(input: &str) -> Program {
-    let mut program = Program {
-        registers: [0, 0, 0, 0, 0, 0],
-        instruction_pointer: 0,
-        instructions: Vec::new(),
-    };
-    let re = Regex::new(r"(\w{4})\s(\d+)\s(\d+)\s(\d+)").unwrap();
-    for line in input.lines() {
-        let bytes = line.as_bytes();
-        if bytes[0] == b'#' {
-            program.instruction_pointer = (bytes[4] as char).to_digit(10).unwrap() as usize;
-            continue;
-        }
-        let caps = re.captures(line).unwrap();
-        let instructions: [usize; 3] = [
-            caps[2].parse().unwrap(),
-            caps[3].parse().unwrap(),
-            caps[4].parse().unwrap(),
-        ];
-        let instruction = match &caps[1] {
-            "addr" => (Opcode::addr, instructions),
-            "addi" => (Opcode::addi, instructions),
-            "mulr" => (Opcode::mulr, instructions),
-            "muli" => (Opcode::muli, instructions),
-            "banr" => (Opcode::banr, instructions),
-            "bani" => (Opcode::bani, instructions),
-            "borr" => (Opcode::borr, instructions),
-            "bori" => (Opcode::bori, instructions),
-            "setr" => (Opcode::setr, instructions),
-            "seti" => (Opcode::seti, instructions),
-            "gtir" => (Opcode::gtir, instructions),
-            "gtri" => (Opcode::gtri, instructions),
-            "gtrr" => (Opcode::gtrr, instructions),
-            "eqir" => (Opcode::eqir, instructions),
-            "eqri" => (Opcode::eqri, instructions),
-            "eqrr" => (Opcode::eqrr, instructions),
-            _ => unreachable!(),
-        };
-        program.instructions.push(instruction);
-    }
-    program
+pub fn input_generator(input: &str) -> Result<Program, ParseError> {
+    parse_program(19, input, 6)
 }
 
 /// What value is left in register 0 when the background process halts?
 #[aoc(day19, part1)]
-fn solve_part1(program: &Program) -> usize {
-    let mut our_program = program.to_owned();
-    let register_bound = our_program.instruction_pointer;
-    our_program.instruction_pointer = our_program.registers[register_bound];
-    while our_program.instruction_pointer < our_program.instructions.len() {
-        our_program.registers[register_bound] = our_program.instruction_pointer;
-        let instruction = &our_program.instructions[our_program.instruction_pointer];
-        let [a, b, c] = instruction.1;
-        let result = our_program.handle_opcode(&instruction.0, a, b);
-        our_program.registers[c] = result;
-        our_program.instruction_pointer = our_program.registers[register_bound] + 1;
-    }
-    our_program.registers[0]
+pub fn solve_part1(program: &Program) -> usize {
+    let mut machine = Machine::with_program(6, program);
+    machine.run();
+    machine.registers[0]
 }
 
 /// What value is left in register 0 when this new background process halts?
 #[aoc(day19, part2)]
-fn solve_part2(program: &Program) -> usize {
-    let mut our_program = program.to_owned();
-    let register_bound = our_program.instruction_pointer;
-    our_program.registers[0] = 1;
-    our_program.instruction_pointer = our_program.registers[register_bound];
-    while our_program.registers[register_bound] != 1 {
-        our_program.registers[register_bound] = our_program.instruction_pointer;
-        let instruction = &our_program.instructions[our_program.instruction_pointer];
-        let [a, b, c] = instruction.1;
-        let result = our_program.handle_opcode(&instruction.0, a, b);
-        our_program.registers[c] = result;
-        our_program.instruction_pointer = our_program.registers[register_bound] + 1;
+pub fn solve_part2(program: &Program) -> Result<usize, Unsolved> {
+    let mut machine = Machine::with_program(6, program);
+    let register_bound = machine
+        .ip_register()
+        .ok_or(Unsolved::NoSolution("the program has no `#ip` line"))?;
+    machine.registers[0] = 1;
+    while machine.registers[register_bound] != 1 {
+        if !machine.step() {
+            return Err(Unsolved::NoSolution(
+                "the program halts before working out its number",
+            ));
+        }
     }
 
-    let target = *our_program.registers.iter().max().unwrap();
+    let target = *machine.registers.iter().max().unwrap();
     let mut total = 0;
     for i in 1..=target {
         if target % i == 0 {
             total += i;
         }
     }
-    total
+    Ok(total)
 }
 
 #[cfg(test)]
@@ -190,7 +61,8 @@ addr 1 2 3
 setr 1 0 0
 seti 8 0 4
 seti 9 0 5",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 6);
     }
@@ -206,8 +78,17 @@ addr 1 2 3
 setr 1 0 0
 seti 8 0 4
 seti 9 0 5",
-        );
+        )
+        .unwrap();
         let result = solve_part2(&input);
-        assert_eq!(result, 1);
+        assert_eq!(result, Ok(1));
+    }
+
+    #[test]
+    fn part2_without_ip() {
+        let input = input_generator("seti 5 0 1").unwrap();
+        assert!(matches!(solve_part2(&input), Err(Unsolved::NoSolution(_))));
+        let input = input_generator("#ip 1\nseti 5 0 2").unwrap();
+        assert!(matches!(solve_part2(&input), Err(Unsolved::NoSolution(_))));
     }
 }
@@ -1,53 +1,267 @@
+//! Inventory Management System: a list of box IDs, which the solvers take as text.
+//!
+//! Part one is the checksum of the IDs with some letter exactly twice or three times, which
+//! `checksum` works out for any multiplicities. Part two is the letters shared by the two IDs
+//! that differ in one place; `near_pairs` finds every such pair, and `clusters` groups IDs
+//! within any distance of each other.
+use crate::budget::Unsolved;
 use aoc_runner_derive::aoc;
 use hashbrown::HashMap;
+use std::collections::BTreeMap;
 
 #[aoc(day2, part1)]
-pub fn solve_part1(input: &str) -> u32 {
-    let (mut doubles, mut triples) = (0, 0);
-    for id in input.lines() {
-        let mut map: HashMap<_, u32> = HashMap::new();
+pub fn solve_part1(input: &str) -> Result<usize, Unsolved> {
+    checksum(input.lines(), &[2, 3])
+        .product()
+        .ok_or(Unsolved::NoSolution("the checksum overflows a usize"))
+}
+
+/// For each multiplicity asked about, how many IDs have some character exactly that many
+/// times.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checksum {
+    pub counts: BTreeMap<usize, usize>,
+}
+
+impl Checksum {
+    /// The counts multiplied together, or `None` if that overflows.
+    pub fn product(&self) -> Option<usize> {
+        self.counts
+            .values()
+            .try_fold(1_usize, |product, &count| product.checked_mul(count))
+    }
+}
+
+/// Counts the IDs with some character exactly `n` times, for each `n` in `multiplicities`.
+///
+/// Characters are Unicode scalar values, so an accent written as a combining mark counts
+/// apart from the letter it sits on.
+pub fn checksum<'a>(ids: impl IntoIterator<Item = &'a str>, multiplicities: &[usize]) -> Checksum {
+    let mut counts: BTreeMap<usize, usize> = multiplicities.iter().map(|&n| (n, 0)).collect();
+    for id in ids {
+        let mut map: HashMap<char, usize> = HashMap::new();
         id.chars().for_each(|c| {
             *map.entry(c).or_default() += 1;
         });
-        doubles += map.values().any(|&x| x == 2) as u32;
-        triples += map.values().any(|&x| x == 3) as u32;
+        for (multiplicity, count) in counts.iter_mut() {
+            *count += usize::from(map.values().any(|x| x == multiplicity));
+        }
     }
-    doubles * triples
+    Checksum { counts }
 }
 
 #[aoc(day2, part2)]
-pub fn solve_part2(input: &str) -> String {
+pub fn solve_part2(input: &str) -> Result<String, Unsolved> {
     let ids: Vec<&str> = input.lines().collect();
-    for (idx, current) in ids.iter().enumerate() {
-        for next in ids.iter().skip(idx + 1) {
-            if is_single_distance(current, next) {
-                return current
-                    .chars()
-                    .zip(next.chars())
-                    .filter_map(|(c1, c2)| if c1 == c2 { Some(c1) } else { None })
-                    .collect();
+    let pair = near_pairs(&ids)
+        .into_iter()
+        .next()
+        .ok_or(Unsolved::NoSolution("no two ids differ by one character"))?;
+    Ok(common_letters(ids[pair.first], ids[pair.second]))
+}
+
+/// Two IDs, by their index in the list, that differ only at `position`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub struct NearPair {
+    pub first: usize,
+    pub second: usize,
+    pub position: usize,
+}
+
+/// The letters `first` and `second` have in the same places.
+pub fn common_letters(first: &str, second: &str) -> String {
+    first
+        .chars()
+        .zip(second.chars())
+        .filter_map(|(c1, c2)| if c1 == c2 { Some(c1) } else { None })
+        .collect()
+}
+
+/// Every pair of IDs that differ in exactly one position, in order of the first ID and then
+/// the second.
+///
+/// Rather than comparing every pair, each ID is hashed once with each of its positions masked
+/// out. Two IDs differing only at a position share that position's masked hash, so only the
+/// IDs in the same bucket need comparing. The masked hashes come from taking the position's
+/// term out of a polynomial hash of the whole ID, which keeps the search O(n·L).
+pub fn near_pairs(ids: &[&str]) -> Vec<NearPair> {
+    const BASE: u64 = 0x0100_0000_01b3;
+    let ids: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+    let longest = ids.iter().map(Vec::len).max().unwrap_or(0);
+    let powers: Vec<u64> = (0..longest)
+        .scan(1_u64, |power, _| {
+            let current = *power;
+            *power = power.wrapping_mul(BASE);
+            Some(current)
+        })
+        .collect();
+
+    // (length, masked position, masked hash) to the IDs with that hash
+    let mut buckets: HashMap<(usize, usize, u64), Vec<usize>> = HashMap::new();
+    for (index, id) in ids.iter().enumerate() {
+        let terms: Vec<u64> = id
+            .iter()
+            .zip(&powers)
+            .map(|(&c, power)| (u64::from(c) + 1).wrapping_mul(*power))
+            .collect();
+        let hash = terms
+            .iter()
+            .fold(0_u64, |hash, term| hash.wrapping_add(*term));
+        for (position, term) in terms.iter().enumerate() {
+            let key = (id.len(), position, hash.wrapping_sub(*term));
+            buckets.entry(key).or_default().push(index);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for ((_, position, _), indices) in buckets {
+        for (i, &first) in indices.iter().enumerate() {
+            for &second in &indices[i + 1..] {
+                let (a, b) = (&ids[first], &ids[second]);
+                // a shared hash is only a hint, so check the rest of the IDs really do match
+                if a[position] != b[position]
+                    && a.iter().zip(b).filter(|(c1, c2)| c1 != c2).count() == 1
+                {
+                    pairs.push(NearPair {
+                        first,
+                        second,
+                        position,
+                    });
+                }
             }
         }
     }
-    unreachable!();
+    pairs.sort();
+    pairs
+}
+
+/// How `clusters` measures the distance between two IDs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Distance {
+    /// The places two IDs of the same length differ in. IDs of different lengths are never
+    /// close.
+    Hamming,
+    /// The fewest insertions, deletions and substitutions turning one ID into the other.
+    Levenshtein,
+}
+
+impl Distance {
+    /// How far apart `first` and `second` are, if they can be compared at all.
+    pub fn between(self, first: &[char], second: &[char]) -> Option<usize> {
+        match self {
+            Self::Hamming if first.len() != second.len() => None,
+            Self::Hamming => Some(first.iter().zip(second).filter(|(a, b)| a != b).count()),
+            Self::Levenshtein => Some(levenshtein(first, second)),
+        }
+    }
+}
+
+fn levenshtein(first: &[char], second: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=second.len()).collect();
+    for (i, a) in first.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in second.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[second.len()]
+}
+
+/// The longest run of characters, not necessarily adjacent, that `first` and `second` share
+/// in the same order.
+fn common_subsequence(first: &[char], second: &[char]) -> Vec<char> {
+    let mut lengths = vec![vec![0_usize; second.len() + 1]; first.len() + 1];
+    for (i, a) in first.iter().enumerate().rev() {
+        for (j, b) in second.iter().enumerate().rev() {
+            lengths[i][j] = if a == b {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j, mut common) = (0, 0, Vec::new());
+    while i < first.len() && j < second.len() {
+        if first[i] == second[j] {
+            common.push(first[i]);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    common
 }
 
-fn is_single_distance(first: &str, second: &str) -> bool {
-    if first.len() != second.len() {
-        return false;
+/// The root of `index`'s tree in a union-find forest, shortening the path there on the way.
+fn root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
     }
+    index
+}
+
+/// IDs close enough to each other to be grouped together, by their index in the list.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cluster {
+    pub members: Vec<usize>,
+    /// The characters every member shares: in the same places for `Distance::Hamming`, and in
+    /// the same order for `Distance::Levenshtein`.
+    pub common: String,
+}
 
-    let mut difference_found = false;
-    for (c1, c2) in first.chars().zip(second.chars()) {
-        if c1 != c2 {
-            if difference_found {
-                return false;
+/// Groups the IDs that are within `k` of each other, and of each other's neighbours in turn,
+/// in order of their first member. IDs within `k` of no other ID are left out.
+///
+/// With `Distance::Levenshtein` the common characters are found by taking the longest
+/// common subsequence of the first two members, then of that and the third, and so on, which
+/// can miss a longer subsequence when there are more than two.
+pub fn clusters(ids: &[&str], k: usize, distance: Distance) -> Vec<Cluster> {
+    let ids: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+    // each ID's parent in a union-find forest, with each cluster's root as its parent
+    let mut parents: Vec<usize> = (0..ids.len()).collect();
+    for first in 0..ids.len() {
+        for second in first + 1..ids.len() {
+            let close = distance
+                .between(&ids[first], &ids[second])
+                .is_some_and(|d| d <= k);
+            if close {
+                let (a, b) = (root(&mut parents, first), root(&mut parents, second));
+                parents[a.max(b)] = a.min(b);
             }
-            difference_found = true;
         }
     }
 
-    true
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for index in 0..ids.len() {
+        let group = root(&mut parents, index);
+        groups.entry(group).or_default().push(index);
+    }
+    groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let first = &ids[members[0]];
+            let common = match distance {
+                Distance::Hamming => (0..first.len())
+                    .filter(|&i| members.iter().all(|&member| ids[member][i] == first[i]))
+                    .map(|i| first[i])
+                    .collect(),
+                Distance::Levenshtein => members[1..].iter().fold(first.clone(), |common, &m| {
+                    common_subsequence(&common, &ids[m])
+                }),
+            };
+            Cluster {
+                members,
+                common: common.into_iter().collect(),
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -65,7 +279,13 @@ aabcdd
 abcdee
 ababab",
         );
-        assert_eq!(result, 12);
+        assert_eq!(result, Ok(12));
+    }
+
+    #[test]
+    fn part1_wide() {
+        let input = vec!["aabbb"; 70_000].join("\n");
+        assert_eq!(solve_part1(&input), Ok(4_900_000_000));
     }
 
     #[test]
@@ -79,6 +299,90 @@ fguij
 axcye
 wvxyz",
         );
-        assert_eq!(result, "fgij");
+        assert_eq!(result, Ok("fgij".to_owned()));
+    }
+
+    #[test]
+    fn part2_unsolvable() {
+        assert!(solve_part2("").is_err());
+        assert!(solve_part2("garbage").is_err());
+    }
+
+    #[test]
+    fn pairs() {
+        let ids = [
+            "abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz", "fghia",
+        ];
+        let pairs = near_pairs(&ids);
+        assert_eq!(
+            pairs,
+            vec![
+                NearPair {
+                    first: 1,
+                    second: 4,
+                    position: 2
+                },
+                NearPair {
+                    first: 1,
+                    second: 7,
+                    position: 4
+                }
+            ]
+        );
+        assert_eq!(common_letters(ids[1], ids[7]), "fghi");
+        // identical IDs and IDs of different lengths are never a pair
+        assert_eq!(near_pairs(&["abc", "abc", "ab", "abcd", "xbcd"]).len(), 1);
+        assert_eq!(near_pairs(&["日本語", "日本人"])[0].position, 2);
+    }
+
+    #[test]
+    fn clustering() {
+        let ids = [
+            "abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz",
+        ];
+        let members = |k, distance| -> Vec<Vec<usize>> {
+            clusters(&ids, k, distance)
+                .into_iter()
+                .map(|cluster| cluster.members)
+                .collect()
+        };
+        assert_eq!(members(1, Distance::Hamming), vec![vec![1, 4]]);
+        assert_eq!(members(2, Distance::Hamming), vec![vec![0, 5], vec![1, 4]]);
+        assert_eq!(
+            clusters(&ids, 2, Distance::Hamming)[0].common,
+            "ace".to_owned()
+        );
+
+        let ids = ["abcde", "zzz", "abde", "abxde", "abxdef", "zz"];
+        let found = clusters(&ids, 1, Distance::Levenshtein);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].members, vec![0, 2, 3, 4]);
+        assert_eq!(found[0].common, "abde");
+        assert_eq!(found[1].members, vec![1, 5]);
+        assert_eq!(clusters(&ids, 1, Distance::Hamming)[0].members, vec![0, 3]);
+        assert_eq!(
+            Distance::Levenshtein.between(&['k', 'i', 't'], &['s', 'i', 't', 's']),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn checksums() {
+        let ids = "abcdef\nbababc\nabbcde\nabcccd\naabcdd\nabcdee\nababab";
+        let sum = checksum(ids.lines(), &[2, 3]);
+        assert_eq!(
+            sum.counts.into_iter().collect::<Vec<_>>(),
+            vec![(2, 4), (3, 3)]
+        );
+        let sum = checksum(ids.lines(), &[1, 2, 3, 4]);
+        assert_eq!(sum.counts[&1], 6);
+        assert_eq!(sum.counts[&4], 0);
+        assert_eq!(sum.product(), Some(0));
+        assert_eq!(checksum(ids.lines(), &[]).product(), Some(1));
+
+        let sum = checksum(vec!["ééa", "日日日本", "e\u{301}e\u{301}"], &[2, 3]);
+        assert_eq!(sum.counts[&2], 2);
+        assert_eq!(sum.counts[&3], 1);
+        assert_eq!(sum.product(), Some(2));
     }
 }
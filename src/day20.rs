@@ -1,20 +1,90 @@
-/// I originally tried to implement a parser approach using Pest.rs to explore that library.
-/// This is an implementation by a redditor from r/adventofcode.
-///
-/// Hoping to return and redo a solution using Pest or another parser.
+//! A Regular Map: a regex describing every route through a facility of rooms and doors.
+//!
+//! `doors` follows the regex from the starting room to find every door it passes through,
+//! and `distances` then finds the shortest way to each room; `input_generator` does both.
+//! Part one is the most doors to any room, and part two counts the rooms at least 1000
+//! doors away; `solve_part1_with_witness` also returns the `Path` to the furthest room.
+//!
+//! I originally tried to implement a parser approach using Pest.rs to explore that library.
+//! This is an implementation by a redditor from r/adventofcode.
+//!
+//! Hoping to return and redo a solution using Pest or another parser.
+use crate::error::{lines, Line, ParseError};
 use aoc_runner_derive::{aoc, aoc_generator};
 use hashbrown::HashMap;
 use pathfinding::prelude::dijkstra_all;
+use std::cmp::Reverse;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 
-type Point = (isize, isize);
+/// A room, as `(x, y)` from the starting room with y growing to the south.
+pub type Point = (isize, isize);
+
+/// Each room and the rooms the regex steps into from it, away from the start.
+pub type Doors = BTreeMap<Point, BTreeSet<Point>>;
+
+fn route(input: &str) -> Result<Line<'_>, ParseError> {
+    lines(20, input.trim())
+        .next()
+        .ok_or_else(|| ParseError::new(20, 1, 1, "", "missing route regex"))
+}
+
+/// Everything that stops `line` from being a route `explore` can follow: a missing `^` or
+/// `$`, an unexpected character, unbalanced parentheses, or a `|` outside them.
+fn check_route(line: &Line) -> Vec<ParseError> {
+    if !line.text.starts_with('^') {
+        return vec![line.error(line.text, "expected `^`")];
+    }
+    if !line.text.ends_with('$') {
+        return vec![line.error(&line.text[line.text.len()..], "expected `$`")];
+    }
+    let body = &line.text[1..line.text.len() - 1];
+    if let Some(index) = body.find(|c| !"NESW|()".contains(c)) {
+        return vec![line.error(&body[index..], "unexpected character")];
+    }
+
+    let mut errors = Vec::new();
+    let mut open = Vec::new();
+    for (index, c) in body.char_indices() {
+        let problem = match c {
+            '(' => {
+                open.push(index);
+                None
+            }
+            ')' => open
+                .pop()
+                .map_or(Some("`)` without a matching `(`"), |_| None),
+            '|' if open.is_empty() => Some("`|` outside any parentheses"),
+            _ => None,
+        };
+        if let Some(reason) = problem {
+            errors.push(line.error(&body[index..], reason));
+        }
+    }
+    for index in open {
+        errors.push(line.error(&body[index..], "`(` is never closed"));
+    }
+    errors
+}
+
+/// Follows every branch of the route regex from the starting room at `(0, 0)`.
+pub fn doors(input: &str) -> Result<Doors, ParseError> {
+    let line = route(input)?;
+    if let Some(error) = check_route(&line).into_iter().next() {
+        return Err(error);
+    }
 
-#[aoc_generator(day20)]
-fn input_generator(input: &str) -> HashMap<Point, (Point, isize)> {
     let mut map = BTreeMap::new();
-    explore(&mut map, (0, 0), input.as_bytes(), &mut 1);
+    explore(&mut map, (0, 0), line.text.as_bytes(), &mut 1);
+    Ok(map)
+}
+
+/// Every room the starting room leads to, with the room before it on the shortest way there
+/// and how many doors that passes through.
+pub fn distances(doors: &Doors) -> HashMap<Point, (Point, isize)> {
     dijkstra_all(&(0, 0), |pos| {
-        map.get(pos)
+        doors
+            .get(pos)
             .into_iter()
             .flat_map(|neighbours| neighbours.iter().map(|n| (*n, 1)))
     })
@@ -22,22 +92,80 @@ fn input_generator(input: &str) -> HashMap<Point, (Point, isize)> {
     .collect()
 }
 
+#[aoc_generator(day20)]
+pub fn input_generator(input: &str) -> Result<HashMap<Point, (Point, isize)>, ParseError> {
+    Ok(distances(&doors(input)?))
+}
+
+/// Reports every problem with the route regex, where the generator stops at the first.
+pub fn validate(input: &str) -> Vec<ParseError> {
+    match route(input) {
+        Ok(line) => check_route(&line),
+        Err(error) => vec![error],
+    }
+}
+
+/// The way from the starting room to another, as the directions of the doors it goes through.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path {
+    pub room: Point,
+    pub directions: String,
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{},{} is {} doors away by ^{}$",
+            self.room.0,
+            self.room.1,
+            self.directions.len(),
+            self.directions
+        )
+    }
+}
+
+/// The shortest path to `room`, following the rooms before it back to the start.
+fn path(cells: &HashMap<Point, (Point, isize)>, room: Point) -> Path {
+    let mut directions = Vec::new();
+    let mut current = room;
+    while let Some(&(previous, _)) = cells.get(&current) {
+        directions.push(match (current.0 - previous.0, current.1 - previous.1) {
+            (0, -1) => 'N',
+            (1, 0) => 'E',
+            (0, 1) => 'S',
+            _ => 'W',
+        });
+        current = previous;
+    }
+    directions.reverse();
+    Path {
+        room,
+        directions: directions.into_iter().collect(),
+    }
+}
+
 #[aoc(day20, part1)]
-fn solve_part1(cells: &HashMap<Point, (Point, isize)>) -> isize {
+pub fn solve_part1(cells: &HashMap<Point, (Point, isize)>) -> isize {
     cells.values().map(|(_, c)| *c).max().unwrap()
 }
 
+/// Part one's answer along with the path to the furthest room, the first in reading order if
+/// several are as far.
+pub fn solve_part1_with_witness(cells: &HashMap<Point, (Point, isize)>) -> (isize, Path) {
+    let (&room, &(_, doors)) = cells
+        .iter()
+        .max_by_key(|&(&(x, y), &(_, doors))| (doors, Reverse((y, x))))
+        .unwrap();
+    (doors, path(cells, room))
+}
+
 #[aoc(day20, part2)]
-fn solve_part2(cells: &HashMap<Point, (Point, isize)>) -> usize {
+pub fn solve_part2(cells: &HashMap<Point, (Point, isize)>) -> usize {
     cells.values().filter(|&(_, c)| *c >= 1000).count()
 }
 
-fn explore(
-    map: &mut BTreeMap<Point, BTreeSet<Point>>,
-    start: Point,
-    input: &[u8],
-    index: &mut usize,
-) -> Vec<Point> {
+fn explore(map: &mut Doors, start: Point, input: &[u8], index: &mut usize) -> Vec<Point> {
     let mut exits = vec![start];
     loop {
         match input[*index] {
@@ -73,16 +201,25 @@ mod tests {
 
     #[test]
     fn part1() {
-        let input = input_generator("^ENWWW(NEEE|SSE(EE|N))$");
+        let input = input_generator("^ENWWW(NEEE|SSE(EE|N))$").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 10);
 
-        let input = input_generator("^WNE$");
+        let input = input_generator("^WNE$").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 3);
 
-        let input = input_generator("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$");
+        let input = input_generator("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 18);
     }
+
+    #[test]
+    fn witness() {
+        let input = input_generator("^ENWWW(NEEE|SSE(EE|N))$").unwrap();
+        let (result, path) = solve_part1_with_witness(&input);
+        assert_eq!(result, 10);
+        assert_eq!(path.room, (1, 1));
+        assert_eq!(path.to_string(), "1,1 is 10 doors away by ^ENWWWSSEEE$");
+    }
 }
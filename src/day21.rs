@@ -1,182 +1,77 @@
+//! Chronal Conversion: an ElfCode `Program` that only halts once register 0 equals a value
+//! it works out.
+//!
+//! Part one is the first value compared against register 0, and part two the last one before
+//! the values start repeating. Part two runs the program itself, so the registry has it as
+//! the `slow` variant.
+use crate::budget::{Budget, Unsolved};
+use crate::cycle::memo;
+use crate::elfcode::{parse_program, Machine, Opcode, Program};
+use crate::error::ParseError;
 use aoc_runner_derive::{aoc, aoc_generator};
-use hashbrown::HashSet;
-use regex::Regex;
 
-#[allow(non_camel_case_types)]
-#[derive(Debug, Clone, PartialEq)]
-enum Opcode {
-    addr,
-    addi,
-    mulr,
-    muli,
-    banr,
-    bani,
-    borr,
-    bori,
-    setr,
-    seti,
-    gtir,
-    gtri,
-    gtrr,
-    eqir,
-    eqri,
-    eqrr,
-}
-
-#[derive(Debug, Clone)]
-struct Program {
-    registers: [usize; 6],
-    instruction_pointer: usize,
-    instructions: Vec<(Opcode, [usize; 3])>,
+#[aoc_generator(day21)]
+pub fn input_generator(input: &str) -> Result<Program, ParseError> {
+    parse_program(21, input, 6)
 }
 
-impl Program {
-    fn handle_opcode(&self, opcode: &Opcode, a: usize, b: usize) -> usize {
-        match opcode {
-            Opcode::addr => self.registers[a] + self.registers[b],
-            Opcode::addi => self.registers[a] + b,
-            Opcode::mulr => self.registers[a] * self.registers[b],
-            Opcode::muli => self.registers[a] * b,
-            Opcode::banr => self.registers[a] & self.registers[b],
-            Opcode::bani => self.registers[a] & b,
-            Opcode::borr => self.registers[a] | self.registers[b],
-            Opcode::bori => self.registers[a] | b,
-            Opcode::setr => self.registers[a],
-            Opcode::seti => a,
-            Opcode::gtir => {
-                if a > self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::gtri => {
-                if self.registers[a] > b {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::gtrr => {
-                if self.registers[a] > self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::eqir => {
-                if a == self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::eqri => {
-                if self.registers[a] == b {
-                    1
-                } else {
-                    0
-                }
-            }
-            Opcode::eqrr => {
-                if self.registers[a] == self.registers[b] {
-                    1
-                } else {
-                    0
-                }
-            }
+/// Runs until the program compares register 0 against another register, the only way it can
+/// halt, and returns the value register 0 would need to hold. `None` if the program halts
+/// without comparing.
+fn run_to_check(machine: &mut Machine) -> Option<usize> {
+    while let Some(&instruction) = machine.current() {
+        if instruction.opcode == Opcode::eqrr && instruction.b == 0 {
+            return Some(machine.registers[instruction.a]);
         }
+        machine.step();
     }
+    None
 }
 
-#[aoc_generator(day21)]
-fn input_generator(input: &str) -> Program {
-    let mut program = Program {
-        registers: [0, 0, 0, 0, 0, 0],
-        instruction_pointer: 0,
-        instructions: Vec::new(),
-    };
-    let re = Regex::new(r"(\w{4})\s(\d+)\s(\d+)\s(\d+)").unwrap();
-    for line in input.lines() {
-        let bytes = line.as_bytes();
-        if bytes[0] == b'#' {
-            program.instruction_pointer = (bytes[4] as char).to_digit(10).unwrap() as usize;
-            continue;
-        }
-        let caps = re.captures(line).unwrap();
-        let instructions: [usize; 3] = [
-            caps[2].parse().unwrap(),
-            caps[3].parse().unwrap(),
-            caps[4].parse().unwrap(),
-        ];
-        let instruction = match &caps[1] {
-            "addr" => (Opcode::addr, instructions),
-            "addi" => (Opcode::addi, instructions),
-            "mulr" => (Opcode::mulr, instructions),
-            "muli" => (Opcode::muli, instructions),
-            "banr" => (Opcode::banr, instructions),
-            "bani" => (Opcode::bani, instructions),
-            "borr" => (Opcode::borr, instructions),
-            "bori" => (Opcode::bori, instructions),
-            "setr" => (Opcode::setr, instructions),
-            "seti" => (Opcode::seti, instructions),
-            "gtir" => (Opcode::gtir, instructions),
-            "gtri" => (Opcode::gtri, instructions),
-            "gtrr" => (Opcode::gtrr, instructions),
-            "eqir" => (Opcode::eqir, instructions),
-            "eqri" => (Opcode::eqri, instructions),
-            "eqrr" => (Opcode::eqrr, instructions),
-            _ => unreachable!(),
-        };
-        program.instructions.push(instruction);
-    }
-    program
-}
+const NO_CHECK: Unsolved = Unsolved::NoSolution("the program halts without checking register 0");
 
 /// What is the lowest non-negative integer value for register 0 that causes the program
 /// to halt after executing the fewest instructions?
 #[aoc(day21, part1)]
-fn solve_part1(program: &Program) -> usize {
-    let mut our_program = program.to_owned();
-    let register_bound = our_program.instruction_pointer;
-    our_program.instruction_pointer = our_program.registers[register_bound];
-    while our_program.instruction_pointer < our_program.instructions.len() {
-        our_program.registers[register_bound] = our_program.instruction_pointer;
-        let instruction = &our_program.instructions[our_program.instruction_pointer];
-        let [a, b, c] = instruction.1;
-        if instruction.0 == Opcode::eqrr && b == 0 {
-            return our_program.registers[a];
-        }
-        let result = our_program.handle_opcode(&instruction.0, a, b);
-        our_program.registers[c] = result;
-        our_program.instruction_pointer = our_program.registers[register_bound] + 1;
-    }
-    unreachable!();
+pub fn solve_part1(program: &Program) -> Result<usize, Unsolved> {
+    let mut machine = Machine::with_program(6, program);
+    run_to_check(&mut machine).ok_or(NO_CHECK)
 }
 
 /// What is the lowest non-negative integer value for register 0 that causes the program
 /// to halt after executing the most instructions?
+///
+/// The values compared against eventually repeat, so the answer is the last one before they do.
 #[aoc(day21, part2, slow)]
-fn solve_part2(program: &Program) -> usize {
-    let mut values_seen = HashSet::new();
-    let mut last_value = 0;
+pub fn solve_part2(program: &Program) -> Result<usize, Unsolved> {
+    solve_part2_within(program, Budget::default())
+}
 
-    let mut our_program = program.to_owned();
-    let register_bound = our_program.instruction_pointer;
-    our_program.instruction_pointer = our_program.registers[register_bound];
-    while our_program.instruction_pointer < our_program.instructions.len() {
-        our_program.registers[register_bound] = our_program.instruction_pointer;
-        let instruction = &our_program.instructions[our_program.instruction_pointer];
-        let [a, b, c] = instruction.1;
-        if instruction.0 == Opcode::eqrr && b == 0 {
-            if !values_seen.insert(our_program.registers[a]) {
-                return last_value;
-            }
-            last_value = our_program.registers[a];
-        }
-        let result = our_program.handle_opcode(&instruction.0, a, b);
-        our_program.registers[c] = result;
-        our_program.instruction_pointer = our_program.registers[register_bound] + 1;
+/// Part two, giving up after `budget` values compared against without one repeating.
+pub fn solve_part2_within(program: &Program, budget: Budget) -> Result<usize, Unsolved> {
+    let mut start = Machine::with_program(6, program);
+    run_to_check(&mut start).ok_or(NO_CHECK)?;
+    // once the program halts, it stays halted with no value to check
+    let next_check = |machine: &Machine| {
+        let mut machine = machine.clone();
+        machine.step();
+        run_to_check(&mut machine);
+        machine
+    };
+    let (cycle, mut checks) = memo(start, &mut budget.start(), next_check, |machine| {
+        run_to_check(&mut machine.clone())
+    })?;
+    run_to_check(&mut checks[cycle.prefix + cycle.period - 1]).ok_or(NO_CHECK)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day21::*;
+
+    #[test]
+    fn no_check() {
+        let input = input_generator("#ip 1\nseti 5 0 2\nseti 9 0 1").unwrap();
+        assert!(solve_part1(&input).is_err());
+        assert!(solve_part2(&input).is_err());
     }
-    unreachable!();
 }
@@ -1,170 +1,190 @@
+//! Mode Maze: a cave's depth and a target, from which the type of every region follows.
+//!
+//! `input_generator` returns the target and the grid of `RegionType`s around it. Part one sums
+//! the risk levels up to the target, and part two finds the quickest way there, switching
+//! tools as the regions demand; `solve_part2_with_witness` also returns that `Route`.
+use crate::budget::Unsolved;
+use crate::error::{lines, Line, ParseError};
+use crate::geometry::{Bounds, Point2};
+use crate::grid::{Grid, Position};
 use aoc_runner_derive::{aoc, aoc_generator};
-use hashbrown::{HashMap, HashSet};
-use std::collections::VecDeque;
-
-#[derive(Clone, Hash, Eq, PartialEq, Debug)]
-struct Point(isize, isize);
-impl Point {
-    fn neighbours(&self) -> Vec<Point> {
-        let &Point(x, y) = self;
-        vec![
-            Point(x, y - 1),
-            Point(x - 1, y),
-            Point(x + 1, y),
-            Point(x, y + 1),
-        ]
-    }
-}
-
-struct Region(usize, RegionType);
+use pathfinding::prelude::dijkstra;
+use std::fmt;
 
 #[derive(Eq, PartialEq)]
-enum RegionType {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum RegionType {
     Rocky,
     Wet,
     Narrow,
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
-enum ToolType {
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ToolType {
     Torch,
     Climbing,
     HandsFree,
 }
 
+impl ToolType {
+    /// Whether the tool can be used in a region of type `region`.
+    fn fits(self, region: &RegionType) -> bool {
+        match region {
+            RegionType::Rocky => self != Self::HandsFree,
+            RegionType::Wet => self != Self::Torch,
+            RegionType::Narrow => self != Self::Climbing,
+        }
+    }
+}
+
+impl fmt::Display for ToolType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Torch => "torch",
+            Self::Climbing => "climbing gear",
+            Self::HandsFree => "neither",
+        })
+    }
+}
+
+/// A quickest way to the target: every region passed through with the tool held there, and
+/// the minutes it takes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Route {
+    pub steps: Vec<(Position, ToolType)>,
+    pub minutes: usize,
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} minutes", self.minutes)?;
+        let mut tool = None;
+        for &((x, y), held) in &self.steps {
+            if tool != Some(held) {
+                write!(f, "; {}", held)?;
+                tool = Some(held);
+            }
+            write!(f, " {},{}", x, y)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the value of a `name: value` line.
+fn field<'a>(
+    line: Option<Line<'a>>,
+    number: usize,
+    name: &str,
+) -> Result<(Line<'a>, &'a str), ParseError> {
+    let line =
+        line.ok_or_else(|| ParseError::new(22, number, 1, "", &format!("missing {}", name)))?;
+    match line.text.split(' ').collect::<Vec<_>>().as_slice() {
+        [key, value] if key.trim_end_matches(':') == name => Ok((line, value)),
+        _ => Err(line.error(line.text, &format!("expected `{}: ...`", name))),
+    }
+}
+
+/// How far past the target the quickest route could stray.
+///
+/// Walking straight there, switching tools before every step and once more at the end, takes
+/// at most `8 * (x + y) + 7` minutes. Going `m` regions past the target and back costs `2 * m`
+/// more than the `x + y` steps it needs anyway, so no quicker route goes further.
+const fn search_margin((x, y): Position) -> usize {
+    (7 * (x + y) + 7) / 2 + 1
+}
+
 #[aoc_generator(day22)]
-fn input_generator(input: &str) -> (Point, HashMap<Point, Region>) {
-    let mut lines = input.lines();
-    let depth: usize = lines
-        .next()
-        .unwrap()
-        .split(' ')
-        .nth(1)
-        .unwrap()
-        .parse()
-        .unwrap();
-    let target: Vec<isize> = lines
-        .next()
-        .unwrap()
-        .split(' ')
-        .nth(1)
-        .unwrap()
+pub fn input_generator(input: &str) -> Result<(Position, Grid<RegionType>), ParseError> {
+    let mut lines = lines(22, input);
+    let (line, depth) = field(lines.next(), 1, "depth")?;
+    let depth: usize = line.parse(depth)?;
+    let (line, target) = field(lines.next(), 2, "target")?;
+    let target: Vec<usize> = target
         .split(',')
-        .map(|s| s.parse::<isize>().unwrap())
-        .collect();
+        .map(|s| line.parse(s))
+        .collect::<Result<_, _>>()?;
+    if target.len() != 2 {
+        return Err(line.error(line.text, "expected `target: x,y`"));
+    }
     let (target_x, target_y) = (target[0], target[1]);
 
-    let mut cave: HashMap<Point, Region> = HashMap::new();
-    for y in 0..depth as isize {
-        for x in 0..depth as isize {
+    let margin = search_margin((target_x, target_y));
+    let (width, height) = (target_x + margin + 1, target_y + margin + 1);
+    let mut erosion_levels: Grid<usize> = Grid::new(width, height, 0);
+    for y in 0..height {
+        for x in 0..width {
             let geological_index = if (x == 0 && y == 0) || (x == target_x && y == target_y) {
                 0
             } else if y == 0 {
-                x as usize * 16807
+                x * 16807
             } else if x == 0 {
-                y as usize * 48271
+                y * 48271
             } else {
-                let a = cave.get(&Point(x - 1, y)).unwrap().0;
-                let b = cave.get(&Point(x, y - 1)).unwrap().0;
-                a * b
-            };
-            let erosion_level = (geological_index + depth) % 20183;
-            let region_type = match erosion_level % 3 {
-                0 => RegionType::Rocky,
-                1 => RegionType::Wet,
-                _ => RegionType::Narrow,
+                erosion_levels[(x - 1, y)] * erosion_levels[(x, y - 1)]
             };
-            cave.insert(Point(x, y), Region(erosion_level, region_type));
+            erosion_levels[(x, y)] = (geological_index + depth) % 20183;
         }
     }
-    (Point(target_x, target_y), cave)
+    let cave = Grid::from_fn(width, height, |position| {
+        match erosion_levels[position] % 3 {
+            0 => RegionType::Rocky,
+            1 => RegionType::Wet,
+            _ => RegionType::Narrow,
+        }
+    });
+    Ok(((target_x, target_y), cave))
 }
 
 /// What is the total risk level for the smallest rectangle that includes 0,0
 /// and the target's coordinates?
 #[aoc(day22, part1)]
-fn solve_part1((target, cave): &(Point, HashMap<Point, Region>)) -> usize {
-    let mut risk_level = 0;
-    for y in 0..=target.1 {
-        for x in 0..=target.0 {
-            let region = cave.get(&Point(x, y)).unwrap();
-            risk_level += match region.1 {
-                RegionType::Rocky => 0,
-                RegionType::Wet => 1,
-                RegionType::Narrow => 2,
-            };
-        }
-    }
-    risk_level
+pub fn solve_part1((target, cave): &(Position, Grid<RegionType>)) -> usize {
+    Bounds::new(Point2::ORIGIN, Point2::from(*target))
+        .points()
+        .filter_map(|point| cave.position(point))
+        .map(|position| match cave[position] {
+            RegionType::Rocky => 0,
+            RegionType::Wet => 1,
+            RegionType::Narrow => 2,
+        })
+        .sum()
 }
 
 /// What is the fewest number of minutes you can take to reach the target?
-///
-/// TODO: Dijktra this...
 #[aoc(day22, part2)]
-fn solve_part2((target, cave): &(Point, HashMap<Point, Region>)) -> usize {
-    let mut queue: VecDeque<(Point, ToolType, usize, usize)> = VecDeque::new();
-    let mut visited: HashSet<(Point, ToolType)> = HashSet::new();
-    queue.push_back((Point(0, 0), ToolType::Torch, 0, 0));
-    visited.insert((Point(0, 0), ToolType::Torch));
-
-    while let Some((point, tool, switching, time)) = queue.pop_front() {
-        if switching > 0 {
-            if switching != 1 || visited.insert((point.clone(), tool.clone())) {
-                queue.push_back((point.clone(), tool.clone(), switching - 1, time + 1));
-            }
-            continue;
-        }
-
-        if point == *target && tool == ToolType::Torch {
-            return time;
-        }
-
-        let region_type = &cave.get(&point).unwrap().1;
-        for neighbour in point.neighbours() {
-            if neighbour.0 < 0 || neighbour.1 < 0 {
-                continue;
-            }
-            match cave.get(&neighbour).unwrap().1 {
-                RegionType::Rocky => {
-                    if tool != ToolType::HandsFree
-                        && visited.insert((neighbour.clone(), tool.clone()))
-                    {
-                        queue.push_back((neighbour.clone(), tool.clone(), 0, time + 1));
-                    }
-                }
-                RegionType::Wet => {
-                    if tool != ToolType::Torch && visited.insert((neighbour.clone(), tool.clone()))
-                    {
-                        queue.push_back((neighbour.clone(), tool.clone(), 0, time + 1));
-                    }
-                }
-                RegionType::Narrow => {
-                    if tool != ToolType::Climbing
-                        && visited.insert((neighbour.clone(), tool.clone()))
-                    {
-                        queue.push_back((neighbour.clone(), tool.clone(), 0, time + 1));
-                    }
-                }
-            };
-        }
+pub fn solve_part2(input: &(Position, Grid<RegionType>)) -> Result<usize, Unsolved> {
+    solve_part2_with_witness(input).map(|(minutes, _)| minutes)
+}
 
-        match region_type {
-            RegionType::Rocky => {
-                queue.push_back((point.clone(), ToolType::Climbing, 6, time + 1));
-                queue.push_back((point.clone(), ToolType::Torch, 6, time + 1));
-            }
-            RegionType::Wet => {
-                queue.push_back((point.clone(), ToolType::Climbing, 6, time + 1));
-                queue.push_back((point.clone(), ToolType::HandsFree, 6, time + 1));
-            }
-            RegionType::Narrow => {
-                queue.push_back((point.clone(), ToolType::HandsFree, 6, time + 1));
-                queue.push_back((point.clone(), ToolType::Torch, 6, time + 1));
-            }
-        };
+/// Part two's answer along with a route that takes that long. Switching tools shows as the
+/// same region twice in a row.
+pub fn solve_part2_with_witness(
+    (target, cave): &(Position, Grid<RegionType>),
+) -> Result<(usize, Route), Unsolved> {
+    if cave[*target] == RegionType::Wet {
+        // the torch is needed at the target but can't be held in a wet region
+        return Err(Unsolved::NoSolution(
+            "the target is in a wet region, out of the torch's reach",
+        ));
     }
-    unreachable!();
+    let tools = [ToolType::Torch, ToolType::Climbing, ToolType::HandsFree];
+    let (steps, minutes) = dijkstra(
+        &((0, 0), ToolType::Torch),
+        |&(point, tool)| {
+            let moves = cave
+                .neighbours4(point)
+                .filter(move |&neighbour| tool.fits(&cave[neighbour]))
+                .map(move |neighbour| ((neighbour, tool), 1));
+            let switches = tools
+                .iter()
+                .filter(move |&&other| other != tool && other.fits(&cave[point]))
+                .map(move |&other| ((point, other), 7));
+            moves.chain(switches).collect::<Vec<_>>()
+        },
+        |&(point, tool)| point == *target && tool == ToolType::Torch,
+    )
+    .ok_or(Unsolved::NoSolution("no route reaches the target"))?;
+    Ok((minutes, Route { steps, minutes }))
 }
 
 #[cfg(test)]
@@ -173,15 +193,47 @@ mod tests {
 
     #[test]
     fn part1() {
-        let input = input_generator("depth: 510\ntarget: 10,10");
+        let input = input_generator("depth: 510\ntarget: 10,10").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 114);
     }
 
     #[test]
     fn part2() {
-        let input = input_generator("depth: 510\ntarget: 10,10");
+        let input = input_generator("depth: 510\ntarget: 10,10").unwrap();
         let result = solve_part2(&input);
+        assert_eq!(result, Ok(45));
+    }
+
+    #[test]
+    fn witness() {
+        let input = input_generator("depth: 510\ntarget: 10,10").unwrap();
+        let (result, route) = solve_part2_with_witness(&input).unwrap();
         assert_eq!(result, 45);
+        assert_eq!(route.steps.first(), Some(&((0, 0), ToolType::Torch)));
+        assert_eq!(route.steps.last(), Some(&((10, 10), ToolType::Torch)));
+        let switches = route.steps.windows(2).filter(|w| w[0].0 == w[1].0).count();
+        assert_eq!(route.steps.len() - 1 - switches + 7 * switches, 45);
+    }
+
+    #[test]
+    fn deep_target() {
+        // a target deeper than the cave's depth is still a puzzle
+        let input = input_generator("depth: 5\ntarget: 3,12").unwrap();
+        assert_eq!(input.0, (3, 12));
+        assert!(solve_part2(&input).is_ok());
+    }
+
+    #[test]
+    fn wet_target() {
+        // depth 511 makes the target's erosion level 511, which is wet
+        let input = input_generator("depth: 511\ntarget: 3,3").unwrap();
+        assert!(solve_part1(&input) > 0);
+        assert_eq!(
+            solve_part2(&input),
+            Err(Unsolved::NoSolution(
+                "the target is in a wet region, out of the torch's reach"
+            ))
+        );
     }
 }
@@ -1,93 +1,175 @@
+//! Experimental Emergency Teleportation: the positions and signal radii of nanobots.
+//!
+//! `input_generator` maps each nanobot's position to its radius, and `read_input` reads them
+//! from a stream instead. Part one counts the nanobots in range of the strongest, and part two
+//! finds how close to the origin the point in range of the most nanobots is, which
+//! `solve_part2_with_witness` returns as a `Spot`.
+use crate::budget::{Budget, Unsolved};
+use crate::error::{lines, read_lines, Line, ParseError, ReadError};
+use crate::geometry::{Bounds, Point3};
 use aoc_runner_derive::{aoc, aoc_generator};
 use hashbrown::HashMap;
 use regex::Regex;
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::io::BufRead;
 
-#[derive(Clone, Hash, Eq, PartialEq, Debug)]
-struct Coord(isize, isize, isize);
+pub type Coord = Point3;
+
+fn nanobot_regex() -> Regex {
+    Regex::new(r"pos=<(-?\d+),(-?\d+),(-?\d+)>,\sr=(\d+)").unwrap()
+}
+
+fn parse_nanobot(re: &Regex, line: Line) -> Result<(Coord, usize), ParseError> {
+    let caps = line.captures(re)?;
+    let coord = Coord::new([
+        line.parse(&caps[1])?,
+        line.parse(&caps[2])?,
+        line.parse(&caps[3])?,
+    ]);
+    Ok((coord, line.parse::<usize>(&caps[4])?))
+}
 
 /// pos=<75543860,72403174,16415803>, r=92408569
 #[aoc_generator(day23)]
-fn input_generator(input: &str) -> HashMap<Coord, usize> {
-    let mut map = HashMap::new();
-    let re = Regex::new(r"pos=<(-?\d+),(-?\d+),(-?\d+)>,\sr=(\d+)").unwrap();
-    for line in input.lines() {
-        let caps = re.captures(line).unwrap();
-        let (x, y, z): (isize, isize, isize) = (
-            caps[1].parse().unwrap(),
-            caps[2].parse().unwrap(),
-            caps[3].parse().unwrap(),
-        );
-        map.insert(Coord(x, y, z), caps[4].parse::<usize>().unwrap());
-    }
-    map
+pub fn input_generator(input: &str) -> Result<HashMap<Coord, usize>, ParseError> {
+    let re = nanobot_regex();
+    lines(23, input)
+        .map(|line| parse_nanobot(&re, line))
+        .collect()
+}
+
+/// The same as `input_generator`, reading the nanobots a line at a time from `reader`.
+pub fn read_input<R: BufRead>(reader: R) -> Result<HashMap<Coord, usize>, ReadError> {
+    let re = nanobot_regex();
+    read_lines(23, reader, |line| parse_nanobot(&re, line)).collect()
 }
 
 /// Find the nanobot with the largest signal radius.
 /// How many nanobots are in range of its signals?
 #[aoc(day23, part1)]
-fn solve_part1(map: &HashMap<Coord, usize>) -> usize {
-    let (source_key, source_value) = map.iter().max_by_key(|&(_, v)| v).unwrap();
-    let mut num_in_range = 0;
-    for key in map.keys() {
-        let &Coord(x, y, z) = key;
-        let distance = ((x - source_key.0).abs()
-            + (y - source_key.1).abs()
-            + (z - source_key.2).abs()) as usize;
-        if distance <= *source_value {
-            num_in_range += 1;
-        }
-    }
-    num_in_range
+pub fn solve_part1(map: &HashMap<Coord, usize>) -> Result<usize, Unsolved> {
+    let (source_key, source_value) = map
+        .iter()
+        .max_by_key(|&(_, v)| v)
+        .ok_or(Unsolved::NoSolution("there are no nanobots"))?;
+    Ok(map
+        .keys()
+        .filter(|key| key.manhattan(source_key) <= *source_value)
+        .count())
 }
 
 /// Find the coordinates that are in range of the largest number of nanobots.
 /// What is the shortest manhattan distance between any of those points and 0,0,0?
 ///
 /// Initially, I implemented a solution using Z3 that took >30s to get the result.
-/// This implementation is based off a Rust solution from the reddit thread.
+/// This implementation narrows a cube around every nanobot's range down to the point itself.
 #[aoc(day23, part2)]
-fn solve_part2(map: &HashMap<Coord, usize>) -> usize {
-    let mut distances: BTreeMap<isize, isize> = BTreeMap::new();
-    for (coord, signal_strength) in map {
-        let &Coord(x, y, z) = coord;
-        let distance = x + y + z;
-        *distances
-            .entry(distance - *signal_strength as isize)
-            .or_default() += 1;
-        *distances
-            .entry(distance + *signal_strength as isize + 1)
-            .or_default() -= 1;
+pub fn solve_part2(map: &HashMap<Coord, usize>) -> Result<usize, Unsolved> {
+    solve_part2_within(map, Budget::default())
+}
+
+/// Part two, giving up after splitting `budget` cubes.
+pub fn solve_part2_within(map: &HashMap<Coord, usize>, budget: Budget) -> Result<usize, Unsolved> {
+    closest_spot(map, budget).map(|spot| spot.distance)
+}
+
+/// A point in range of as many nanobots as any point is, the closest such point to the origin.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Spot {
+    pub point: Coord,
+    pub in_range: usize,
+    /// The point's manhattan distance from 0,0,0.
+    pub distance: usize,
+}
+
+impl fmt::Display for Spot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [x, y, z] = self.point.0;
+        write!(
+            f,
+            "{},{},{} is in range of {} nanobots, {} from 0,0,0",
+            x, y, z, self.in_range, self.distance
+        )
     }
+}
 
-    let run: Vec<_> = distances
-        .iter()
-        .scan(0_isize, |state, (distance, count)| {
-            *state += count;
-            Some((distance, *state))
-        })
-        .collect();
-    let max = run.iter().map(|&(_, count)| count).max().unwrap();
-    let intervals: Vec<_> = run
+/// How far `point` is from the nearest point of the cube with corner `min` and sides `size`.
+fn distance_to_cube(point: &Coord, min: &Coord, size: isize) -> usize {
+    point
+        .0
         .iter()
-        .zip(run.iter().skip(1))
-        .filter_map(|(&(distance_a, count), &(distance_b, _))| {
-            if count == max {
-                Some((*distance_a, *distance_b - 1))
-            } else {
-                None
-            }
-        })
-        .collect();
-    if intervals.iter().any(|&(a, b)| a <= 0 && b >= 0) {
-        0
-    } else {
-        intervals
-            .iter()
-            .map(|&(a, b)| if b < 0 { -b } else { a })
-            .min()
-            .unwrap() as usize
+        .zip(min.0.iter())
+        .map(|(&p, &low)| ((low - p).max(0) + (p - (low + size - 1)).max(0)) as usize)
+        .sum()
+}
+
+/// Part two's answer along with the point it measures.
+pub fn solve_part2_with_witness(map: &HashMap<Coord, usize>) -> Result<(usize, Spot), Unsolved> {
+    let spot = closest_spot(map, Budget::default())?;
+    Ok((spot.distance, spot))
+}
+
+/// Splits a cube around every nanobot's range into eighths, always splitting next the cube
+/// that could be in range of the most nanobots and is closest to the origin. The first single
+/// point split off that way is the spot.
+fn closest_spot(map: &HashMap<Coord, usize>, budget: Budget) -> Result<Spot, Unsolved> {
+    let origin = Coord::ORIGIN;
+    let corners = map.iter().flat_map(|(&coord, &radius)| {
+        let radius = Coord::new([radius as isize; 3]);
+        vec![coord - radius, coord + radius]
+    });
+    let bounds = match Bounds::of(corners) {
+        Some(bounds) => bounds,
+        None => {
+            return Ok(Spot {
+                point: origin,
+                in_range: 0,
+                distance: 0,
+            })
+        }
+    };
+    let span = (0..3).map(|axis| bounds.span(axis)).max().unwrap();
+    let size = span.next_power_of_two() as isize;
+    // How many nanobots could be in range of a point in the cube, and how close to the origin
+    // such a point could be. A point in range of all of them is no closer than the cube is,
+    // nor than any of their ranges come.
+    let in_range = |min: &Coord, size: isize| {
+        map.iter()
+            .filter(|&(coord, &radius)| distance_to_cube(coord, min, size) <= radius)
+            .fold(
+                (0, distance_to_cube(&origin, min, size)),
+                |(count, distance), (coord, &radius)| {
+                    let nearest = coord.manhattan(&origin).saturating_sub(radius);
+                    (count + 1, distance.max(nearest))
+                },
+            )
+    };
+    let mut meter = budget.start();
+    let mut cubes = BinaryHeap::new();
+    let (count, distance) = in_range(&bounds.min, size);
+    cubes.push((count, Reverse(distance), Reverse(size), Reverse(bounds.min)));
+    while let Some((in_range_count, Reverse(distance), Reverse(size), Reverse(min))) = cubes.pop() {
+        if size == 1 {
+            return Ok(Spot {
+                point: min,
+                in_range: in_range_count,
+                distance,
+            });
+        }
+        meter.tick()?;
+        let half = size / 2;
+        for octant in 0..8 {
+            let offset = Coord::new([octant & 1, (octant >> 1) & 1, octant >> 2]);
+            let corner = min + offset * half;
+            let (count, distance) = in_range(&corner, half);
+            cubes.push((count, Reverse(distance), Reverse(half), Reverse(corner)));
+        }
     }
+    Err(Unsolved::NoSolution(
+        "every cube was split without finding a point",
+    ))
 }
 
 #[cfg(test)]
@@ -106,9 +188,14 @@ pos=<0,0,3>, r=1
 pos=<1,1,1>, r=1
 pos=<1,1,2>, r=1
 pos=<1,3,1>, r=1",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&input);
-        assert_eq!(result, 7);
+        assert_eq!(result, Ok(7));
+        assert_eq!(
+            solve_part1(&HashMap::new()),
+            Err(Unsolved::NoSolution("there are no nanobots"))
+        );
     }
 
     #[test]
@@ -120,8 +207,62 @@ pos=<16,12,12>, r=4
 pos=<14,14,14>, r=6
 pos=<50,50,50>, r=200
 pos=<10,10,10>, r=5",
-        );
+        )
+        .unwrap();
         let result = solve_part2(&input);
+        assert_eq!(result, Ok(36));
+    }
+
+    #[test]
+    fn part2_single() {
+        let input = input_generator("pos=<10,10,10>, r=5").unwrap();
+        assert_eq!(solve_part2(&input), Ok(25));
+        assert!(solve_part2_within(&input, Budget::steps(2)).is_err());
+    }
+
+    #[test]
+    fn part2_brute_force() {
+        let mut rng = crate::gen::Rng::new(23);
+        for _ in 0..20 {
+            let map: HashMap<Coord, usize> = (0..8)
+                .map(|_| {
+                    let mut axis = || rng.range(0, 9) as isize;
+                    (Coord::new([axis(), axis(), axis()]), rng.below(6))
+                })
+                .collect();
+            let mut best = (0, Reverse(0));
+            for x in -6..16 {
+                for y in -6..16 {
+                    for z in -6..16 {
+                        let point = Coord::new([x, y, z]);
+                        let count = map
+                            .iter()
+                            .filter(|&(coord, &radius)| coord.manhattan(&point) <= radius)
+                            .count();
+                        best = best.max((count, Reverse(point.manhattan(&Coord::ORIGIN))));
+                    }
+                }
+            }
+            assert_eq!(solve_part2(&map), Ok((best.1).0), "{:?}", map);
+        }
+    }
+
+    #[test]
+    fn witness() {
+        let input = input_generator(
+            "pos=<10,12,12>, r=2
+pos=<12,14,12>, r=2
+pos=<16,12,12>, r=4
+pos=<14,14,14>, r=6
+pos=<50,50,50>, r=200
+pos=<10,10,10>, r=5",
+        )
+        .unwrap();
+        let (result, spot) = solve_part2_with_witness(&input).unwrap();
         assert_eq!(result, 36);
+        assert_eq!(
+            spot.to_string(),
+            "12,12,12 is in range of 5 nanobots, 36 from 0,0,0"
+        );
     }
 }
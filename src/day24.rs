@@ -1,3 +1,12 @@
+//! Immune System Simulator 20XX: the `Group`s of the immune system and the infection.
+//!
+//! `Fight` runs the battle a round at a time. Part one is how many units the winning army has
+//! left, and part two how many the immune system has with the smallest boost it needs to
+//! win.
+use crate::budget::{Budget, Unsolved};
+use crate::error::{lines, Line, ParseError};
+use crate::parallel;
+use crate::simulation::{Runner, Simulation};
 use aoc_runner_derive::{aoc, aoc_generator};
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -7,8 +16,9 @@ lazy_static! {
     static ref RE: Regex = Regex::new(r"(?P<units>\d+) units each with (?P<health>\d+) hit points ?(?:\((?:(?P<type_a>weak|immune) to (?P<types_a>[^;)]+))?(?:;? ?(?:(?P<type_b>weak|immune) to (?P<types_b>[^;)]+)))?\))? with an attack that does (?P<attack>\d+) (?P<attack_type>\w+) damage at initiative (?P<initiative>\d+)").unwrap();
 }
 
-#[derive(Hash, Clone, Copy, Eq, PartialEq)]
-enum AttackType {
+#[derive(Hash, Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AttackType {
     Radiation,
     Cold,
     Fire,
@@ -17,26 +27,29 @@ enum AttackType {
 }
 
 impl AttackType {
-    fn from_str(input: &str) -> Self {
+    fn parse(line: &Line, input: &str) -> Result<Self, ParseError> {
         match input {
-            "radiation" => AttackType::Radiation,
-            "cold" => AttackType::Cold,
-            "fire" => AttackType::Fire,
-            "bludgeoning" => AttackType::Bludgeoning,
-            "slashing" => AttackType::Slashing,
-            _ => unreachable!(),
+            "radiation" => Ok(AttackType::Radiation),
+            "cold" => Ok(AttackType::Cold),
+            "fire" => Ok(AttackType::Fire),
+            "bludgeoning" => Ok(AttackType::Bludgeoning),
+            "slashing" => Ok(AttackType::Slashing),
+            _ => Err(line.error(input, "unknown attack type")),
         }
     }
 }
 
 #[derive(Hash, Clone, Eq, PartialEq, Debug)]
-enum ArmyType {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ArmyType {
     ImmuneSystem,
     Infection,
 }
 
+/// A group of identical units, as listed under its army in the input.
 #[derive(Hash, Clone, Eq, PartialEq)]
-struct Group {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Group {
     army_type: ArmyType,
     unit_count: usize,
     health_points: usize,
@@ -45,19 +58,50 @@ struct Group {
     weaknesses: Vec<AttackType>,
     immunities: Vec<AttackType>,
     initiative: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
     target_id: Option<usize>,
 }
 
 impl Group {
-    fn build(input: &str, army_type: ArmyType) -> Self {
-        let caps = RE.captures(input).unwrap();
+    pub const fn army(&self) -> &ArmyType {
+        &self.army_type
+    }
+
+    pub const fn units(&self) -> usize {
+        self.unit_count
+    }
+
+    /// Hit points of each unit.
+    pub const fn hit_points(&self) -> usize {
+        self.health_points
+    }
+
+    /// Damage each unit deals, before any boost, and its type.
+    pub const fn attack(&self) -> (usize, AttackType) {
+        (self.attack_power, self.attack_type)
+    }
+
+    pub fn weaknesses(&self) -> &[AttackType] {
+        &self.weaknesses
+    }
+
+    pub fn immunities(&self) -> &[AttackType] {
+        &self.immunities
+    }
+
+    pub const fn initiative(&self) -> usize {
+        self.initiative
+    }
+
+    fn build(line: &Line, army_type: ArmyType) -> Result<Self, ParseError> {
+        let caps = line.captures(&RE)?;
         let mut weaknesses = Vec::new();
         let mut immunities = Vec::new();
 
         let types_a = caps.name("types_a");
         if let Some(_types) = types_a {
             for _type in _types.as_str().split(", ") {
-                let actual_type = AttackType::from_str(_type);
+                let actual_type = AttackType::parse(line, _type)?;
                 if &caps["type_a"] == "weak" {
                     weaknesses.push(actual_type);
                 } else {
@@ -69,7 +113,7 @@ impl Group {
         let types_b = caps.name("types_b");
         if let Some(_types) = types_b {
             for _type in _types.as_str().split(", ") {
-                let actual_type = AttackType::from_str(_type);
+                let actual_type = AttackType::parse(line, _type)?;
                 if &caps["type_b"] == "weak" {
                     weaknesses.push(actual_type);
                 } else {
@@ -78,17 +122,17 @@ impl Group {
             }
         }
 
-        Group {
+        Ok(Group {
             army_type,
-            unit_count: caps["units"].parse().unwrap(),
-            health_points: caps["health"].parse().unwrap(),
-            attack_power: caps["attack"].parse().unwrap(),
-            attack_type: AttackType::from_str(&caps["attack_type"]),
+            unit_count: line.parse(&caps["units"])?,
+            health_points: line.parse(&caps["health"])?,
+            attack_power: line.parse(&caps["attack"])?,
+            attack_type: AttackType::parse(line, &caps["attack_type"])?,
             weaknesses,
             immunities,
-            initiative: caps["initiative"].parse().unwrap(),
+            initiative: line.parse(&caps["initiative"])?,
             target_id: None,
-        }
+        })
     }
 
     fn effective_power(&self, boost: usize) -> usize {
@@ -120,55 +164,103 @@ impl Group {
 }
 
 #[aoc_generator(day24)]
-fn input_generator(input: &str) -> Vec<Group> {
+pub fn input_generator(input: &str) -> Result<Vec<Group>, ParseError> {
     let mut armies: Vec<Group> = Vec::new();
-
-    let immune_iter = input.lines().skip(1).take_while(|line| !line.is_empty());
-    for group in immune_iter.clone() {
-        armies.push(Group::build(group, ArmyType::ImmuneSystem));
-    }
-
-    let infections_iter = input.lines().skip(immune_iter.count() + 3);
-    for group in infections_iter {
-        armies.push(Group::build(group, ArmyType::Infection));
+    let mut army_type = None;
+    for line in lines(24, input) {
+        match line.text {
+            "" => continue,
+            "Immune System:" => army_type = Some(ArmyType::ImmuneSystem),
+            "Infection:" => army_type = Some(ArmyType::Infection),
+            _ => match &army_type {
+                Some(army_type) => armies.push(Group::build(&line, army_type.clone())?),
+                None => return Err(line.error(line.text, "expected an army heading")),
+            },
+        }
     }
-
-    armies
+    Ok(armies)
 }
 
 /// As it stands now, how many units would the winning army have?
 #[aoc(day24, part1)]
-fn solve_part1(armies: &[Group]) -> usize {
-    let mut armies = armies.to_owned();
-    solve(&mut armies, 0);
-    armies.iter().map(|g| g.unit_count).sum()
+pub fn solve_part1(armies: &[Group]) -> usize {
+    let fight = Runner::new(Fight::new(armies, 0)).run();
+    fight.armies().iter().map(|g| g.unit_count).sum()
 }
 
 /// How many units does the immune system have left after getting the smallest boost it needs to win?
 #[aoc(day24, part2)]
-fn solve_part2(_armies: &[Group]) -> usize {
-    let mut boost_value = 0;
-    loop {
-        let mut armies = _armies.to_owned();
-        solve(&mut armies, boost_value);
-        if armies
-            .iter()
-            .filter(|g| g.army_type == ArmyType::Infection && g.unit_count > 0)
-            .count()
-            == 0
-        {
-            return armies
+pub fn solve_part2(armies: &[Group]) -> Result<usize, Unsolved> {
+    solve_part2_within(armies, Budget::default())
+}
+
+/// Part two, giving up after trying `budget` boosts.
+pub fn solve_part2_within(armies: &[Group], budget: Budget) -> Result<usize, Unsolved> {
+    // no boost helps against an infection group that's immune to every immune system attack
+    let invulnerable = armies
+        .iter()
+        .filter(|g| g.army_type == ArmyType::Infection)
+        .any(|infection| {
+            armies
                 .iter()
                 .filter(|g| g.army_type == ArmyType::ImmuneSystem)
-                .map(|g| g.unit_count)
-                .sum();
-        }
-        boost_value += 1;
+                .all(|immune| infection.is_immune_to(immune.attack_type))
+        });
+    if invulnerable {
+        return Err(Unsolved::NoSolution(
+            "an infection group is immune to the whole immune system",
+        ));
     }
+
+    let mut meter = budget.start();
+    parallel::find_first(0, &mut meter, |boost_value| {
+        let fight = Runner::new(Fight::new(armies, boost_value)).run();
+        Some(fight.units(ArmyType::ImmuneSystem)).filter(|_| fight.units(ArmyType::Infection) == 0)
+    })
+}
+
+/// The fight a round at a time, until one army is wiped out or a round kills nobody.
+#[derive(Clone)]
+pub struct Fight {
+    armies: Vec<Group>,
+    boost: usize,
+    rounds: usize,
+    finished: bool,
 }
 
-fn solve(armies: &mut [Group], boost: usize) {
-    loop {
+impl Fight {
+    /// `boost` is added to the attack of every immune system group.
+    pub fn new(armies: &[Group], boost: usize) -> Self {
+        Self {
+            armies: armies.to_owned(),
+            boost,
+            rounds: 0,
+            finished: false,
+        }
+    }
+
+    /// Every group, with the units each has left.
+    pub fn armies(&self) -> &[Group] {
+        &self.armies
+    }
+
+    pub const fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// How many units `army_type` has left.
+    pub fn units(&self, army_type: ArmyType) -> usize {
+        self.armies
+            .iter()
+            .filter(|g| g.army_type == army_type)
+            .map(|g| g.unit_count)
+            .sum()
+    }
+
+    /// Fights a round, returning whether the fight is over.
+    fn round(&mut self) -> bool {
+        let armies = &mut self.armies;
+        let boost = self.boost;
         let mut attackers = (0..armies.len()).collect::<Vec<_>>();
         attackers.sort_unstable_by_key(|&i| {
             (
@@ -226,24 +318,31 @@ fn solve(armies: &mut [Group], boost: usize) {
         }
         let units_after_attack = armies.iter().map(|g| g.unit_count).sum::<usize>();
         if units_before_attack == units_after_attack {
-            break;
+            return true;
         }
 
         // Finished?
-        if armies
-            .iter()
-            .filter(|g| g.army_type == ArmyType::ImmuneSystem && g.unit_count > 0)
-            .count()
-            == 0
-            || armies
-                .iter()
-                .filter(|g| g.army_type == ArmyType::Infection && g.unit_count > 0)
-                .count()
-                == 0
-        {
-            break;
+        self.units(ArmyType::ImmuneSystem) == 0 || self.units(ArmyType::Infection) == 0
+    }
+}
+
+impl Simulation for Fight {
+    type Snapshot = Vec<Group>;
+
+    fn step(&mut self) {
+        if !self.finished {
+            self.finished = self.round();
+            self.rounds += 1;
         }
     }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn snapshot(&self) -> Vec<Group> {
+        self.armies.clone()
+    }
 }
 
 #[cfg(test)]
@@ -258,11 +357,29 @@ mod tests {
 
 Infection:
 801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
-4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4");
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 5216);
     }
 
+    #[test]
+    fn rounds() {
+        let input = input_generator("Immune System:
+17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+989 units each with 1274 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 25 slashing damage at initiative 3
+
+Infection:
+801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4").unwrap();
+        let mut units = Vec::new();
+        let fight = Runner::new(Fight::new(&input, 0))
+            .observe(|_, fight| units.push(fight.units(ArmyType::Infection)))
+            .run();
+        assert_eq!(fight.rounds(), 8);
+        assert_eq!(&units[..3], &[5286, 5231, 5227]);
+        assert_eq!(fight.units(ArmyType::ImmuneSystem), 0);
+    }
+
     #[test]
     fn part2() {
         let input = input_generator("Immune System:
@@ -271,8 +388,37 @@ Infection:
 
 Infection:
 801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
-4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4");
-        let result = solve_part2(&input);
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4").unwrap();
+        let result = solve_part2(&input).unwrap();
         assert_eq!(result, 51);
     }
+
+    #[test]
+    fn part2_budget() {
+        // the boost of 1570 comes from trying every smaller boost first
+        let input = input_generator("Immune System:
+17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+989 units each with 1274 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 25 slashing damage at initiative 3
+
+Infection:
+801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4").unwrap();
+        assert!(solve_part2_within(&input, Budget::steps(1570)).is_err());
+        assert_eq!(solve_part2_within(&input, Budget::steps(1571)), Ok(51));
+    }
+
+    #[test]
+    fn part2_unsolvable() {
+        let input = input_generator("Immune System:
+17 units each with 5390 hit points with an attack that does 4507 fire damage at initiative 2
+
+Infection:
+801 units each with 4706 hit points (immune to fire) with an attack that does 116 bludgeoning damage at initiative 1").unwrap();
+        assert_eq!(
+            solve_part2(&input),
+            Err(Unsolved::NoSolution(
+                "an infection group is immune to the whole immune system"
+            ))
+        );
+    }
 }
@@ -1,44 +1,42 @@
+//! Four-Dimensional Adventure: fixed points in spacetime.
+//!
+//! The only part counts the constellations the points form, and `read_input` reads them from a
+//! stream instead of text.
+use crate::error::{lines, read_lines, Line, ParseError, ReadError};
+use crate::geometry::Point4;
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::iter::FromIterator;
 use std::collections::VecDeque;
 use hashbrown::HashSet;
+use std::io::BufRead;
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct Coord(isize, isize, isize, isize);
+pub type Coord = Point4;
 
-impl Coord {
-    fn distance(&self, other: &Coord) -> usize {
-        let a = (self.0 - other.0).abs();
-        let b = (self.1 - other.1).abs();
-        let c = (self.2 - other.2).abs();
-        let d = (self.3 - other.3).abs();
-        (a + b + c + d) as usize
+fn parse_point(line: Line) -> Result<Coord, ParseError> {
+    let values = line
+        .text
+        .trim()
+        .split(',')
+        .map(|s| line.parse::<isize>(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    match values.as_slice() {
+        &[a, b, c, d] => Ok(Coord::new([a, b, c, d])),
+        _ => Err(line.error(line.text, "expected four coordinates")),
     }
 }
 
-impl FromIterator<isize> for Coord {
-    fn from_iter<I: IntoIterator<Item=isize>>(iter: I) -> Self {
-        let mut values = [0_isize; 4];
-        for (idx, value) in iter.into_iter().enumerate() {
-            values[idx] = value;
-        }
-        Coord(values[0], values[1], values[2], values[3])
-    }
+#[aoc_generator(day25)]
+pub fn input_generator(input: &str) -> Result<Vec<Coord>, ParseError> {
+    lines(25, input).map(parse_point).collect()
 }
 
-#[aoc_generator(day25)]
-fn input_generator(input: &str) -> Vec<Coord> {
-    let mut coords = Vec::new();
-    for line in input.lines() {
-        let values = line.trim().split(',').map(|s| s.parse::<isize>().unwrap()).collect::<Coord>();
-        coords.push(values);
-    }
-    coords
+/// The same as `input_generator`, reading the points a line at a time from `reader`.
+pub fn read_input<R: BufRead>(reader: R) -> Result<Vec<Coord>, ReadError> {
+    read_lines(25, reader, parse_point).collect()
 }
 
 /// How many constellations are formed by the fixed points in spacetime?
 #[aoc(day25, part1)]
-fn solve_part1(coords: &[Coord]) -> usize {
+pub fn solve_part1(coords: &[Coord]) -> usize {
     let mut all_points = HashSet::new();
     let mut total_constellations = 0;
     loop {
@@ -58,7 +56,7 @@ fn solve_part1(coords: &[Coord]) -> usize {
             all_points.insert(current);
             for idx in 0..coords.len() {
                 if !tried.contains(&idx) && !all_points.contains(&idx) {
-                    if coords[idx].distance(&coords[current]) <= 3 {
+                    if coords[idx].manhattan(&coords[current]) <= 3 {
                         queue.push_back(idx);
                     }
                 }
@@ -85,7 +83,7 @@ mod tests {
  0,0,0,3
  0,0,0,6
  9,0,0,0
-12,0,0,0");
+12,0,0,0").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 2);
 
@@ -98,7 +96,7 @@ mod tests {
 -2,2,0,0
 2,-2,0,-1
 1,-1,0,-1
-3,2,0,2");
+3,2,0,2").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 3);
 
@@ -111,8 +109,18 @@ mod tests {
 0,-2,-1,0
 -2,2,3,-1
 1,2,2,0
--1,-2,0,-2");
+-1,-2,0,-2").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 8);
     }
+
+    #[test]
+    fn reading() {
+        let input = " 0,0,0,0\n3,0,0,0\n9,0,0,0\n";
+        let read = read_input(input.as_bytes()).unwrap();
+        assert_eq!(read, input_generator(input).unwrap());
+        assert_eq!(solve_part1(&read), 2);
+        let error = read_input(&b"0,0,0,0\n1,2,3\n"[..]).unwrap_err();
+        assert_eq!(error.to_string(), "day25 line 2 column 1: expected four coordinates in \"1,2,3\"");
+    }
 }
@@ -1,7 +1,15 @@
+//! No Matter How You Slice It: rectangular `Claim`s on a sheet of fabric.
+//!
+//! Part one counts the square inches under more than one claim, and part two finds the one
+//! claim that overlaps no other.
+use crate::budget::Unsolved;
+use crate::error::{lines, read_lines, Line, ParseError, ReadError};
 use aoc_runner_derive::{aoc, aoc_generator};
 use hashbrown::{HashMap, HashSet};
 use regex::Regex;
+use std::io::BufRead;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Claim {
     id: u32,
     x: u32,
@@ -10,22 +18,31 @@ pub struct Claim {
     h: u32,
 }
 
+fn claim_regex() -> Regex {
+    Regex::new(r"#(\d+) @ (\d+),(\d+): (\d+)x(\d+)").unwrap()
+}
+
+fn parse_claim(re: &Regex, line: Line) -> Result<Claim, ParseError> {
+    let matches = line.captures(re)?;
+    Ok(Claim {
+        id: line.parse(&matches[1])?,
+        x: line.parse(&matches[2])?,
+        y: line.parse(&matches[3])?,
+        w: line.parse(&matches[4])?,
+        h: line.parse(&matches[5])?,
+    })
+}
+
 #[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> Vec<Claim> {
-    let re = Regex::new(r"#(\d+) @ (\d+),(\d+): (\d+)x(\d+)").unwrap();
-    input
-        .lines()
-        .map(|s| {
-            let matches = re.captures(s).unwrap();
-            Claim {
-                id: matches[1].parse().unwrap(),
-                x: matches[2].parse().unwrap(),
-                y: matches[3].parse().unwrap(),
-                w: matches[4].parse().unwrap(),
-                h: matches[5].parse().unwrap(),
-            }
-        })
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<Claim>, ParseError> {
+    let re = claim_regex();
+    lines(3, input).map(|line| parse_claim(&re, line)).collect()
+}
+
+/// The same as `input_generator`, reading the claims a line at a time from `reader`.
+pub fn read_input<R: BufRead>(reader: R) -> Result<Vec<Claim>, ReadError> {
+    let re = claim_regex();
+    read_lines(3, reader, |line| parse_claim(&re, line)).collect()
 }
 
 fn build_map(claims: &[Claim]) -> HashMap<(u32, u32), Vec<u32>> {
@@ -46,7 +63,7 @@ pub fn solve_part1(claims: &[Claim]) -> u32 {
 }
 
 #[aoc(day3, part2)]
-pub fn solve_part2(claims: &[Claim]) -> u32 {
+pub fn solve_part2(claims: &[Claim]) -> Result<u32, Unsolved> {
     let all_ids: HashSet<_> = claims.iter().map(|x| x.id).collect();
     let other_ids: HashSet<_> = build_map(claims)
         .values()
@@ -55,12 +72,10 @@ pub fn solve_part2(claims: &[Claim]) -> u32 {
         .flatten()
         .collect();
 
-    for id in all_ids {
-        if !other_ids.contains(&id) {
-            return id;
-        }
-    }
-    unreachable!();
+    all_ids
+        .into_iter()
+        .find(|id| !other_ids.contains(id))
+        .ok_or(Unsolved::NoSolution("every claim overlaps another"))
 }
 
 #[cfg(test)]
@@ -73,7 +88,8 @@ mod tests {
             "#1 @ 1,3: 4x4
 #2 @ 3,1: 4x4
 #3 @ 5,5: 2x2",
-        );
+        )
+        .unwrap();
         let result = solve_part1(&test_input);
         assert_eq!(result, 4);
     }
@@ -84,8 +100,29 @@ mod tests {
             "#1 @ 1,3: 4x4
 #2 @ 3,1: 4x4
 #3 @ 5,5: 2x2",
-        );
+        )
+        .unwrap();
         let result = solve_part2(&test_input);
-        assert_eq!(result, 3);
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn part2_unsolvable() {
+        let overlapping = input_generator("#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4").unwrap();
+        let expected = Err(Unsolved::NoSolution("every claim overlaps another"));
+        assert_eq!(solve_part2(&overlapping), expected);
+        assert_eq!(solve_part2(&[]), expected);
+    }
+
+    #[test]
+    fn malformed_claim() {
+        let error = input_generator(
+            "#1 @ 1,3: 4x4
+#2 @ 3,1 4x4",
+        )
+        .err()
+        .unwrap();
+        assert_eq!((error.day, error.line, error.column), (3, 2, 1));
+        assert_eq!(error.text, "#2 @ 3,1 4x4");
     }
 }
@@ -1,25 +1,49 @@
+//! Repose Record: the guards' sleep log, with its records in any order.
+//!
+//! `input_generator` sorts the log and counts, for each guard id, how often they were asleep
+//! at each minute past midnight. The solvers take the log as text, and `validate` reports
+//! records that can't follow the ones before them. The `_with_witness` solvers also return the
+//! `Sleeper` behind the answer.
+use crate::budget::Unsolved;
+use crate::error::{lines, Line, ParseError};
 use aoc_runner_derive::aoc;
 use hashbrown::HashMap;
 use regex::Regex;
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fmt;
+
+fn record_regex() -> Regex {
+    Regex::new(r"\d+:(\d+)\]\s(\w+)\s#?(\d+)?").unwrap()
+}
+
+/// The input's lines in the order they happened.
+fn sorted_lines(input: &str) -> Vec<Line<'_>> {
+    let mut sorted_input: Vec<Line> = lines(4, input).collect();
+    sorted_input.sort_by_key(|line| line.text);
+    sorted_input
+}
 
 // can't return HashMap from aoc_generator...?
-fn input_generator(input: &str) -> HashMap<u32, HashMap<u32, u32>> {
+pub fn input_generator(input: &str) -> Result<HashMap<u32, HashMap<u32, u32>>, ParseError> {
     // We need to sort the input into the correct order by date.
-    let mut sorted_input: Vec<&str> = input.lines().collect();
-    sorted_input.sort();
+    let sorted_input = sorted_lines(input);
 
-    let re = Regex::new(r"\d+:(\d+)\]\s(\w+)\s#?(\d+)?").unwrap();
+    let re = record_regex();
 
     // Store log as { guard_id: { minute: count } }
     let mut activities: HashMap<u32, HashMap<u32, u32>> = HashMap::new();
     let (mut last_guard_id, mut last_sleeping_minute) = (0, 0);
 
     for line in sorted_input {
-        let matches = re.captures(line).unwrap();
-        let minute = matches[1].parse().unwrap();
+        let matches = line.captures(&re)?;
+        let minute = line.parse(&matches[1])?;
         match &matches[2] {
             "Guard" => {
-                last_guard_id = matches[3].parse().unwrap();
+                let guard_id = matches
+                    .get(3)
+                    .ok_or_else(|| line.error(&matches[2], "expected a guard id"))?;
+                last_guard_id = line.parse(guard_id.as_str())?;
             }
             "falls" => {
                 last_sleeping_minute = minute;
@@ -30,42 +54,140 @@ fn input_generator(input: &str) -> HashMap<u32, HashMap<u32, u32>> {
                     *entry.entry(minute).or_default() += 1;
                 }
             }
-            _ => panic!("uhhh...."), // shouldn't happen? bad input
+            _ => return Err(line.error(&matches[2], "unknown event")),
         }
     }
-    activities
+    Ok(activities)
 }
 
-#[aoc(day4, part1)]
-pub fn solve_part1(input: &str) -> u32 {
-    let activities = input_generator(input);
-    let (guard_id, minutes) = activities
+/// Reports the records that can't follow the ones before them once sorted by time.
+///
+/// That's a guard waking without having fallen asleep, falling asleep twice or before any
+/// shift begins, and a nap that lasts into the next shift.
+pub fn validate(input: &str) -> Vec<ParseError> {
+    let re = record_regex();
+    let mut errors = Vec::new();
+    let mut on_shift = false;
+    let mut asleep: Option<(Line, u32)> = None;
+    for line in sorted_lines(input) {
+        let record = line
+            .captures(&re)
+            .and_then(|matches| Ok((line.parse::<u32>(&matches[1])?, matches)));
+        let (minute, matches) = match record {
+            Ok(record) => record,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        let problem = match &matches[2] {
+            "Guard" => {
+                on_shift = true;
+                if let Some((napping, _)) = asleep.take() {
+                    errors.push(napping.error(napping.text, "falls asleep until the next shift"));
+                }
+                matches.get(3).is_none().then_some("expected a guard id")
+            }
+            "falls" if !on_shift => Some("falls asleep before any shift begins"),
+            "falls" if asleep.is_some() => Some("falls asleep while already asleep"),
+            "falls" => {
+                asleep = Some((line, minute));
+                None
+            }
+            "wakes" => match asleep.take() {
+                None => Some("wakes up without having fallen asleep"),
+                Some((_, from)) if minute < from => {
+                    Some("wakes up before the minute it fell asleep")
+                }
+                Some(_) => None,
+            },
+            _ => Some("unknown event"),
+        };
+        if let Some(reason) = problem {
+            errors.push(line.error(&matches[2], reason));
+        }
+    }
+    if let Some((napping, _)) = asleep {
+        errors.push(napping.error(napping.text, "falls asleep and never wakes up"));
+    }
+    errors
+}
+
+/// The guard a strategy picks, and the minute they were most often asleep at.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sleeper {
+    pub guard: u32,
+    pub minute: u32,
+    /// How many minutes the guard slept over the whole log.
+    pub minutes_asleep: u32,
+    /// How many days the guard was asleep at `minute`.
+    pub times_at_minute: u32,
+}
+
+impl fmt::Display for Sleeper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "guard #{} slept {} minutes, {} times at 00:{:02}",
+            self.guard, self.minutes_asleep, self.times_at_minute, self.minute
+        )
+    }
+}
+
+/// The guard with the largest `strategy`, and their sleepiest minute. Ties go to the lower
+/// guard id and the earlier minute.
+fn choose(
+    activities: &HashMap<u32, HashMap<u32, u32>>,
+    strategy: impl Fn(&HashMap<u32, u32>) -> u32,
+) -> Result<Sleeper, Unsolved> {
+    const NO_SLEEP: Unsolved = Unsolved::NoSolution("no guard ever falls asleep");
+    let (&guard, minutes) = activities
         .iter()
-        .max_by_key(|(_, v)| v.values().sum::<u32>())
-        .unwrap();
-    let (minute, _) = minutes.iter().max_by_key(|&(_, &v)| v).unwrap();
-    guard_id * minute
+        .max_by_key(|&(&guard, minutes)| (strategy(minutes), Reverse(guard)))
+        .ok_or(NO_SLEEP)?;
+    let (&minute, &times_at_minute) = minutes
+        .iter()
+        .max_by_key(|&(&minute, &times)| (times, Reverse(minute)))
+        .ok_or(NO_SLEEP)?;
+    Ok(Sleeper {
+        guard,
+        minute,
+        minutes_asleep: minutes.values().sum(),
+        times_at_minute,
+    })
+}
+
+/// Part one's answer along with the guard and minute it comes from.
+pub fn solve_part1_with_witness(input: &str) -> Result<(u32, Sleeper), Box<dyn Error>> {
+    let activities = input_generator(input)?;
+    let sleeper = choose(&activities, |minutes| minutes.values().sum())?;
+    Ok((sleeper.guard * sleeper.minute, sleeper))
+}
+
+/// Part two's answer along with the guard and minute it comes from.
+pub fn solve_part2_with_witness(input: &str) -> Result<(u32, Sleeper), Box<dyn Error>> {
+    let activities = input_generator(input)?;
+    let sleeper = choose(&activities, |minutes| {
+        minutes.values().max().copied().unwrap_or(0)
+    })?;
+    Ok((sleeper.guard * sleeper.minute, sleeper))
+}
+
+#[aoc(day4, part1)]
+pub fn solve_part1(input: &str) -> Result<u32, Box<dyn Error>> {
+    solve_part1_with_witness(input).map(|(answer, _)| answer)
 }
 
 #[aoc(day4, part2)]
-pub fn solve_part2(input: &str) -> u32 {
-    let activities = input_generator(input);
-    let (guard_id, minutes) = activities
-        .iter()
-        .max_by_key(|(_k, v)| v.values().max())
-        .unwrap();
-    let (minute, _) = minutes.iter().max_by_key(|&(_, &v)| v).unwrap();
-    guard_id * minute
+pub fn solve_part2(input: &str) -> Result<u32, Box<dyn Error>> {
+    solve_part2_with_witness(input).map(|(answer, _)| answer)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day4::*;
 
-    #[test]
-    fn part1() {
-        let result = solve_part1(
-            "[1518-11-01 00:00] Guard #10 begins shift
+    const EXAMPLE: &str = "[1518-11-01 00:00] Guard #10 begins shift
 [1518-11-01 00:05] falls asleep
 [1518-11-01 00:25] wakes up
 [1518-11-01 00:30] falls asleep
@@ -81,32 +203,48 @@ mod tests {
 [1518-11-04 00:46] wakes up
 [1518-11-05 00:03] Guard #99 begins shift
 [1518-11-05 00:45] falls asleep
-[1518-11-05 00:55] wakes up",
-        );
-        assert_eq!(result, 240);
+[1518-11-05 00:55] wakes up";
+
+    #[test]
+    fn part1() {
+        let result = solve_part1(EXAMPLE);
+        assert_eq!(result.unwrap(), 240);
     }
 
     #[test]
     fn part2() {
-        let result = solve_part2(
-            "[1518-11-01 00:00] Guard #10 begins shift
-[1518-11-01 00:05] falls asleep
-[1518-11-01 00:25] wakes up
-[1518-11-01 00:30] falls asleep
-[1518-11-01 00:55] wakes up
-[1518-11-01 23:58] Guard #99 begins shift
-[1518-11-02 00:40] falls asleep
-[1518-11-02 00:50] wakes up
-[1518-11-03 00:05] Guard #10 begins shift
-[1518-11-03 00:24] falls asleep
-[1518-11-03 00:29] wakes up
-[1518-11-04 00:02] Guard #99 begins shift
-[1518-11-04 00:36] falls asleep
-[1518-11-04 00:46] wakes up
-[1518-11-05 00:03] Guard #99 begins shift
-[1518-11-05 00:45] falls asleep
-[1518-11-05 00:55] wakes up",
+        let result = solve_part2(EXAMPLE);
+        assert_eq!(result.unwrap(), 4455);
+    }
+
+    #[test]
+    fn witnesses() {
+        let (_, sleeper) = solve_part1_with_witness(EXAMPLE).unwrap();
+        assert_eq!(
+            sleeper,
+            Sleeper {
+                guard: 10,
+                minute: 24,
+                minutes_asleep: 50,
+                times_at_minute: 2
+            }
         );
-        assert_eq!(result, 4455);
+        let (answer, sleeper) = solve_part2_with_witness(EXAMPLE).unwrap();
+        assert_eq!(answer, 4455);
+        assert_eq!(
+            sleeper.to_string(),
+            "guard #99 slept 30 minutes, 3 times at 00:45"
+        );
+    }
+
+    #[test]
+    fn nobody_sleeps() {
+        let input = "[1518-11-01 00:00] Guard #10 begins shift";
+        for result in [solve_part1(input), solve_part2(input), solve_part1("")] {
+            let error = result.unwrap_err().to_string();
+            assert_eq!(error, "no solution: no guard ever falls asleep");
+        }
+        let error = solve_part1("[1518-11-01 00:05] dozes off").unwrap_err();
+        assert!(error.downcast_ref::<ParseError>().is_some());
     }
 }
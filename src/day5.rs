@@ -1,8 +1,26 @@
+//! Alchemical Reduction: a polymer whose units react with the same type of the opposite
+//! polarity.
+//!
+//! Part one is the polymer's length once every reaction has happened, and part two the
+//! shortest length left after taking out every unit of one type, which
+//! `solve_part2_with_witness` names as a `Removal`.
+use crate::error::{lines, ParseError};
+use crate::parallel;
 use aoc_runner_derive::{aoc, aoc_generator};
+use std::fmt;
 
 #[aoc_generator(day5)]
-pub fn input_generator(input: &str) -> Vec<char> {
-    input.trim().chars().collect()
+pub fn input_generator(input: &str) -> Result<Vec<char>, ParseError> {
+    let mut polymer = Vec::new();
+    for line in lines(5, input.trim()) {
+        for (index, unit) in line.text.char_indices() {
+            if !unit.is_ascii_alphabetic() {
+                return Err(line.error(&line.text[index..], "expected a polymer unit"));
+            }
+            polymer.push(unit);
+        }
+    }
+    Ok(polymer)
 }
 
 #[aoc(day5, part1)]
@@ -10,27 +28,55 @@ pub fn solve_part1(chars: &[char]) -> usize {
     react_polymer(chars).len()
 }
 
-#[aoc(day5, part2)]
-pub fn solve_part2(chars: &[char]) -> usize {
+/// The unit type part two takes out, and how long the polymer is once it has reacted without
+/// it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Removal {
+    pub unit: char,
+    pub length: usize,
+}
+
+impl fmt::Display for Removal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "removing {}/{} leaves {} units",
+            self.unit,
+            self.unit.to_ascii_uppercase(),
+            self.length
+        )
+    }
+}
+
+/// Part two's answer along with the unit type removed to get it, the first in the alphabet
+/// of any ties.
+pub fn solve_part2_with_witness(chars: &[char]) -> (usize, Removal) {
     let polymer = react_polymer(chars);
     let alphabet: Vec<_> = "abcdefghijklmnopqrstuvwxyz".chars().collect();
-    let w: Vec<_> = alphabet
-        .iter()
-        .map(|c| {
-            let x: String = polymer.iter().collect();
-            let u: char = c.to_ascii_uppercase();
-            let y: Vec<char> = x
-                .chars()
-                .filter(|x| match x {
-                    _ if x == c => false,
-                    _ if *x == u => false,
-                    _ => true,
-                })
-                .collect();
-            react_polymer(&y).len()
-        })
-        .collect();
-    *w.iter().min().unwrap()
+    let w = parallel::map(&alphabet, |c| {
+        let u: char = c.to_ascii_uppercase();
+        let y: Vec<char> = polymer
+            .iter()
+            .cloned()
+            .filter(|x| match x {
+                _ if x == c => false,
+                _ if *x == u => false,
+                _ => true,
+            })
+            .collect();
+        react_polymer(&y).len()
+    });
+    let (unit, length) = alphabet
+        .into_iter()
+        .zip(w)
+        .min_by_key(|&(_, length)| length)
+        .unwrap();
+    (length, Removal { unit, length })
+}
+
+#[aoc(day5, part2)]
+pub fn solve_part2(chars: &[char]) -> usize {
+    solve_part2_with_witness(chars).0
 }
 
 fn react_polymer(chars: &[char]) -> Vec<char> {
@@ -58,15 +104,30 @@ mod tests {
 
     #[test]
     fn part1() {
-        let test_input = input_generator("dabAcCaCBAcCcaDA");
+        let test_input = input_generator("dabAcCaCBAcCcaDA").unwrap();
         let result = solve_part1(&test_input);
         assert_eq!(result, 10);
     }
 
     #[test]
     fn part2() {
-        let test_input = input_generator("dabAcCaCBAcCcaDA");
+        let test_input = input_generator("dabAcCaCBAcCcaDA").unwrap();
         let result = solve_part2(&test_input);
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn witness() {
+        let test_input = input_generator("dabAcCaCBAcCcaDA").unwrap();
+        let (result, removal) = solve_part2_with_witness(&test_input);
+        assert_eq!(result, 4);
+        assert_eq!(
+            removal,
+            Removal {
+                unit: 'c',
+                length: 4
+            }
+        );
+        assert_eq!(removal.to_string(), "removing c/C leaves 4 units");
+    }
 }
@@ -1,104 +1,79 @@
+//! Chronal Coordinates: a list of points as `Coord`s.
+//!
+//! Part one finds the largest area of locations closest to one point that isn't infinite, and
+//! part two the area within a total distance of 10000 of every point.
+use crate::budget::Unsolved;
+use crate::error::{lines, ParseError};
+use crate::geometry::{Bounds, Point2};
 use aoc_runner_derive::{aoc, aoc_generator};
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
-pub struct Coord {
-    x: usize,
-    y: usize,
-}
+pub type Coord = Point2;
 
 #[aoc_generator(day6)]
-pub fn input_generator(input: &str) -> Vec<Coord> {
-    input
-        .trim()
-        .lines()
-        .map(|x| {
-            let mut coords = x.split(", ").map(|y| y.parse::<usize>().unwrap());
-            Coord {
-                x: coords.next().unwrap(),
-                y: coords.next().unwrap(),
+pub fn input_generator(input: &str) -> Result<Vec<Coord>, ParseError> {
+    lines(6, input.trim())
+        .map(|line| {
+            let mut coords = line.text.split(", ");
+            match (coords.next(), coords.next(), coords.next()) {
+                (Some(x), Some(y), None) => Ok(Coord::new([line.parse(x)?, line.parse(y)?])),
+                _ => Err(line.error(line.text, "expected `x, y`")),
             }
         })
         .collect()
 }
 
+const NO_COORDINATES: Unsolved = Unsolved::NoSolution("there are no coordinates");
+
+/// The size of the largest area that isn't infinite, or 0 when every area is.
 #[aoc(day6, part1)]
-pub fn solve_part1(coords: &[Coord]) -> usize {
-    let x_min_max = (
-        coords.iter().map(|c| c.x).min().unwrap(),
-        coords.iter().map(|c| c.x).max().unwrap(),
-    );
-    let y_min_max = (
-        coords.iter().map(|c| c.y).min().unwrap(),
-        coords.iter().map(|c| c.y).max().unwrap(),
-    );
-    let mut grid: HashMap<usize, usize> = HashMap::new();
+pub fn solve_part1(coords: &[Coord]) -> Result<usize, Unsolved> {
+    let bounds = Bounds::of(coords.iter().copied()).ok_or(NO_COORDINATES)?;
+    let mut areas: HashMap<usize, usize> = HashMap::new();
+    // areas reaching the edge of the box go on forever
+    let mut infinite = HashSet::new();
 
-    for x in x_min_max.0..=x_min_max.1 {
-        for y in y_min_max.0..=y_min_max.1 {
-            let mut min_distance: Option<usize> = None;
-            let mut best_index: Option<usize> = None;
+    for point in bounds.points() {
+        let mut min_distance: Option<usize> = None;
+        let mut best_index: Option<usize> = None;
 
-            for (idx, coord) in coords.iter().enumerate() {
-                let distance = man_dist(coord.x, coord.y, x, y);
-                if min_distance == None || distance < min_distance.unwrap() {
-                    min_distance = Some(distance);
-                    best_index = Some(idx);
-                } else if distance == min_distance.unwrap() {
-                    best_index = None;
-                }
+        for (idx, coord) in coords.iter().enumerate() {
+            let distance = coord.manhattan(&point);
+            if min_distance == None || distance < min_distance.unwrap() {
+                min_distance = Some(distance);
+                best_index = Some(idx);
+            } else if distance == min_distance.unwrap() {
+                best_index = None;
             }
+        }
 
-            match best_index {
-                Some(idx) => {
-                    if x != x_min_max.0 && x != x_min_max.1 && y != y_min_max.0 && y != y_min_max.1
-                    {
-                        *grid.entry(idx).or_insert(0) += 1;
-                    } else {
-                        *grid.entry(idx).or_insert(0) = 0;
-                    }
-                }
-                None => continue,
+        if let Some(idx) = best_index {
+            if bounds.on_edge(&point) {
+                infinite.insert(idx);
+            } else {
+                *areas.entry(idx).or_insert(0) += 1;
             }
         }
     }
-    *grid.values().max().unwrap()
+    Ok(areas
+        .iter()
+        .filter(|(idx, _)| !infinite.contains(*idx))
+        .map(|(_, &area)| area)
+        .max()
+        .unwrap_or(0))
 }
 
 #[aoc(day6, part2)]
-pub fn solve_part2(coords: &[Coord]) -> usize {
+pub fn solve_part2(coords: &[Coord]) -> Result<usize, Unsolved> {
     part2_solver(coords, 10000)
 }
 
-fn part2_solver(coords: &[Coord], limit: usize) -> usize {
-    let x_min_max = (
-        coords.iter().map(|c| c.x).min().unwrap(),
-        coords.iter().map(|c| c.x).max().unwrap(),
-    );
-    let y_min_max = (
-        coords.iter().map(|c| c.y).min().unwrap(),
-        coords.iter().map(|c| c.y).max().unwrap(),
-    );
-
-    let mut result: usize = 0;
-    for x in x_min_max.0..=x_min_max.1 {
-        for y in y_min_max.0..=y_min_max.1 {
-            let mut distance_sum: usize = 0;
-            for coord in coords {
-                let distance = man_dist(coord.x, coord.y, x, y);
-                distance_sum += distance;
-            }
-            if distance_sum < limit {
-                result += 1;
-            }
-        }
-    }
-    result
-}
-
-fn man_dist(source_x: usize, source_y: usize, target_x: usize, target_y: usize) -> usize {
-    let x: isize = source_x as isize - target_x as isize;
-    let y: isize = source_y as isize - target_y as isize;
-    (x.abs() + y.abs()) as usize
+fn part2_solver(coords: &[Coord], limit: usize) -> Result<usize, Unsolved> {
+    Ok(Bounds::of(coords.iter().copied())
+        .ok_or(NO_COORDINATES)?
+        .points()
+        .filter(|point| coords.iter().map(|c| c.manhattan(point)).sum::<usize>() < limit)
+        .count())
 }
 
 #[cfg(test)]
@@ -114,8 +89,9 @@ mod tests {
 3, 4
 5, 5
 8, 9",
-        );
-        let result = solve_part1(&coords);
+        )
+        .unwrap();
+        let result = solve_part1(&coords).unwrap();
         assert_eq!(result, 17);
     }
 
@@ -128,8 +104,16 @@ mod tests {
 3, 4
 5, 5
 8, 9",
-        );
+        )
+        .unwrap();
         let result = part2_solver(&coords, 32);
-        assert_eq!(result, 16);
+        assert_eq!(result, Ok(16));
+    }
+
+    #[test]
+    fn no_coordinates() {
+        let coords = input_generator("").unwrap();
+        assert_eq!(solve_part1(&coords), Err(NO_COORDINATES));
+        assert_eq!(solve_part2(&coords), Err(NO_COORDINATES));
     }
 }
@@ -1,20 +1,103 @@
+//! The Sum of Its Parts: instructions that one step must finish before another begins.
+//!
+//! `input_generator` returns each instruction as `[before, after]`. Part one is the order one
+//! worker does the steps in, part two how long five workers take, and `validate` finds steps
+//! that wait on each other and so can never be done.
+use crate::error::{lines, Line, ParseError};
 use aoc_runner_derive::{aoc, aoc_generator};
 use hashbrown::HashMap;
 use regex::Regex;
+use std::collections::BTreeSet;
+
+fn step_regex() -> Regex {
+    Regex::new(r"Step ([A-Z]) must be finished before step ([A-Z]) can begin.").unwrap()
+}
+
+fn parse_step(re: &Regex, line: &Line) -> Result<(char, char), ParseError> {
+    let caps = line.captures(re)?;
+    Ok((caps[1].as_bytes()[0] as char, caps[2].as_bytes()[0] as char))
+}
 
 #[aoc_generator(day7)]
-pub fn input_generator(input: &str) -> Vec<Vec<char>> {
-    let re = Regex::new(r"Step ([A-Z]) must be finished before step ([A-Z]) can begin.").unwrap();
+pub fn input_generator(input: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let re = step_regex();
     let mut pairs: Vec<Vec<_>> = Vec::new();
 
-    for line in input.trim().lines() {
-        let caps = re.captures(line).unwrap();
-        let (first, second): (char, char) =
-            (caps[1].as_bytes()[0] as char, caps[2].as_bytes()[0] as char);
+    for line in lines(7, input.trim()) {
+        let (first, second) = parse_step(&re, &line)?;
         pairs.push(vec![first, second]);
     }
 
-    pairs
+    Ok(pairs)
+}
+
+/// Reports every cycle of steps waiting on each other, which the solvers can never finish,
+/// at the line of the cycle's first instruction.
+pub fn validate(input: &str) -> Vec<ParseError> {
+    let re = step_regex();
+    let mut errors = Vec::new();
+    let mut steps = Vec::new();
+    for line in lines(7, input.trim()) {
+        match parse_step(&re, &line) {
+            Ok(step) => steps.push((step, line)),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    let waits_on = |step: char, blocked: &BTreeSet<char>| -> Vec<char> {
+        steps
+            .iter()
+            .filter(|((first, second), _)| *second == step && blocked.contains(first))
+            .map(|((first, _), _)| *first)
+            .collect()
+    };
+    let mut blocked: BTreeSet<char> = steps.iter().flat_map(|((a, b), _)| vec![*a, *b]).collect();
+    loop {
+        // whatever isn't waiting on a blocked step can be done
+        while let Some(ready) = blocked
+            .iter()
+            .copied()
+            .find(|&step| waits_on(step, &blocked).is_empty())
+        {
+            blocked.remove(&ready);
+        }
+        // every step left waits on another, so following them back must come round again
+        let mut path = match blocked.iter().next() {
+            Some(&step) => vec![step],
+            None => break,
+        };
+        let cycle = loop {
+            let before = *waits_on(*path.last().unwrap(), &blocked)
+                .iter()
+                .min()
+                .unwrap();
+            if let Some(start) = path.iter().position(|&step| step == before) {
+                let mut cycle = path.split_off(start);
+                cycle.reverse();
+                let first = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap();
+                cycle.rotate_left(first);
+                break cycle;
+            }
+            path.push(before);
+        };
+        let line = steps
+            .iter()
+            .filter(|((first, second), _)| {
+                (0..cycle.len())
+                    .any(|i| (cycle[i], cycle[(i + 1) % cycle.len()]) == (*first, *second))
+            })
+            .map(|(_, line)| line)
+            .min_by_key(|line| line.number)
+            .unwrap();
+        let mut order: Vec<String> = cycle.iter().map(char::to_string).collect();
+        order.push(order[0].clone());
+        let reason = format!("steps wait on each other: {}", order.join(" -> "));
+        errors.push(line.error(line.text, &reason));
+        for step in &cycle {
+            blocked.remove(step);
+        }
+    }
+    errors
 }
 
 #[aoc(day7, part1)]
@@ -44,7 +127,7 @@ pub fn solve_1(pairs: &[Vec<char>]) -> String {
 
         let valid_task = *valid_tasks[0];
         done.push(valid_task);
-        todo.remove_item(&valid_task);
+        todo.retain(|t| *t != valid_task); //COMPAT1
     }
 
     done.iter().collect()
@@ -96,7 +179,7 @@ fn part2_solver(pairs: &[Vec<char>], worker_count: usize, duration: usize) -> us
             if *work_time == (total_time + 1) {
                 workers.remove(task);
                 done.push(*task);
-                todo.remove_item(task);
+                todo.retain(|t| t != task); //COMPAT2
             }
         }
 
@@ -119,7 +202,8 @@ Step A must be finished before step D can begin.
 Step B must be finished before step E can begin.
 Step D must be finished before step E can begin.
 Step F must be finished before step E can begin.",
-        );
+        )
+        .unwrap();
         assert_eq!(solve_1(&result), "CABDFE");
     }
 
@@ -133,7 +217,8 @@ Step A must be finished before step D can begin.
 Step B must be finished before step E can begin.
 Step D must be finished before step E can begin.
 Step F must be finished before step E can begin.",
-        );
+        )
+        .unwrap();
         assert_eq!(part2_solver(&result, 2, 0), 15);
     }
 }
@@ -1,11 +1,31 @@
+//! Memory Maneuver: a tree of nodes written out as a list of numbers.
+//!
+//! `input_generator` returns the numbers and `Node::new` builds the tree from them. Part one
+//! sums every node's metadata and part two works out the root node's value; the `fast`
+//! variants read the numbers directly without building the tree.
+use crate::error::{lines, Line, ParseError};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::slice::Iter;
 
+/// The two numbers a node starts with: how many children it has and how many metadata entries.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct NodeHeader {
     child_count: usize,
     metadata_count: usize,
 }
 
+impl NodeHeader {
+    pub const fn child_count(&self) -> usize {
+        self.child_count
+    }
+
+    pub const fn metadata_count(&self) -> usize {
+        self.metadata_count
+    }
+}
+
+/// A node of the tree, with its children in the order they were written.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Node {
     header: NodeHeader,
     children: Vec<Node>,
@@ -13,7 +33,11 @@ pub struct Node {
 }
 
 impl Node {
-    fn new(iter: &mut Iter<usize>) -> Self {
+    /// Reads a node, and its children, from the numbers `iter` hasn't reached yet.
+    ///
+    /// Panics if the numbers run out partway through; `input_generator` only returns numbers
+    /// that make a whole tree.
+    pub fn new(iter: &mut Iter<usize>) -> Self {
         let mut node = Self {
             header: NodeHeader {
                 child_count: *iter.next().unwrap(),
@@ -37,6 +61,18 @@ impl Node {
         node
     }
 
+    pub const fn header(&self) -> &NodeHeader {
+        &self.header
+    }
+
+    pub fn children(&self) -> &[Self] {
+        &self.children
+    }
+
+    pub fn metadata(&self) -> &[usize] {
+        &self.metadata
+    }
+
     fn metadata_sum(&self) -> usize {
         let mut data: Vec<usize> = Vec::new();
         for child in &self.children {
@@ -50,7 +86,7 @@ impl Node {
         let mut data: Vec<usize> = Vec::new();
         if self.header.child_count > 0 {
             for meta_index in &self.metadata {
-                match self.children.get(*meta_index - 1) {
+                match meta_index.checked_sub(1).and_then(|i| self.children.get(i)) {
                     Some(child) => {
                         data.push(child.node_value());
                     }
@@ -64,9 +100,94 @@ impl Node {
     }
 }
 
+/// Fails on the first problem `validate` finds, as the solvers would otherwise run out of
+/// numbers partway through the tree or ignore the ones after it.
 #[aoc_generator(day8)]
-pub fn input_generator(input: &str) -> Vec<usize> {
-    input.split(' ').map(|x| x.parse().unwrap()).collect()
+pub fn input_generator(input: &str) -> Result<Vec<usize>, ParseError> {
+    let numbers = numbers(input)?;
+    if let Some(error) = check_tree(&numbers).into_iter().next() {
+        return Err(error);
+    }
+    Ok(numbers.iter().map(|&(value, _, _)| value).collect())
+}
+
+/// Each number with the line it's on and its text, for pointing errors at it.
+fn numbers(input: &str) -> Result<Vec<(usize, Line<'_>, &str)>, ParseError> {
+    let mut numbers = Vec::new();
+    for line in lines(8, input) {
+        for number in line.text.split_whitespace() {
+            numbers.push((line.parse(number)?, line, number));
+        }
+    }
+    Ok(numbers)
+}
+
+/// Reports the first node whose header asks for more children or metadata than there are
+/// numbers left, or the numbers left over once the root node ends.
+pub fn validate(input: &str) -> Vec<ParseError> {
+    match numbers(input) {
+        Ok(numbers) => check_tree(&numbers),
+        Err(error) => vec![error],
+    }
+}
+
+fn check_tree(numbers: &[(usize, Line, &str)]) -> Vec<ParseError> {
+    if numbers.is_empty() {
+        return vec![ParseError::new(
+            8,
+            1,
+            1,
+            "",
+            "missing the root node's header",
+        )];
+    }
+    let mut index = 0;
+    let mut errors: Vec<ParseError> = check_node(numbers, &mut index).err().into_iter().collect();
+    if errors.is_empty() && index < numbers.len() {
+        let (_, line, number) = &numbers[index];
+        let reason = format!(
+            "{} numbers left over after the root node",
+            numbers.len() - index
+        );
+        errors.push(line.error(number, &reason));
+    }
+    errors
+}
+
+fn check_node(numbers: &[(usize, Line, &str)], index: &mut usize) -> Result<(), ParseError> {
+    let (children, line, header) = numbers[*index];
+    let metadata = match numbers.get(*index + 1) {
+        Some(&(metadata, _, _)) => metadata,
+        None => return Err(line.error(header, "header is missing its metadata count")),
+    };
+    *index += 2;
+    for child in 0..children {
+        if *index >= numbers.len() {
+            let reason = format!(
+                "header asks for {} but the data ends after {}",
+                counted(children, "child", "children"),
+                child
+            );
+            return Err(line.error(header, &reason));
+        }
+        check_node(numbers, index)?;
+    }
+    let left = numbers.len() - *index;
+    if metadata > left {
+        let reason = format!(
+            "header asks for {} but only {} left",
+            counted(metadata, "metadata entry", "metadata entries"),
+            counted(left, "number is", "numbers are")
+        );
+        return Err(line.error(header, &reason));
+    }
+    *index += metadata;
+    Ok(())
+}
+
+/// `count` followed by whichever of `one` and `many` it takes.
+fn counted(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
 }
 
 #[aoc(day8, part1)]
@@ -97,7 +218,9 @@ fn solve_b_fast(iter: &mut Iter<usize>) -> usize {
             let sums = (0..*child_count)
                 .map(|_| solve_b_fast(iter))
                 .collect::<Vec<usize>>();
-            iter.take(*meta_count).filter_map(|x| sums.get(x - 1)).sum()
+            iter.take(*meta_count)
+                .filter_map(|x| x.checked_sub(1).and_then(|i| sums.get(i)))
+                .sum()
         }
     }
 }
@@ -120,29 +243,55 @@ mod tests {
 
     #[test]
     fn part1() {
-        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2");
+        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap();
         let result = solve_part1(&input);
         assert_eq!(result, 138);
     }
 
     #[test]
     fn part2() {
-        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2");
+        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap();
         let result = solve_part2(&input);
         assert_eq!(result, 66);
     }
 
+    #[test]
+    fn tree() {
+        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap();
+        let root = Node::new(&mut input.iter());
+        assert_eq!(root.header().child_count(), 2);
+        assert_eq!(root.header().metadata_count(), 3);
+        assert_eq!(root.metadata(), &[1, 1, 2]);
+        assert_eq!(root.children()[0].metadata(), &[10, 11, 12]);
+        assert_eq!(root.children()[1].children()[0].metadata(), &[99]);
+    }
+
     #[test]
     fn part1_fast() {
-        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2");
+        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap();
         let result = solve_part1_fast(&input);
         assert_eq!(result, 138);
     }
 
     #[test]
     fn part2_fast() {
-        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2");
+        let input = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap();
         let result = solve_part2_fast(&input);
         assert_eq!(result, 66);
     }
+
+    #[test]
+    fn truncated() {
+        let error = input_generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.reason,
+            "header asks for 3 metadata entries but only 1 number is left"
+        );
+        assert!(input_generator("1").is_err());
+        assert!(input_generator("").is_err());
+        let input = input_generator("1 1 0 1 0 0").unwrap();
+        assert_eq!((solve_part2(&input), solve_part2_fast(&input)), (0, 0));
+    }
 }
@@ -1,12 +1,20 @@
+//! Marble Mania: the number of players and the value of the last marble.
+//!
+//! Both parts return the winning elf's score, part two with a last marble worth a hundred
+//! times as much.
+use crate::error::{lines, ParseError};
 use aoc_runner_derive::{aoc, aoc_generator};
 use regex::Regex;
 use std::collections::VecDeque;
 
 #[aoc_generator(day9)]
-pub fn input_generator(input: &str) -> [usize; 2] {
+pub fn input_generator(input: &str) -> Result<[usize; 2], ParseError> {
     let re = Regex::new(r"(\d+) players; last marble is worth (\d+) points").unwrap();
-    let caps = re.captures(input).unwrap();
-    [caps[1].parse().unwrap(), caps[2].parse().unwrap()]
+    let line = lines(9, input)
+        .next()
+        .ok_or_else(|| ParseError::new(9, 1, 1, "", "missing game description"))?;
+    let caps = line.captures(&re)?;
+    Ok([line.parse(&caps[1])?, line.parse(&caps[2])?])
 }
 
 #[aoc(day9, part1)]
@@ -51,28 +59,28 @@ mod tests {
 
     #[test]
     fn part1() {
-        let mut input = input_generator("9 players; last marble is worth 25 points");
+        let mut input = input_generator("9 players; last marble is worth 25 points").unwrap();
         assert_eq!(solve_part1(&input), 32);
 
-        input = input_generator("10 players; last marble is worth 1618 points");
+        input = input_generator("10 players; last marble is worth 1618 points").unwrap();
         assert_eq!(solve_part1(&input), 8317);
 
-        input = input_generator("13 players; last marble is worth 7999 points");
+        input = input_generator("13 players; last marble is worth 7999 points").unwrap();
         assert_eq!(solve_part1(&input), 146_373);
 
-        input = input_generator("17 players; last marble is worth 1104 points");
+        input = input_generator("17 players; last marble is worth 1104 points").unwrap();
         assert_eq!(solve_part1(&input), 2764);
 
-        input = input_generator("21 players; last marble is worth 6111 points");
+        input = input_generator("21 players; last marble is worth 6111 points").unwrap();
         assert_eq!(solve_part1(&input), 54718);
 
-        input = input_generator("30 players; last marble is worth 5807 points");
+        input = input_generator("30 players; last marble is worth 5807 points").unwrap();
         assert_eq!(solve_part1(&input), 37305);
     }
 
     #[test]
     fn part2() {
-        let input = input_generator("9 players; last marble is worth 25 points");
+        let input = input_generator("9 players; last marble is worth 25 points").unwrap();
         assert_eq!(solve_part2(&input), 22563);
     }
 }
@@ -0,0 +1,150 @@
+//! Every day's parsed input as JSON, for tools that would rather read the structured models
+//! than parse the puzzle text again. Only built with the `serde` feature.
+//!
+//! Models come out much as they are declared. Grids are `{ width, height, cells }` with the
+//! cells in reading order, except grids of tiles, which are their rows as strings. Maps are
+//! lists of `[key, value]` pairs, as JSON only allows strings for keys. Maps and sets are
+//! sorted, so the same input always dumps the same JSON.
+use crate::grid::Grid;
+use crate::*;
+use hashbrown::{HashMap, HashSet};
+use serde::{Serialize, Serializer};
+use std::error::Error;
+use std::hash::Hash;
+
+fn sorted_entries<K: Ord + Hash, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Serializes a map as a list of `[key, value]` pairs in key order.
+pub fn entries<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Hash + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    sorted_entries(map).serialize(serializer)
+}
+
+/// Serializes a set as a sorted list.
+pub fn sorted<T, S>(set: &HashSet<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Ord + Hash + Serialize,
+    S: Serializer,
+{
+    let mut items: Vec<_> = set.iter().collect();
+    items.sort();
+    items.serialize(serializer)
+}
+
+fn tile_rows(grid: &Grid<u8>) -> Vec<String> {
+    let drawn = grid.render(|&tile| tile as char);
+    drawn.lines().map(str::to_owned).collect()
+}
+
+/// Serializes a grid of tiles as its rows, each a string with a character per tile.
+pub fn tiles<S: Serializer>(grid: &Grid<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+    tile_rows(grid).serialize(serializer)
+}
+
+fn json<T: Serialize + ?Sized>(model: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(model)
+}
+
+/// Parses `input` the way `day`'s solvers do and returns the result as pretty-printed JSON.
+/// Day2 has no model beyond its lines, and day11 none beyond its serial number.
+pub fn to_json(day: u8, input: &str) -> Result<String, Box<dyn Error>> {
+    let input = input.trim_end_matches('\n');
+    let dumped = match day {
+        1 => json(&day1::input_generator(input)?),
+        2 => json(&input.lines().collect::<Vec<_>>()),
+        3 => json(&day3::input_generator(input)?),
+        4 => {
+            let guards = day4::input_generator(input)?;
+            let guards: Vec<_> = sorted_entries(&guards)
+                .into_iter()
+                .map(|(guard, minutes)| (guard, sorted_entries(minutes)))
+                .collect();
+            json(&guards)
+        }
+        5 => json(&day5::input_generator(input)?),
+        6 => json(&day6::input_generator(input)?),
+        7 => json(&day7::input_generator(input)?),
+        8 => json(&day8::Node::new(&mut day8::input_generator(input)?.iter())),
+        9 => json(&day9::input_generator(input)?),
+        10 => json(&day10::input_generator(input)?),
+        11 => json(&day11::parse_serial(input)?),
+        12 => json(&day12::input_generator(input)?),
+        13 => {
+            let (carts, tiles) = day13::input_generator(input)?;
+            json(&(carts, tile_rows(&tiles)))
+        }
+        14 => json(&day14::input_generator(input)?),
+        15 => json(&day15::input_generator(input)?),
+        16 => json(&day16::input_generator(input)?),
+        17 => json(&day17::input_generator(input)?),
+        18 => json(&day18::input_generator(input)?),
+        19 => json(&day19::input_generator(input)?),
+        20 => json(&sorted_entries(&day20::input_generator(input)?)),
+        21 => json(&day21::input_generator(input)?),
+        22 => json(&day22::input_generator(input)?),
+        23 => json(&sorted_entries(&day23::input_generator(input)?)),
+        24 => json(&day24::input_generator(input)?),
+        25 => json(&day25::input_generator(input)?),
+        _ => return Err(format!("no day{}", day).into()),
+    };
+    Ok(dumped?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dump::*;
+    use crate::gen;
+
+    #[test]
+    fn models() {
+        let claims = to_json(3, "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4").unwrap();
+        assert!(claims.contains("\"id\": 2"));
+        assert!(claims.contains("\"w\": 4"));
+
+        let tree: serde_json::Value =
+            serde_json::from_str(&to_json(8, "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap())
+                .unwrap();
+        assert_eq!(tree["metadata"], serde_json::json!([1, 1, 2]));
+        assert_eq!(tree["children"][1]["children"][0]["metadata"][0], 99);
+
+        let bots: serde_json::Value =
+            serde_json::from_str(&to_json(23, "pos=<1,0,0>, r=4\npos=<0,0,0>, r=1").unwrap())
+                .unwrap();
+        assert_eq!(bots, serde_json::json!([[[0, 0, 0], 1], [[1, 0, 0], 4]]));
+
+        let ground: serde_json::Value =
+            serde_json::from_str(&to_json(17, "x=500, y=1..2").unwrap()).unwrap();
+        assert_eq!(ground["ground"], serde_json::json!([".+.", ".#.", ".#."]));
+
+        let groups: serde_json::Value = serde_json::from_str(
+            &to_json(
+                24,
+                "Immune System:\n17 units each with 5390 hit points with an attack that does 4507 fire damage at initiative 2\n\nInfection:\n801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(groups[1]["unit_count"], 801);
+        assert!(groups[0].get("target_id").is_none());
+
+        assert!(to_json(3, "#1 @ 1,3 4x4").is_err());
+        assert!(to_json(26, "").is_err());
+    }
+
+    #[test]
+    fn every_day() {
+        for day in 1..=25 {
+            let input = gen::generate(day, 1, 5).unwrap();
+            let dumped = to_json(day, &input).unwrap();
+            assert_eq!(dumped, to_json(day, &input).unwrap());
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! The "ElfCode" device shared by day16, day19 and day21.
+//!
+//! A `Machine` owns a bank of registers and an optional program. When the program
+//! declares `#ip N`, register N is bound to the instruction pointer as described in day19.
+use crate::error::{lines, ParseError};
+use regex::Regex;
+
+/// The sixteen operations, named as in the puzzle: an `r` reads that operand from a register
+/// and an `i` takes it as a value.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Opcode {
+    addr,
+    addi,
+    mulr,
+    muli,
+    banr,
+    bani,
+    borr,
+    bori,
+    setr,
+    seti,
+    gtir,
+    gtri,
+    gtrr,
+    eqir,
+    eqri,
+    eqrr,
+}
+
+impl Opcode {
+    /// All sixteen opcodes, in the order the puzzle lists them.
+    pub fn values() -> Vec<Self> {
+        vec![
+            Self::addr,
+            Self::addi,
+            Self::mulr,
+            Self::muli,
+            Self::banr,
+            Self::bani,
+            Self::borr,
+            Self::bori,
+            Self::setr,
+            Self::seti,
+            Self::gtir,
+            Self::gtri,
+            Self::gtrr,
+            Self::eqir,
+            Self::eqri,
+            Self::eqrr,
+        ]
+    }
+
+    /// The opcode called `name`, if there is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let opcode = match name {
+            "addr" => Self::addr,
+            "addi" => Self::addi,
+            "mulr" => Self::mulr,
+            "muli" => Self::muli,
+            "banr" => Self::banr,
+            "bani" => Self::bani,
+            "borr" => Self::borr,
+            "bori" => Self::bori,
+            "setr" => Self::setr,
+            "seti" => Self::seti,
+            "gtir" => Self::gtir,
+            "gtri" => Self::gtri,
+            "gtrr" => Self::gtrr,
+            "eqir" => Self::eqir,
+            "eqri" => Self::eqri,
+            "eqrr" => Self::eqrr,
+            _ => return None,
+        };
+        Some(opcode)
+    }
+
+    /// Whether the opcode reads its `a` and its `b` from registers, rather than taking them as
+    /// values.
+    pub const fn reads_registers(self) -> (bool, bool) {
+        match self {
+            Self::addr | Self::mulr | Self::banr | Self::borr | Self::gtrr | Self::eqrr => {
+                (true, true)
+            }
+            Self::addi
+            | Self::muli
+            | Self::bani
+            | Self::bori
+            | Self::setr
+            | Self::gtri
+            | Self::eqri => (true, false),
+            Self::gtir | Self::eqir => (false, true),
+            Self::seti => (false, false),
+        }
+    }
+
+    /// Computes the value this opcode would write, given the current registers.
+    pub fn apply(self, registers: &[usize], a: usize, b: usize) -> usize {
+        match self {
+            Self::addr => registers[a] + registers[b],
+            Self::addi => registers[a] + b,
+            Self::mulr => registers[a] * registers[b],
+            Self::muli => registers[a] * b,
+            Self::banr => registers[a] & registers[b],
+            Self::bani => registers[a] & b,
+            Self::borr => registers[a] | registers[b],
+            Self::bori => registers[a] | b,
+            Self::setr => registers[a],
+            Self::seti => a,
+            Self::gtir => (a > registers[b]) as usize,
+            Self::gtri => (registers[a] > b) as usize,
+            Self::gtrr => (registers[a] > registers[b]) as usize,
+            Self::eqir => (a == registers[b]) as usize,
+            Self::eqri => (registers[a] == b) as usize,
+            Self::eqrr => (registers[a] == registers[b]) as usize,
+        }
+    }
+}
+
+/// An opcode with its operands: `a` and `b` are read and register `c` is written.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+}
+
+impl Instruction {
+    pub const fn new(opcode: Opcode, a: usize, b: usize, c: usize) -> Self {
+        Self { opcode, a, b, c }
+    }
+}
+
+/// A parsed program: its instructions, and the register bound to the instruction pointer if
+/// it declares one.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Program {
+    pub ip_register: Option<usize>,
+    pub instructions: Vec<Instruction>,
+}
+
+/// Parses an optional `#ip` declaration followed by one instruction per line:
+///
+/// ```text
+/// #ip 4
+/// addi 4 16 4
+/// ```
+///
+/// Fails on a register that a machine with `register_count` registers doesn't have.
+pub fn parse_program(day: u8, input: &str, register_count: usize) -> Result<Program, ParseError> {
+    let re = Regex::new(r"^(\w{4})\s(\d+)\s(\d+)\s(\d+)$").unwrap();
+    let mut program = Program::default();
+    for line in lines(day, input) {
+        // a register must be one of the machine's
+        let register = |text: &str| {
+            let register = line.parse(text)?;
+            if register < register_count {
+                Ok(register)
+            } else {
+                let reason = format!("register {} of only {}", register, register_count);
+                Err(line.error(text, &reason))
+            }
+        };
+        if line.text.is_empty() {
+            continue;
+        }
+        if let Some(ip) = line.text.strip_prefix("#ip ") {
+            program.ip_register = Some(register(ip.trim())?);
+            continue;
+        }
+        let caps = line.captures(&re)?;
+        let opcode =
+            Opcode::from_name(&caps[1]).ok_or_else(|| line.error(&caps[1], "unknown opcode"))?;
+        let (a, b) = opcode.reads_registers();
+        let operand = |text: &str, is_register: bool| {
+            if is_register {
+                register(text)
+            } else {
+                line.parse(text)
+            }
+        };
+        program.instructions.push(Instruction::new(
+            opcode,
+            operand(&caps[2], a)?,
+            operand(&caps[3], b)?,
+            register(&caps[4])?,
+        ));
+    }
+    Ok(program)
+}
+
+/// The device: its registers, and the instruction pointer into the program it was given.
+#[derive(Debug, Clone)]
+pub struct Machine {
+    pub registers: Vec<usize>,
+    pub ip: usize,
+    ip_register: Option<usize>,
+    instructions: Vec<Instruction>,
+}
+
+impl Machine {
+    /// A machine with `register_count` registers, all zero, and no program.
+    pub fn new(register_count: usize) -> Self {
+        Self {
+            registers: vec![0; register_count],
+            ip: 0,
+            ip_register: None,
+            instructions: Vec::new(),
+        }
+    }
+
+    /// A machine ready to run `program` from its first instruction.
+    pub fn with_program(register_count: usize, program: &Program) -> Self {
+        let mut machine = Self::new(register_count);
+        machine.ip_register = program.ip_register;
+        machine.instructions = program.instructions.clone();
+        machine
+    }
+
+    /// The register bound to the instruction pointer, if the program declared one.
+    pub const fn ip_register(&self) -> Option<usize> {
+        self.ip_register
+    }
+
+    /// Applies a single instruction to the registers, ignoring the instruction pointer.
+    pub fn execute(&mut self, instruction: &Instruction) {
+        self.registers[instruction.c] =
+            instruction
+                .opcode
+                .apply(&self.registers, instruction.a, instruction.b);
+    }
+
+    /// The instruction the next call to `step` will execute, if the program hasn't halted.
+    pub fn current(&self) -> Option<&Instruction> {
+        self.instructions.get(self.ip)
+    }
+
+    /// Executes the instruction at the instruction pointer.
+    /// Returns false, without doing anything, once the program has halted.
+    pub fn step(&mut self) -> bool {
+        let instruction = match self.instructions.get(self.ip) {
+            Some(instruction) => *instruction,
+            None => return false,
+        };
+        if let Some(register) = self.ip_register {
+            self.registers[register] = self.ip;
+        }
+        self.execute(&instruction);
+        self.ip = match self.ip_register {
+            Some(register) => self.registers[register] + 1,
+            None => self.ip + 1,
+        };
+        true
+    }
+
+    /// Steps until the instruction pointer leaves the program.
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::elfcode::*;
+
+    #[test]
+    fn opcodes() {
+        let registers = [3, 2, 1, 1];
+        let matching: Vec<Opcode> = Opcode::values()
+            .into_iter()
+            .filter(|opcode| opcode.apply(&registers, 2, 1) == 2)
+            .collect();
+        assert_eq!(matching, vec![Opcode::addi, Opcode::mulr, Opcode::seti]);
+    }
+
+    #[test]
+    fn run() {
+        let program = parse_program(
+            19,
+            "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5",
+            6,
+        )
+        .unwrap();
+        let mut machine = Machine::with_program(6, &program);
+        machine.run();
+        assert_eq!(machine.registers, vec![6, 5, 6, 0, 0, 9]);
+    }
+
+    #[test]
+    fn registers() {
+        let reason = |input| parse_program(19, input, 6).err().unwrap().to_string();
+        assert_eq!(
+            reason("#ip 9"),
+            "day19 line 1 column 5: register 9 of only 6 in \"#ip 9\""
+        );
+        assert_eq!(
+            reason("seti 0 0 9"),
+            "day19 line 1 column 10: register 9 of only 6 in \"seti 0 0 9\""
+        );
+        assert_eq!(
+            reason("addr 7 1 0"),
+            "day19 line 1 column 6: register 7 of only 6 in \"addr 7 1 0\""
+        );
+        assert!(parse_program(19, "seti 7 0 0", 6).is_ok());
+        assert!(parse_program(19, "seti 7 0 0 junk", 6).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Errors for input that can't be used, pointing at the line and column where it went wrong,
+//! and the `Line`s the parsers read to build them.
+use regex::{Captures, Regex};
+use std::any::type_name;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+/// Where, and why, a day's puzzle input couldn't be understood.
+/// `line` and `column` are 1-based; `text` is the whole offending line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(day: u8, line: usize, column: usize, text: &str, reason: &str) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            text: text.to_owned(),
+            reason: reason.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day{} line {} column {}: {} in {:?}",
+            self.day, self.line, self.column, self.reason, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// A line of puzzle input that remembers where it came from.
+#[derive(Clone, Copy, Debug)]
+pub struct Line<'a> {
+    pub day: u8,
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// Builds an error pointing at `field`, which should be a slice of this line.
+    /// Anything else points at the start of the line.
+    pub fn error(&self, field: &str, reason: &str) -> ParseError {
+        let start = self.text.as_ptr() as usize;
+        let offset = (field.as_ptr() as usize).wrapping_sub(start);
+        let inside = offset <= self.text.len() && field.len() <= self.text.len() - offset;
+        let column = if inside && self.text.is_char_boundary(offset) {
+            self.text[..offset].chars().count() + 1
+        } else {
+            1
+        };
+        ParseError::new(self.day, self.number, column, self.text, reason)
+    }
+
+    pub fn captures(&self, re: &Regex) -> Result<Captures<'a>, ParseError> {
+        re.captures(self.text)
+            .ok_or_else(|| self.error(self.text, "line does not match the expected format"))
+    }
+
+    pub fn parse<T: FromStr>(&self, field: &str) -> Result<T, ParseError> {
+        field
+            .parse()
+            .map_err(|_| self.error(field, &format!("expected {}", type_name::<T>())))
+    }
+}
+
+pub fn lines(day: u8, input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(move |(index, text)| Line {
+        day,
+        number: index + 1,
+        text,
+    })
+}
+
+/// Why input read from a stream couldn't be used: either reading it failed, or what was read
+/// couldn't be understood.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "reading input: {}", error),
+            Self::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// Reads `reader` a line at a time, numbering the lines as `lines` does and handing each to
+/// `parse`, so only one line of the input is held at once.
+pub fn read_lines<R: BufRead, T>(
+    day: u8,
+    reader: R,
+    mut parse: impl FnMut(Line) -> Result<T, ParseError>,
+) -> impl Iterator<Item = Result<T, ReadError>> {
+    reader.lines().enumerate().map(move |(index, text)| {
+        let text = text?;
+        Ok(parse(Line {
+            day,
+            number: index + 1,
+            text: &text,
+        })?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::*;
+
+    #[test]
+    fn points_at_field() {
+        let line = lines(3, "#1 @ 1,3: 4x4\n#2 @ 3,x: 4x4").nth(1).unwrap();
+        let error = line.parse::<u32>(&line.text[7..8]).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(3, 2, 8, "#2 @ 3,x: 4x4", "expected u32")
+        );
+        assert_eq!(
+            error.to_string(),
+            "day3 line 2 column 8: expected u32 in \"#2 @ 3,x: 4x4\""
+        );
+    }
+
+    #[test]
+    fn foreign_field() {
+        let line = lines(1, "+1").next().unwrap();
+        assert_eq!(line.error("elsewhere", "nope").column, 1);
+    }
+
+    #[test]
+    fn reading() {
+        let input = "+1\r\n-2\n\n+x\n";
+        let parsed: Vec<_> = read_lines(1, input.as_bytes(), |line| line.parse::<i32>(line.text))
+            .map(|value| value.map_err(|error| error.to_string()))
+            .collect();
+        assert_eq!(parsed[..2], [Ok(1), Ok(-2)]);
+        assert_eq!(
+            parsed[3],
+            Err("day1 line 4 column 1: expected i32 in \"+x\"".to_owned())
+        );
+
+        let invalid: &[u8] = b"+1\n\xff\n";
+        let mut values = read_lines(1, invalid, |line| line.parse::<i32>(line.text));
+        assert_eq!(values.next().unwrap().unwrap(), 1);
+        assert!(matches!(values.next(), Some(Err(ReadError::Io(_)))));
+    }
+}
@@ -0,0 +1,903 @@
+//! Seeded puzzle inputs for fuzzing and load testing the solvers.
+//!
+//! Every day has a generator writing input in that day's exact format, with `size` scaling the
+//! input the way the day's own puzzle grows: more lines, a bigger map, a longer polymer. The same
+//! seed and size always give the same input. Each generator keeps to whatever the day's solvers
+//! assume of the real input, noted on the generator; the days whose answer can only be found by
+//! running the puzzle to its end are still free to run for a long time.
+use crate::elfcode::Opcode;
+use crate::grid::Grid;
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// SplitMix64: small, fast and good enough to shuffle puzzle input.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    pub const fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `low..=high`.
+    pub const fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next_u64() % (high - low + 1) as u64) as i64
+    }
+
+    pub const fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// The input for `day`, or `None` for a day that doesn't exist.
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    let generator = match day {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+        11 => day11,
+        12 => day12,
+        13 => day13,
+        14 => day14,
+        15 => day15,
+        16 => day16,
+        17 => day17,
+        18 => day18,
+        19 => day19,
+        20 => day20,
+        21 => day21,
+        22 => day22,
+        23 => day23,
+        24 => day24,
+        25 => day25,
+        _ => return None,
+    };
+    Some(generator(&mut Rng::new(seed), size))
+}
+
+fn join(lines: &[String]) -> String {
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+/// `size` frequency changes. Part two only finds a repeat when the changes allow one.
+pub fn day1(rng: &mut Rng, size: usize) -> String {
+    let lines: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let change = rng.range(1, 20);
+            format!("{:+}", if rng.one_in(2) { change } else { -change })
+        })
+        .collect();
+    join(&lines)
+}
+
+/// `size` box IDs, two of which differ by exactly one letter.
+pub fn day2(rng: &mut Rng, size: usize) -> String {
+    fn id(rng: &mut Rng) -> Vec<u8> {
+        (0..26).map(|_| b'a' + rng.below(26) as u8).collect()
+    }
+
+    let mut ids: Vec<Vec<u8>> = (1..size.max(2)).map(|_| id(rng)).collect();
+    let mut twin = rng.pick(&ids).clone();
+    let at = rng.below(twin.len());
+    twin[at] = b'a' + (twin[at] - b'a' + 1 + rng.below(25) as u8) % 26;
+    ids.insert(rng.below(ids.len() + 1), twin);
+    let lines: Vec<String> = ids
+        .into_iter()
+        .map(|id| String::from_utf8(id).unwrap())
+        .collect();
+    join(&lines)
+}
+
+/// `size` claims, one of which overlaps no other. The fabric grows with the claims to keep
+/// them about as crowded as the real input.
+pub fn day3(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let side = ((size as f64).sqrt() * 28.0).max(40.0) as i64;
+    let claim = |rng: &mut Rng| {
+        let (width, height) = (rng.range(5, 29), rng.range(5, 29));
+        (
+            rng.range(0, side - width),
+            rng.range(0, side - height),
+            width,
+            height,
+        )
+    };
+    let overlaps = |a: (i64, i64, i64, i64), b: (i64, i64, i64, i64)| {
+        a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+    };
+
+    let intact_id = 1 + rng.below(size);
+    let intact = claim(rng);
+    let lines: Vec<String> = (1..=size)
+        .map(|id| {
+            let (x, y, width, height) = if id == intact_id {
+                intact
+            } else {
+                loop {
+                    let other = claim(rng);
+                    if !overlaps(intact, other) {
+                        break other;
+                    }
+                }
+            };
+            format!("#{} @ {},{}: {}x{}", id, x, y, width, height)
+        })
+        .collect();
+    join(&lines)
+}
+
+/// The guard records for `size` nights, shuffled. Someone falls asleep on the first night.
+pub fn day4(rng: &mut Rng, size: usize) -> String {
+    const DAYS_IN_MONTH: [usize; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    let date = |mut day: usize| {
+        let year = 1518 + day / 365;
+        day %= 365;
+        let mut month = 0;
+        while day >= DAYS_IN_MONTH[month] {
+            day -= DAYS_IN_MONTH[month];
+            month += 1;
+        }
+        format!("{}-{:02}-{:02}", year, month + 1, day + 1)
+    };
+
+    let guards: Vec<i64> = (0..size / 10 + 2).map(|_| rng.range(10, 3500)).collect();
+    let mut lines = Vec::new();
+    for night in 1..=size.max(1) {
+        let guard = *rng.pick(&guards);
+        if rng.one_in(2) {
+            let minute = rng.range(45, 59);
+            lines.push(format!(
+                "[{} 23:{:02}] Guard #{} begins shift",
+                date(night - 1),
+                minute,
+                guard
+            ));
+        } else {
+            let minute = rng.below(4);
+            lines.push(format!(
+                "[{} 00:{:02}] Guard #{} begins shift",
+                date(night),
+                minute,
+                guard
+            ));
+        }
+
+        let naps = if night == 1 { 1 } else { rng.below(4) };
+        let mut minutes: Vec<usize> = (5..60).collect();
+        rng.shuffle(&mut minutes);
+        minutes.truncate(naps * 2);
+        minutes.sort();
+        for nap in minutes.chunks(2) {
+            lines.push(format!("[{} 00:{:02}] falls asleep", date(night), nap[0]));
+            lines.push(format!("[{} 00:{:02}] wakes up", date(night), nap[1]));
+        }
+    }
+    rng.shuffle(&mut lines);
+    join(&lines)
+}
+
+/// A polymer `size` units long, with plenty of units reacting with their neighbour.
+pub fn day5(rng: &mut Rng, size: usize) -> String {
+    let mut polymer: Vec<u8> = Vec::with_capacity(size);
+    while polymer.len() < size.max(1) {
+        let unit = match polymer.last() {
+            Some(&last) if rng.one_in(3) => last ^ 0x20,
+            _ => {
+                let unit = b'a' + rng.below(26) as u8;
+                if rng.one_in(2) {
+                    unit.to_ascii_uppercase()
+                } else {
+                    unit
+                }
+            }
+        };
+        polymer.push(unit);
+    }
+    let mut output = String::from_utf8(polymer).unwrap();
+    output.push('\n');
+    output
+}
+
+/// `size` coordinates, spread out like the real input's 50, so that part two's region only
+/// disappears once there are many more.
+pub fn day6(rng: &mut Rng, size: usize) -> String {
+    let span = 40 + 6 * size.min(50) as i64;
+    let lines: Vec<String> = (0..size.max(1))
+        .map(|_| format!("{}, {}", rng.range(0, span), rng.range(0, span)))
+        .collect();
+    join(&lines)
+}
+
+/// Instructions for `size` steps, at most 26, with every step depending on an earlier one.
+pub fn day7(rng: &mut Rng, size: usize) -> String {
+    let mut steps: Vec<char> = (b'A'..=b'Z').map(char::from).collect();
+    rng.shuffle(&mut steps);
+    steps.truncate(size.clamp(2, 26));
+
+    let mut lines = Vec::new();
+    for later in 1..steps.len() {
+        let mut earlier: Vec<usize> = (0..later).collect();
+        rng.shuffle(&mut earlier);
+        let count = 1 + rng.below(later.min(6));
+        for &before in &earlier[..count] {
+            lines.push(format!(
+                "Step {} must be finished before step {} can begin.",
+                steps[before], steps[later]
+            ));
+        }
+    }
+    rng.shuffle(&mut lines);
+    join(&lines)
+}
+
+/// A license tree of `size` nodes, each with one to three metadata entries that mostly name
+/// one of the node's children.
+pub fn day8(rng: &mut Rng, size: usize) -> String {
+    fn write(node: usize, children: &[Vec<usize>], rng: &mut Rng, numbers: &mut Vec<usize>) {
+        let metadata = 1 + rng.below(3);
+        numbers.push(children[node].len());
+        numbers.push(metadata);
+        for &child in &children[node] {
+            write(child, children, rng, numbers);
+        }
+        let entries = children[node].len() + 1;
+        numbers.extend((0..metadata).map(|_| 1 + rng.below(entries)));
+    }
+
+    // a random recursive tree keeps the depth near log(size)
+    let mut children = vec![Vec::new(); size.max(1)];
+    for node in 1..children.len() {
+        let parent = rng.below(node);
+        children[parent].push(node);
+    }
+    let mut numbers = Vec::new();
+    write(0, &children, rng, &mut numbers);
+    let numbers: Vec<String> = numbers.iter().map(usize::to_string).collect();
+    join(&[numbers.join(" ")])
+}
+
+/// A game whose last marble is worth between `size / 2` and `size` points.
+pub fn day9(rng: &mut Rng, size: usize) -> String {
+    let players = rng.range(9, 500);
+    let points = rng.range(size as i64 / 2, size as i64).max(1);
+    join(&[format!(
+        "{} players; last marble is worth {} points",
+        players, points
+    )])
+}
+
+/// A message of `size` letters that appears after up to `100 * size` seconds.
+///
+/// Points moving straight up and down pin the message's top and bottom rows, so the sky is
+/// at its shortest the moment the message appears.
+pub fn day10(rng: &mut Rng, size: usize) -> String {
+    fn padded(n: i64) -> String {
+        if n < 0 {
+            n.to_string()
+        } else {
+            format!(" {}", n)
+        }
+    }
+
+    let seconds = rng.range(10, 10 + 100 * size as i64);
+    let mut lines = Vec::new();
+    for letter in 0..size.max(1) as i64 {
+        for y in 0..10 {
+            for x in 0..6 {
+                let pinned = x < 2 && (y == 0 || y == 9);
+                if !pinned && !rng.one_in(3) {
+                    continue;
+                }
+                let dx = rng.range(-5, 5);
+                let dy = match (pinned, y) {
+                    (true, 0) => -5,
+                    (true, _) => 5,
+                    _ => rng.range(-5, 5),
+                };
+                let (x, y) = (letter * 8 + x - seconds * dx, y - seconds * dy);
+                lines.push(format!(
+                    "position=<{}, {}> velocity=<{}, {}>",
+                    padded(x),
+                    padded(y),
+                    padded(dx),
+                    padded(dy)
+                ));
+            }
+        }
+    }
+    rng.shuffle(&mut lines);
+    join(&lines)
+}
+
+/// A grid serial number; the grid is always 300x300, so `size` is ignored.
+pub fn day11(rng: &mut Rng, _size: usize) -> String {
+    join(&[rng.range(1, 9999).to_string()])
+}
+
+/// `size` pots and all 32 rules. The rules copy every pot from the same pot up to two away,
+/// so the whole row slides along, or stays put, in the steady drift part two waits for.
+pub fn day12(rng: &mut Rng, size: usize) -> String {
+    let mut pots: Vec<char> = (0..size.max(1))
+        .map(|_| if rng.one_in(2) { '#' } else { '.' })
+        .collect();
+    pots[0] = '#';
+    let mut lines = vec![
+        format!("initial state: {}", pots.iter().collect::<String>()),
+        String::new(),
+    ];
+    let source = rng.below(5);
+    for pattern in 0..32 {
+        let text: String = (0..5)
+            .map(|bit| if pattern & (16 >> bit) != 0 { '#' } else { '.' })
+            .collect();
+        lines.push(format!("{} => {}", text, &text[source..=source]));
+    }
+    join(&lines)
+}
+
+/// `size` rectangular loops of track and an odd number of carts, at least three.
+///
+/// No two corners share a row or column, so loops only ever meet at crossings. Carts are
+/// not guaranteed to crash.
+pub fn day13(rng: &mut Rng, size: usize) -> String {
+    let loops = size.max(1);
+    let corners = |rng: &mut Rng| {
+        let mut lines: Vec<usize> = (0..loops * 6 + 4).collect();
+        rng.shuffle(&mut lines);
+        lines.truncate(loops * 2);
+        let first = *lines.iter().min().unwrap();
+        lines.iter().map(|line| line - first).collect::<Vec<_>>()
+    };
+    let (xs, ys) = (corners(rng), corners(rng));
+    let width = xs.iter().max().unwrap() + 1;
+    let height = ys.iter().max().unwrap() + 1;
+
+    let mut tracks = Grid::new(width, height, b' ');
+    for (x, y) in xs.chunks(2).zip(ys.chunks(2)) {
+        let (left, right) = (x[0].min(x[1]), x[0].max(x[1]));
+        let (top, bottom) = (y[0].min(y[1]), y[0].max(y[1]));
+        for x in left + 1..right {
+            for &y in &[top, bottom] {
+                tracks[(x, y)] = if tracks[(x, y)] == b'|' { b'+' } else { b'-' };
+            }
+        }
+        for y in top + 1..bottom {
+            for &x in &[left, right] {
+                tracks[(x, y)] = if tracks[(x, y)] == b'-' { b'+' } else { b'|' };
+            }
+        }
+        tracks[(left, top)] = b'/';
+        tracks[(right, top)] = b'\\';
+        tracks[(left, bottom)] = b'\\';
+        tracks[(right, bottom)] = b'/';
+    }
+
+    let mut straights: Vec<_> = tracks
+        .iter()
+        .filter(|(_, &tile)| tile == b'-' || tile == b'|')
+        .map(|(position, _)| position)
+        .collect();
+    rng.shuffle(&mut straights);
+    let carts = (size / 2).max(1) * 2 + 1;
+    for &position in straights.iter().take(carts) {
+        let heading = match (tracks[position], rng.one_in(2)) {
+            (b'-', true) => b'<',
+            (b'-', false) => b'>',
+            (_, true) => b'^',
+            (_, false) => b'v',
+        };
+        tracks[position] = heading;
+    }
+    join(&[tracks.render(|&tile| tile as char)])
+}
+
+/// A recipe count between `size` and `2 * size`.
+pub fn day14(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    join(&[(size + rng.below(size)).to_string()])
+}
+
+/// A walled cave `size` squares across, with every unit in one open area.
+pub fn day15(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(7);
+    loop {
+        let mut cave = Grid::from_fn(side, side, |(x, y)| {
+            let edge = x == 0 || y == 0 || x == side - 1 || y == side - 1;
+            if edge || rng.one_in(5) {
+                b'#'
+            } else {
+                b'.'
+            }
+        });
+
+        let mut largest: Vec<_> = Vec::new();
+        let mut seen = Grid::new(side, side, false);
+        for start in cave.positions() {
+            if cave[start] != b'.' || seen[start] {
+                continue;
+            }
+            let mut area = vec![start];
+            let mut queue = VecDeque::new();
+            seen[start] = true;
+            queue.push_back(start);
+            while let Some(position) = queue.pop_front() {
+                for next in cave.neighbours4(position) {
+                    if cave[next] == b'.' && !seen[next] {
+                        seen[next] = true;
+                        area.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if area.len() > largest.len() {
+                largest = area;
+            }
+        }
+
+        let units = (largest.len() / 15).max(2);
+        if largest.len() < units * 2 {
+            continue;
+        }
+        rng.shuffle(&mut largest);
+        for (index, &position) in largest.iter().take(units).enumerate() {
+            cave[position] = match index {
+                0 => b'G',
+                1 => b'E',
+                _ if rng.one_in(2) => b'G',
+                _ => b'E',
+            };
+        }
+        return join(&[cave.render(|&tile| tile as char)]);
+    }
+}
+
+/// `size` samples and a program of `size` instructions, under a secret numbering of the
+/// opcodes. The program's registers stay below 2^16.
+///
+/// Random samples don't always tell every opcode apart, so one more sample for each opcode
+/// follows them that no other opcode could explain.
+pub fn day16(rng: &mut Rng, size: usize) -> String {
+    let mut opcodes = Opcode::values();
+    rng.shuffle(&mut opcodes);
+    let number = |opcode: Opcode| opcodes.iter().position(|&o| o == opcode).unwrap();
+    let registers = |registers: &[usize]| {
+        let registers: Vec<String> = registers.iter().map(usize::to_string).collect();
+        registers.join(", ")
+    };
+
+    let sample = |rng: &mut Rng, opcode: Opcode| {
+        let before: Vec<usize> = (0..4).map(|_| rng.below(4)).collect();
+        let (a, b, c) = (rng.below(4), rng.below(4), rng.below(4));
+        let mut after = before.clone();
+        after[c] = opcode.apply(&before, a, b);
+        (before, opcode, (a, b, c), after)
+    };
+    // how many opcodes could have turned `before` into `after`
+    let explaining = |(before, _, (a, b, c), after): &(Vec<usize>, Opcode, _, Vec<usize>)| {
+        let explains = |opcode: &&Opcode| {
+            let mut registers = before.clone();
+            registers[*c] = opcode.apply(before, *a, *b);
+            registers == *after
+        };
+        opcodes.iter().filter(explains).count()
+    };
+
+    let mut samples = Vec::new();
+    for _ in 0..size.max(1) {
+        let opcode = *rng.pick(&opcodes);
+        samples.push(sample(rng, opcode));
+    }
+    for &opcode in &opcodes {
+        let telling = std::iter::repeat_with(|| sample(rng, opcode))
+            .find(|candidate| explaining(candidate) == 1)
+            .unwrap();
+        samples.push(telling);
+    }
+
+    let mut output = String::new();
+    for (before, opcode, (a, b, c), after) in samples {
+        writeln!(output, "Before: [{}]", registers(&before)).unwrap();
+        writeln!(output, "{} {} {} {}", number(opcode), a, b, c).unwrap();
+        writeln!(output, "After:  [{}]", registers(&after)).unwrap();
+        writeln!(output).unwrap();
+    }
+
+    output.push_str("\n\n");
+    let mut state = [0; 4];
+    for _ in 0..size.max(1) {
+        loop {
+            let opcode = *rng.pick(&opcodes);
+            let (a, b, c) = (rng.below(4), rng.below(4), rng.below(4));
+            let value = opcode.apply(&state, a, b);
+            if value < 1 << 16 {
+                state[c] = value;
+                writeln!(output, "{} {} {} {}", number(opcode), a, b, c).unwrap();
+                break;
+            }
+        }
+    }
+    output
+}
+
+/// `size` clay shapes, mostly open-topped buckets, around the spring at x=500.
+///
+/// A short vein at y=1..2, left of everything else, sets the top of the scan, and all other
+/// clay starts at y=3 so water can never back up to the spring.
+pub fn day17(rng: &mut Rng, size: usize) -> String {
+    let spread = 10 + 3 * size as i64;
+    let depth = 10 + 4 * size as i64;
+    let mut lines = Vec::new();
+    let mut left_most = 500;
+    for _ in 0..size.max(1) {
+        let x = 500 + rng.range(-spread, spread);
+        let top = rng.range(3, depth);
+        left_most = left_most.min(x);
+        if rng.one_in(3) {
+            let length = rng.range(0, 8);
+            if rng.one_in(2) {
+                lines.push(format!("x={}, y={}..{}", x, top, top + length));
+            } else {
+                lines.push(format!("y={}, x={}..{}", top, x, x + length));
+            }
+        } else {
+            let width = rng.range(2, 12);
+            let bottom = top + rng.range(2, 10);
+            let right_top = rng.range(top, bottom - 1);
+            lines.push(format!("x={}, y={}..{}", x, top, bottom));
+            lines.push(format!("x={}, y={}..{}", x + width, right_top, bottom));
+            lines.push(format!("y={}, x={}..{}", bottom, x, x + width));
+        }
+    }
+    lines.push(format!("x={}, y=1..2", left_most - 2));
+    rng.shuffle(&mut lines);
+    join(&lines)
+}
+
+/// A `size` by `size` lumber collection area.
+pub fn day18(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(1);
+    let area = Grid::from_fn(side, side, |_| *rng.pick(&['.', '|', '#']));
+    join(&[area.render(|&acre| acre)])
+}
+
+/// The real program with new constants. Part one sums the divisors of a number up to about
+/// `171 * size + 200`, and does so as slowly as the real program.
+pub fn day19(rng: &mut Rng, size: usize) -> String {
+    let square = rng.range(1, 2);
+    let factor = rng.range(1, size.max(1) as i64);
+    let multiplier = rng.range(1, 8);
+    let offset = rng.range(1, 20);
+    let scale = rng.range(1, 20);
+    format!(
+        "#ip 4
+addi 4 16 4
+seti 1 9 5
+seti 1 5 2
+mulr 5 2 1
+eqrr 1 3 1
+addr 1 4 4
+addi 4 1 4
+addr 5 0 0
+addi 2 1 2
+gtrr 2 3 1
+addr 4 1 4
+seti 2 6 4
+addi 5 1 5
+gtrr 5 3 1
+addr 1 4 4
+seti 1 2 4
+mulr 4 4 4
+addi 3 {} 3
+mulr 3 3 3
+mulr 4 3 3
+muli 3 {} 3
+addi 1 {} 1
+mulr 1 4 1
+addi 1 {} 1
+addr 3 1 3
+addr 4 0 4
+seti 0 2 4
+setr 4 8 1
+mulr 1 4 1
+addr 4 1 1
+mulr 4 1 1
+muli 1 {} 1
+mulr 1 4 1
+addr 3 1 3
+seti 0 0 0
+seti 0 2 4
+",
+        square, factor, multiplier, offset, scale
+    )
+}
+
+/// A route of about `size` steps, with branches up to eight deep and some detours that
+/// return to where they started.
+pub fn day20(rng: &mut Rng, size: usize) -> String {
+    fn route(rng: &mut Rng, budget: &mut usize, depth: usize, output: &mut String) {
+        while *budget > 0 {
+            if depth < 8 && rng.one_in(6) {
+                output.push('(');
+                for branch in 0..2 + rng.below(2) {
+                    if branch > 0 {
+                        output.push('|');
+                    }
+                    output.push(*rng.pick(&['N', 'E', 'S', 'W']));
+                    *budget = budget.saturating_sub(1);
+                    route(rng, budget, depth + 1, output);
+                }
+                if rng.one_in(3) {
+                    output.push('|');
+                }
+                output.push(')');
+            } else {
+                output.push(*rng.pick(&['N', 'E', 'S', 'W']));
+                *budget -= 1;
+            }
+            if depth > 0 && rng.one_in(4) {
+                break;
+            }
+        }
+    }
+
+    let mut output = String::from("^");
+    route(rng, &mut size.max(1), 0, &mut output);
+    output.push('$');
+    join(&[output])
+}
+
+/// The real program with a new 24-bit seed for its hash; `size` is ignored.
+pub fn day21(rng: &mut Rng, _size: usize) -> String {
+    format!(
+        "#ip 4
+seti 123 0 5
+bani 5 456 5
+eqri 5 72 5
+addr 5 4 4
+seti 0 0 4
+seti 0 9 5
+bori 5 65536 3
+seti {} 0 5
+bani 3 255 2
+addr 5 2 5
+bani 5 16777215 5
+muli 5 65899 5
+bani 5 16777215 5
+gtir 256 3 2
+addr 2 4 4
+addi 4 1 4
+seti 27 4 4
+seti 0 4 2
+addi 2 1 1
+muli 1 256 1
+gtrr 1 3 1
+addr 1 4 4
+addi 4 1 4
+seti 25 9 4
+addi 2 1 2
+seti 17 9 4
+setr 2 8 3
+seti 7 9 4
+eqrr 5 0 2
+addr 2 4 4
+seti 5 5 4
+",
+        rng.below(1 << 24)
+    )
+}
+
+/// A target up to `size + 5` across and `10 * size + 20` down, in a cave deep enough to
+/// walk around it.
+///
+/// The target's erosion level comes from the depth alone, so the depth is
+/// picked to make it rocky, as the puzzle's targets are.
+pub fn day22(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1) as i64;
+    let x = rng.range(1, size + 5);
+    let y = rng.range(x, 10 * size + 20);
+    let mut depth = 2 * y + rng.range(50, 150);
+    while depth % 20183 % 3 != 0 {
+        depth += 1;
+    }
+    format!("depth: {}\ntarget: {},{}\n", depth, x, y)
+}
+
+/// `size` nanobots with large, heavily overlapping ranges.
+pub fn day23(rng: &mut Rng, size: usize) -> String {
+    let lines: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let (x, y, z) = (
+                rng.range(0, 100_000_000),
+                rng.range(0, 100_000_000),
+                rng.range(0, 100_000_000),
+            );
+            format!(
+                "pos=<{},{},{}>, r={}",
+                x,
+                y,
+                z,
+                rng.range(40_000_000, 100_000_000)
+            )
+        })
+        .collect();
+    join(&lines)
+}
+
+/// `size` groups in each army, with distinct initiatives.
+pub fn day24(rng: &mut Rng, size: usize) -> String {
+    const ATTACKS: [&str; 5] = ["radiation", "cold", "fire", "bludgeoning", "slashing"];
+    let groups = size.max(1);
+    let mut initiatives: Vec<usize> = (1..=groups * 2).collect();
+    rng.shuffle(&mut initiatives);
+
+    let mut group = |rng: &mut Rng| {
+        let mut attacks = ATTACKS.to_vec();
+        rng.shuffle(&mut attacks);
+        let weak = rng.below(3);
+        let immune = rng.below(2);
+        let mut modifiers = Vec::new();
+        if weak > 0 {
+            modifiers.push(format!("weak to {}", attacks[..weak].join(", ")));
+        }
+        if immune > 0 {
+            modifiers.push(format!(
+                "immune to {}",
+                attacks[weak..weak + immune].join(", ")
+            ));
+        }
+        rng.shuffle(&mut modifiers);
+        let modifiers = if modifiers.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", modifiers.join("; "))
+        };
+        format!(
+            "{} units each with {} hit points{} with an attack that does {} {} damage at initiative {}",
+            rng.range(1, 5000),
+            rng.range(1, 10000),
+            modifiers,
+            rng.range(1, 100),
+            rng.pick(&ATTACKS),
+            initiatives.pop().unwrap()
+        )
+    };
+
+    let mut lines = vec!["Immune System:".to_owned()];
+    lines.extend((0..groups).map(|_| group(rng)));
+    lines.push(String::new());
+    lines.push("Infection:".to_owned());
+    lines.extend((0..groups).map(|_| group(rng)));
+    join(&lines)
+}
+
+/// `size` points in four dimensions, each coordinate between -8 and 8.
+pub fn day25(rng: &mut Rng, size: usize) -> String {
+    let lines: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let point: Vec<String> = (0..4).map(|_| rng.range(-8, 8).to_string()).collect();
+            point.join(",")
+        })
+        .collect();
+    join(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day23::{self, Coord};
+    use crate::gen::*;
+    use crate::registry::{Answer, Registry};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn deterministic() {
+        for day in 1..=25 {
+            assert_eq!(generate(day, 7, 5), generate(day, 7, 5));
+        }
+        assert_ne!(generate(3, 7, 5), generate(3, 8, 5));
+        assert_eq!(generate(26, 7, 5), None);
+    }
+
+    #[test]
+    fn part1_solves() {
+        let registry = Registry::new();
+        for day in 1..=25 {
+            for seed in 0..3 {
+                let input = generate(day, seed, 3).unwrap();
+                if let Err(error) = registry.solve(day, 1, None, &input) {
+                    panic!("day{} seed {}: {}\n{}", day, seed, error, input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn part2_solves_for_any_seed() {
+        let registry = Registry::new();
+        for &(day, size) in &[
+            (12, 1),
+            (12, 20),
+            (16, 1),
+            (16, 30),
+            (16, 100),
+            (22, 1),
+            (22, 3),
+        ] {
+            for seed in 0..8 {
+                let input = generate(day, seed, size).unwrap();
+                if let Err(error) = registry.solve(day, 2, None, &input) {
+                    panic!("day{} seed {} size {}: {}", day, seed, size, error);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn day23_witness_agrees() {
+        let registry = Registry::new();
+        for seed in 0..8 {
+            let input = generate(23, seed, 50).unwrap();
+            let answer = registry.solve(23, 2, None, &input).unwrap();
+            let map = day23::input_generator(&input).unwrap();
+            let (distance, spot) = day23::solve_part2_with_witness(&map).unwrap();
+            assert_eq!(answer, Answer::try_from(distance).unwrap(), "seed {}", seed);
+            let in_range = map
+                .iter()
+                .filter(|&(coord, &radius)| coord.manhattan(&spot.point) <= radius)
+                .count();
+            assert_eq!(spot.in_range, in_range, "seed {}", seed);
+            assert_eq!(
+                spot.distance,
+                spot.point.manhattan(&Coord::ORIGIN),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn part2_solves() {
+        let registry = Registry::new();
+        for &(day, size) in &[(2, 50), (3, 50), (7, 26), (10, 3), (16, 300), (20, 200)] {
+            let input = generate(day, 1, size).unwrap();
+            assert!(registry.solve(day, 2, None, &input).is_ok(), "day{}", day);
+        }
+        match registry.solve(2, 2, None, &generate(2, 1, 50).unwrap()) {
+            Ok(Answer::Text(common)) => assert_eq!(common.len(), 25),
+            answer => panic!("unexpected day2 answer {:?}", answer),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Points on a lattice of any dimension, and what the puzzles keep asking of them: Manhattan
+//! and Chebyshev distances, bounding boxes, neighbours and reading order.
+//!
+//! Reading order generalises "top to bottom, then left to right": points compare by their last
+//! coordinate first, so in 2D by `y` and then by `x`.
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Sub};
+
+/// A point with `N` coordinates. Points are ordered in reading order, so sorting them is the
+/// same as sorting by `reading_order`.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Point<const N: usize>(pub [isize; N]);
+
+pub type Point2 = Point<2>;
+pub type Point3 = Point<3>;
+pub type Point4 = Point<4>;
+
+impl<const N: usize> Point<N> {
+    pub const ORIGIN: Self = Self([0; N]);
+
+    pub const fn new(coordinates: [isize; N]) -> Self {
+        Self(coordinates)
+    }
+
+    /// The sum of the distances along each axis.
+    pub fn manhattan(&self, other: &Self) -> usize {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .map(|(a, b)| (a - b).unsigned_abs())
+            .sum()
+    }
+
+    /// The largest distance along any one axis.
+    pub fn chebyshev(&self, other: &Self) -> usize {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .map(|(a, b)| (a - b).unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The `2 * N` points a step along one axis away, in reading order.
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        (0..2 * N).map(move |index| {
+            let mut neighbour = self;
+            if index < N {
+                neighbour.0[N - 1 - index] -= 1;
+            } else {
+                neighbour.0[index - N] += 1;
+            }
+            neighbour
+        })
+    }
+
+    /// The `3^N - 1` points touching this one, diagonals included, in reading order.
+    pub fn neighbours_all(self) -> impl Iterator<Item = Self> {
+        let count = 3_usize.pow(N as u32);
+        (0..count)
+            .filter(move |&index| index != count / 2)
+            .map(move |mut index| {
+                let mut neighbour = self;
+                for coordinate in neighbour.0.iter_mut() {
+                    *coordinate += (index % 3) as isize - 1;
+                    index /= 3;
+                }
+                neighbour
+            })
+    }
+
+    pub fn reading_order(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl Point<2> {
+    pub const fn x(&self) -> isize {
+        self.0[0]
+    }
+
+    pub const fn y(&self) -> isize {
+        self.0[1]
+    }
+}
+
+impl<const N: usize> Ord for Point<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.reading_order(other)
+    }
+}
+
+impl<const N: usize> PartialOrd for Point<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a += b;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a -= b;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Mul<isize> for Point<N> {
+    type Output = Self;
+
+    fn mul(mut self, factor: isize) -> Self {
+        for a in self.0.iter_mut() {
+            *a *= factor;
+        }
+        self
+    }
+}
+
+/// A point serializes as the list of its coordinates.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Point<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+/// The box from `min` to `max`, both included.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Bounds<const N: usize> {
+    pub min: Point<N>,
+    pub max: Point<N>,
+}
+
+impl<const N: usize> Bounds<N> {
+    pub const fn new(min: Point<N>, max: Point<N>) -> Self {
+        Self { min, max }
+    }
+
+    /// The smallest box holding every point, or `None` when there are no points.
+    pub fn of(points: impl IntoIterator<Item = Point<N>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |mut bounds, point| {
+            for axis in 0..N {
+                bounds.min.0[axis] = bounds.min.0[axis].min(point.0[axis]);
+                bounds.max.0[axis] = bounds.max.0[axis].max(point.0[axis]);
+            }
+            bounds
+        }))
+    }
+
+    pub fn contains(&self, point: &Point<N>) -> bool {
+        (0..N).all(|axis| self.min.0[axis] <= point.0[axis] && point.0[axis] <= self.max.0[axis])
+    }
+
+    /// Whether a point in the box lies on its surface.
+    pub fn on_edge(&self, point: &Point<N>) -> bool {
+        (0..N).any(|axis| point.0[axis] == self.min.0[axis] || point.0[axis] == self.max.0[axis])
+    }
+
+    /// How many points wide the box is along `axis`.
+    pub fn span(&self, axis: usize) -> usize {
+        (self.max.0[axis] - self.min.0[axis] + 1).max(0) as usize
+    }
+
+    /// Every point in the box, in reading order.
+    pub fn points(self) -> impl Iterator<Item = Point<N>> {
+        let count = (0..N).map(|axis| self.span(axis)).product();
+        (0..count).map(move |mut index| {
+            let mut point = self.min;
+            for axis in 0..N {
+                let span = self.span(axis);
+                point.0[axis] += (index % span) as isize;
+                index /= span;
+            }
+            point
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::*;
+
+    #[test]
+    fn distances() {
+        let (a, b) = (Point::new([1, -2, 3]), Point::new([-1, 2, 3]));
+        assert_eq!(a.manhattan(&b), 6);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!(Point4::ORIGIN.manhattan(&Point::new([1, 1, -1, 0])), 3);
+        assert_eq!(a + b, Point::new([0, 0, 6]));
+        assert_eq!((a - b) * 2, Point::new([4, -8, 0]));
+    }
+
+    #[test]
+    fn neighbours() {
+        let point = Point2::new([5, 5]);
+        let coordinates = |points: Vec<Point2>| points.iter().map(|p| p.0).collect::<Vec<_>>();
+        assert_eq!(
+            coordinates(point.neighbours().collect()),
+            vec![[5, 4], [4, 5], [6, 5], [5, 6]]
+        );
+        let all: Vec<_> = point.neighbours_all().collect();
+        assert_eq!(all.len(), 8);
+        assert_eq!(
+            coordinates(all[..4].to_vec()),
+            vec![[4, 4], [5, 4], [6, 4], [4, 5]]
+        );
+        assert_eq!(Point4::ORIGIN.neighbours().count(), 8);
+        assert_eq!(Point3::ORIGIN.neighbours_all().count(), 26);
+    }
+
+    #[test]
+    fn reading_order() {
+        let mut points = vec![
+            Point2::new([2, 0]),
+            Point2::new([0, 1]),
+            Point2::new([1, 0]),
+        ];
+        points.sort_by(Point::reading_order);
+        assert_eq!(
+            points,
+            vec![
+                Point2::new([1, 0]),
+                Point2::new([2, 0]),
+                Point2::new([0, 1])
+            ]
+        );
+        let mut sorted = points.clone();
+        sorted.reverse();
+        sorted.sort();
+        assert_eq!(sorted, points);
+        assert!(Point3::new([5, 5, 0]) < Point3::new([0, 0, 1]));
+    }
+
+    #[test]
+    fn bounds() {
+        let points = vec![
+            Point2::new([3, -1]),
+            Point2::new([1, 2]),
+            Point2::new([2, 0]),
+        ];
+        let bounds = Bounds::of(points).unwrap();
+        assert_eq!(bounds, Bounds::new(Point::new([1, -1]), Point::new([3, 2])));
+        assert_eq!((bounds.span(0), bounds.span(1)), (3, 4));
+        assert!(bounds.contains(&Point::new([2, 1])));
+        assert!(!bounds.contains(&Point::new([0, 1])));
+        assert!(bounds.on_edge(&Point::new([2, 2])));
+        assert!(!bounds.on_edge(&Point::new([2, 1])));
+        let inside: Vec<_> = bounds.points().collect();
+        assert_eq!(inside.len(), 12);
+        assert_eq!(inside[1], Point::new([2, -1]));
+        assert!(inside
+            .windows(2)
+            .all(|w| w[0].reading_order(&w[1]) == Ordering::Less));
+        assert_eq!(Bounds::<3>::of(vec![]), None);
+    }
+}
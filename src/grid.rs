@@ -0,0 +1,245 @@
+//! A dense, row-major board for the days whose puzzles are drawn on a rectangle.
+//!
+//! Positions are `(x, y)` with `(0, 0)` in the top left, and everything that walks the
+//! board does so in reading order: top to bottom, then left to right. Neighbours come from
+//! `geometry`, so the grid only has to say which of them lie on the board.
+use crate::error::{lines, ParseError};
+use crate::geometry::Point2;
+use std::cmp::Ordering;
+use std::ops::{Index, IndexMut};
+
+pub type Position = (usize, usize);
+
+impl From<Position> for Point2 {
+    fn from((x, y): Position) -> Self {
+        Self::new([x as isize, y as isize])
+    }
+}
+
+/// Compares two positions in reading order, for `sort_by` and friends.
+pub fn reading_order(a: &Position, b: &Position) -> Ordering {
+    Point2::from(*a).reading_order(&Point2::from(*b))
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid by calling `cell` for every position in reading order.
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Position) -> T) -> Self {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(cell((x, y)));
+            }
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses one cell per character. Every line must be as long as the first, and `cell`
+    /// returning `None` is reported as `reason` at that character.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        reason: &str,
+        mut cell: impl FnMut(Position, char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for (y, line) in lines(day, input).enumerate() {
+            let mut x = 0;
+            for (offset, c) in line.text.char_indices() {
+                let value =
+                    cell((x, y), c).ok_or_else(|| line.error(&line.text[offset..], reason))?;
+                cells.push(value);
+                x += 1;
+            }
+            if *width.get_or_insert(x) != x {
+                return Err(line.error(line.text, "line is not as wide as the first"));
+            }
+            height += 1;
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    const fn offset(&self, (x, y): Position) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.offset(position).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.offset(position)
+            .map(move |offset| &mut self.cells[offset])
+    }
+
+    /// Every position, in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |offset| (offset % width, offset / width))
+    }
+
+    /// Every cell alongside its position, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Where a point lies on the grid, if it does.
+    pub const fn position(&self, point: Point2) -> Option<Position> {
+        on_grid(point, self.width, self.height)
+    }
+
+    /// The up to four orthogonal neighbours of a position, in reading order.
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> {
+        let (width, height) = (self.width, self.height);
+        Point2::from(position)
+            .neighbours()
+            .filter_map(move |point| on_grid(point, width, height))
+    }
+
+    /// The up to eight neighbours of a position, diagonals included, in reading order.
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> {
+        let (width, height) = (self.width, self.height);
+        Point2::from(position)
+            .neighbours_all()
+            .filter_map(move |point| on_grid(point, width, height))
+    }
+
+    /// Draws the grid one character per cell, with a newline after every row but the last.
+    pub fn render(&self, mut cell: impl FnMut(&T) -> char) -> String {
+        let mut output = String::with_capacity(self.cells.len() + self.height);
+        for (index, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            if index > 0 {
+                output.push('\n');
+            }
+            output.extend(row.iter().map(&mut cell));
+        }
+        output
+    }
+}
+
+const fn on_grid(point: Point2, width: usize, height: usize) -> Option<Position> {
+    let (x, y) = (point.x(), point.y());
+    if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+        Some((x as usize, y as usize))
+    } else {
+        None
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside a {}x{} grid",
+                position, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside a {}x{} grid",
+                position, width, height
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::*;
+
+    #[test]
+    fn parse_and_render() {
+        let input = "#.#\n.G.";
+        let grid = Grid::parse(15, input, "unknown tile", |_, c| Some(c)).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 1)], 'G');
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.render(|&c| c), input);
+
+        let error = Grid::parse(15, "#.\n#x", "unknown tile", |_, c| match c {
+            '#' | '.' => Some(c),
+            _ => None,
+        })
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert!(Grid::parse(15, "##\n#", "", |_, c| Some(c)).is_err());
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbours4((1, 1)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1), (2, 1), (1, 2)]
+        );
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbours8((2, 2)).collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn reading_order() {
+        let grid = Grid::from_fn(2, 2, |(x, y)| x + 10 * y);
+        assert_eq!(
+            grid.iter().collect::<Vec<_>>(),
+            vec![((0, 0), &0), ((1, 0), &1), ((0, 1), &10), ((1, 1), &11)]
+        );
+    }
+}
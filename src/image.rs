@@ -0,0 +1,277 @@
+//! Pictures of the grid puzzles as PPM and PNG files, with nothing to install.
+//!
+//! A frame of text becomes an image by painting each character as a `cell` by `cell` square
+//! in its palette colour. The PNG encoder stores its pixels without compressing them, which
+//! keeps it short and exact; the files are about as large as the matching PPM.
+use hashbrown::HashMap;
+
+pub type Rgb = [u8; 3];
+
+/// The colour to paint each character, and the colour for any character not listed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Palette {
+    pub colours: HashMap<char, Rgb>,
+    pub background: Rgb,
+}
+
+impl Palette {
+    pub fn new(background: Rgb, colours: &[(char, Rgb)]) -> Self {
+        Self {
+            colours: colours.iter().cloned().collect(),
+            background,
+        }
+    }
+
+    /// The colours that show off `day`'s frames: the tiles on a dark background.
+    pub fn for_day(day: u8) -> Self {
+        let black = [0, 0, 0];
+        match day {
+            10 => Self::new(black, &[('#', [255, 255, 255])]),
+            13 => Self::new(
+                black,
+                &[
+                    ('|', [90, 90, 90]),
+                    ('-', [90, 90, 90]),
+                    ('/', [90, 90, 90]),
+                    ('\\', [90, 90, 90]),
+                    ('+', [140, 140, 140]),
+                    ('^', [255, 220, 0]),
+                    ('>', [255, 220, 0]),
+                    ('v', [255, 220, 0]),
+                    ('<', [255, 220, 0]),
+                    ('X', [230, 30, 30]),
+                ],
+            ),
+            15 => Self::new(
+                black,
+                &[
+                    ('#', [90, 90, 90]),
+                    ('.', [30, 30, 30]),
+                    ('G', [220, 40, 40]),
+                    ('E', [40, 200, 60]),
+                ],
+            ),
+            17 => Self::new(
+                [40, 30, 20],
+                &[
+                    ('#', [150, 100, 50]),
+                    ('|', [120, 200, 255]),
+                    ('~', [30, 80, 220]),
+                    ('+', [255, 255, 255]),
+                ],
+            ),
+            18 => Self::new(
+                [110, 90, 60],
+                &[('|', [30, 140, 40]), ('#', [200, 160, 40])],
+            ),
+            _ => Self::new(black, &[('#', [255, 255, 255])]),
+        }
+    }
+
+    /// Changes colours from a list like `#=ff0000,.=202020`; `bg=` sets the background.
+    pub fn apply(&mut self, spec: &str) -> Result<(), String> {
+        for entry in spec.split(',').filter(|entry| !entry.is_empty()) {
+            let invalid = || format!("invalid palette entry `{}`", entry);
+            let (key, colour) = entry.rsplit_once('=').ok_or_else(invalid)?;
+            let colour = parse_colour(colour).ok_or_else(invalid)?;
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                _ if key == "bg" => self.background = colour,
+                (Some(c), None) => {
+                    self.colours.insert(c, colour);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(())
+    }
+
+    fn colour(&self, c: char) -> Rgb {
+        *self.colours.get(&c).unwrap_or(&self.background)
+    }
+}
+
+/// Parses six hex digits, with or without a leading `#`.
+fn parse_colour(hex: &str) -> Option<Rgb> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok();
+    Some([channel(0)?, channel(1)?, channel(2)?])
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Paints each character of `text` as a `cell` pixel square. Lines shorter than the
+    /// longest are padded with the background.
+    pub fn from_text(text: &str, cell: usize, palette: &Palette) -> Self {
+        let lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+        let columns = lines.iter().map(Vec::len).max().unwrap_or(0);
+        let (width, height) = (columns * cell, lines.len() * cell);
+        let mut pixels = Vec::with_capacity(width * height);
+        for line in &lines {
+            let row: Vec<Rgb> = (0..columns)
+                .flat_map(|x| {
+                    let colour = line
+                        .get(x)
+                        .map_or(palette.background, |&c| palette.colour(c));
+                    std::iter::repeat_n(colour, cell)
+                })
+                .collect();
+            for _ in 0..cell {
+                pixels.extend_from_slice(&row);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// A binary PPM: a short text header followed by the raw pixels.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.extend(self.pixels.iter().flatten());
+        ppm
+    }
+
+    /// An 8-bit RGB PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        // every row starts with filter type 0, leaving its bytes as they are
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// A zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        zlib.push(u8::from(last));
+        let length = block.len() as u16;
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::image::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn painting() {
+        let palette = Palette::new([0, 0, 0], &[('#', [255, 0, 0])]);
+        let image = Image::from_text("#.\n#", 2, &palette);
+        assert_eq!((image.width(), image.height()), (4, 4));
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+        assert_eq!(&ppm[11..17], &[255, 0, 0, 255, 0, 0]);
+        assert_eq!(&ppm[17..23], &[0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn png() {
+        let image = Image::from_text("#", 1, &Palette::for_day(10));
+        let png = image.to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x01\0\0\0\x01\x08\x02"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+        // the single row: filter byte and one white pixel, stored in one final block
+        let idat = &png[33 + 8..];
+        assert_eq!(&idat[..2], &[0x78, 0x01]);
+        assert_eq!(&idat[2..7], &[1, 4, 0, 0xfb, 0xff]);
+        assert_eq!(&idat[7..11], &[0, 255, 255, 255]);
+        assert_eq!(zlib_stored(&[]).len(), 2 + 5 + 4);
+    }
+
+    #[test]
+    fn palettes() {
+        let mut palette = Palette::for_day(18);
+        palette.apply("|=00ff00,bg=#000000").unwrap();
+        assert_eq!(palette.colour('|'), [0, 255, 0]);
+        assert_eq!(palette.colour('?'), [0, 0, 0]);
+        palette.apply("==123456").unwrap();
+        assert_eq!(palette.colour('='), [0x12, 0x34, 0x56]);
+        assert!(palette.apply("|=0f0").is_err());
+        assert!(palette.apply("ab=000000").is_err());
+        assert!(palette.apply("|").is_err());
+    }
+}
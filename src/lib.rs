@@ -1,3 +1,15 @@
+//! Solutions to Advent of Code 2018, usable as a library as well as through cargo-aoc.
+//!
+//! Each puzzle is its own module, `day1` to `day25`. A day's `input_generator` parses the
+//! puzzle text into the types its solvers take, and `solve_part1` and `solve_part2` answer
+//! the two parts; the modules' own docs say what each returns. The engines behind the bigger
+//! puzzles are public too, such as day15's `Battle` and day20's `doors`, and the ElfCode
+//! device of days 16, 19 and 21 is in `elfcode`.
+//!
+//! Parsing fails with an `error::ParseError` pointing at the offending line and column.
+//! `registry` puts every solver behind one signature, `simulation` steps the days that are
+//! simulations, `validate` checks inputs that parse but can't be solved, and `witness`
+//! returns answers along with what they were worked out from.
 #![warn(
     clippy::all,
     clippy::nursery,
@@ -6,34 +18,52 @@
     clippy::perf,
     clippy::correctness
 )]
-#![feature(vec_remove_item)]
+//COMPAT#![feature(vec_remove_item)]
 
 use aoc_runner_derive::aoc_lib;
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+pub mod answers;
+pub mod bench;
+pub mod budget;
+mod cycle;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+#[cfg(feature = "serde")]
+pub mod dump;
+pub mod elfcode;
+pub mod error;
+pub mod gen;
+pub mod geometry;
+pub mod grid;
+pub mod image;
+mod parallel;
+pub mod registry;
+pub mod replay;
+pub mod simulation;
+pub mod validate;
+pub mod witness;
 
 aoc_lib! { year = 2018 }
@@ -0,0 +1,81 @@
+//! Running independent trials at once when built with the `parallel` feature, and one after
+//! another without it. Either way the results come back in the order the trials were given,
+//! so a solver gets the same answer, ties and all, however it was built.
+use crate::budget::{Meter, Unsolved};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// `f` applied to every item, in order.
+#[cfg(feature = "parallel")]
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    items.par_iter().map(f).collect()
+}
+
+/// `f` applied to every item, in order.
+#[cfg(not(feature = "parallel"))]
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    items.iter().map(f).collect()
+}
+
+/// How many trials `find_first` runs at once.
+#[cfg(feature = "parallel")]
+fn batch_size() -> usize {
+    rayon::current_num_threads().max(1)
+}
+
+#[cfg(not(feature = "parallel"))]
+const fn batch_size() -> usize {
+    1
+}
+
+/// The result for the smallest `n` from `start` up for which `f` finds one. Trials run a
+/// batch at a time, but `meter` ticks once per trial in order before its result is looked
+/// at, so running out of budget happens on the same trial as it would serially.
+pub fn find_first<R: Send>(
+    start: usize,
+    meter: &mut Meter,
+    f: impl Fn(usize) -> Option<R> + Sync + Send,
+) -> Result<R, Unsolved> {
+    let batch = batch_size();
+    let mut first = start;
+    loop {
+        let trials: Vec<usize> = (first..first + batch).collect();
+        for result in map(&trials, |&n| f(n)) {
+            meter.tick()?;
+            if let Some(found) = result {
+                return Ok(found);
+            }
+        }
+        first += batch;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::budget::Budget;
+    use crate::parallel::*;
+
+    #[test]
+    fn in_order() {
+        let items: Vec<u64> = (0..100).collect();
+        assert_eq!(
+            map(&items, |n| n * n),
+            (0..100).map(|n| n * n).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn first_found() {
+        let mut meter = Budget::unlimited().start();
+        let found = find_first(3, &mut meter, |n| Some(n).filter(|n| n % 7 == 0 && *n > 30));
+        assert_eq!(found, Ok(35));
+
+        let mut meter = Budget::steps(10).start();
+        assert!(find_first(0, &mut meter, |n| Some(n).filter(|&n| n == 10)).is_err());
+        let mut meter = Budget::steps(11).start();
+        assert_eq!(
+            find_first(0, &mut meter, |n| Some(n).filter(|&n| n == 10)),
+            Ok(10)
+        );
+    }
+}
@@ -0,0 +1,440 @@
+//! Every day's solvers behind one signature, so they can be listed and called without knowing
+//! what each generator produces or what type each part returns.
+use crate::budget::Budget;
+use crate::*;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::BufRead;
+
+/// A solver's result, whatever type the day itself returns.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{}", number),
+            Self::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// A number a solver returned that doesn't fit an `Answer`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Unrepresentable(pub String);
+
+impl fmt::Display for Unrepresentable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "answer {} doesn't fit an i64", self.0)
+    }
+}
+
+impl Error for Unrepresentable {}
+
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(number: $t) -> Self {
+                    Self::Number(i64::from(number))
+                }
+            }
+        )*
+    };
+}
+
+/// For the types that can hold numbers beyond an `i64`.
+macro_rules! answer_try_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl TryFrom<$t> for Answer {
+                type Error = Unrepresentable;
+
+                fn try_from(number: $t) -> Result<Self, Self::Error> {
+                    i64::try_from(number)
+                        .map(Self::Number)
+                        .map_err(|_| Unrepresentable(number.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+answer_from_number!(i32, u32, i64);
+answer_try_from_number!(isize, usize, u64);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+/// The solving left to do once the input has been parsed.
+pub type Solution<'a> = Box<dyn FnOnce() -> Result<Answer, Box<dyn Error>> + 'a>;
+
+/// Parses the input and hands back the solving still to do, so that the two can be timed
+/// apart. The days that parse inside their solvers leave all the work to the `Solution`.
+pub type Solver = Box<dyn Fn(&str) -> Result<Solution<'_>, Box<dyn Error>> + Send + Sync>;
+
+/// Like a `Solver`, for the days that can parse their input a line at a time as it's read.
+pub type Reader =
+    Box<dyn Fn(&mut dyn BufRead) -> Result<Solution<'static>, Box<dyn Error>> + Send + Sync>;
+
+/// (day, part, variant)
+pub type SolverKey = (u8, u8, Option<&'static str>);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownSolver {
+    pub day: u8,
+    pub part: u8,
+    pub variant: Option<String>,
+}
+
+impl fmt::Display for UnknownSolver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no solver for day{} part{}", self.day, self.part)?;
+        if let Some(variant) = &self.variant {
+            write!(f, " ({})", variant)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for UnknownSolver {}
+
+/// Registers a solver for (day, part, variant), either as `generator => solver` or, for the
+/// days that parse inside their solvers, as `|input| expression`. Solvers that can fail are
+/// registered as `generator => solver?`, and those that can run out of budget as
+/// `generator => solver, budget`.
+/// Like cargo-aoc, trailing newlines are stripped before the generator sees the input.
+macro_rules! register {
+    ($solvers:ident, $key:expr, $generator:path => $solver:path) => {
+        $solvers.insert(
+            $key,
+            Box::new(|input: &str| {
+                let generated = $generator(input.trim_end_matches('\n'))?;
+                Ok(Box::new(move || Ok(Answer::try_from($solver(&generated))?)) as Solution)
+            }),
+        );
+    };
+    ($solvers:ident, $key:expr, $generator:path => $($solver:ident)::+?) => {
+        $solvers.insert(
+            $key,
+            Box::new(|input: &str| {
+                let generated = $generator(input.trim_end_matches('\n'))?;
+                Ok(Box::new(move || Ok(Answer::try_from($($solver)::+(&generated)?)?)) as Solution)
+            }),
+        );
+    };
+    ($solvers:ident, $key:expr, $generator:path => $solver:path, $budget:expr) => {
+        $solvers.insert(
+            $key,
+            Box::new(move |input: &str| {
+                let generated = $generator(input.trim_end_matches('\n'))?;
+                Ok(
+                    Box::new(move || Ok(Answer::try_from($solver(&generated, $budget)?)?))
+                        as Solution,
+                )
+            }),
+        );
+    };
+    ($solvers:ident, $key:expr, |$input:ident| $body:expr) => {
+        $solvers.insert(
+            $key,
+            Box::new(move |$input: &str| {
+                let $input = $input.trim_end_matches('\n');
+                Ok(Box::new(move || Ok(Answer::try_from($body)?)) as Solution)
+            }),
+        );
+    };
+}
+
+/// Registers a `Reader` for (day, part, variant), as `reader => solver`, `reader => solver?`
+/// for a solver that can fail or, with a budget, as `reader => solver, budget`.
+macro_rules! register_reader {
+    ($readers:ident, $key:expr, $reader:path => $solver:path) => {
+        $readers.insert(
+            $key,
+            Box::new(|input: &mut dyn BufRead| {
+                let generated = $reader(input)?;
+                Ok(Box::new(move || Ok(Answer::try_from($solver(&generated))?)) as Solution)
+            }),
+        );
+    };
+    ($readers:ident, $key:expr, $reader:path => $($solver:ident)::+?) => {
+        $readers.insert(
+            $key,
+            Box::new(|input: &mut dyn BufRead| {
+                let generated = $reader(input)?;
+                Ok(Box::new(move || Ok(Answer::try_from($($solver)::+(&generated)?)?)) as Solution)
+            }),
+        );
+    };
+    ($readers:ident, $key:expr, $reader:path => $solver:path, $budget:expr) => {
+        $readers.insert(
+            $key,
+            Box::new(move |input: &mut dyn BufRead| {
+                let generated = $reader(input)?;
+                Ok(
+                    Box::new(move || Ok(Answer::try_from($solver(&generated, $budget)?)?))
+                        as Solution,
+                )
+            }),
+        );
+    };
+}
+
+pub struct Registry {
+    solvers: BTreeMap<SolverKey, Solver>,
+    readers: BTreeMap<SolverKey, Reader>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::with_budget(Budget::default())
+    }
+
+    /// Registers every solver, limiting the open-ended searches to `budget`.
+    pub fn with_budget(budget: Budget) -> Self {
+        let mut solvers: BTreeMap<SolverKey, Solver> = BTreeMap::new();
+        register!(solvers, (1, 1, None), day1::input_generator => day1::solve_part1);
+        register!(solvers, (1, 2, None), day1::input_generator => day1::solve_part2?);
+        register!(solvers, (1, 2, Some("walk")), day1::input_generator => day1::solve_part2_within, budget);
+        register!(solvers, (2, 1, None), |i| day2::solve_part1(i)?);
+        register!(solvers, (2, 2, None), |i| day2::solve_part2(i)?);
+        register!(solvers, (3, 1, None), day3::input_generator => day3::solve_part1);
+        register!(solvers, (3, 2, None), day3::input_generator => day3::solve_part2?);
+        register!(solvers, (4, 1, None), |i| day4::solve_part1(i)?);
+        register!(solvers, (4, 2, None), |i| day4::solve_part2(i)?);
+        register!(solvers, (5, 1, None), day5::input_generator => day5::solve_part1);
+        register!(solvers, (5, 2, None), day5::input_generator => day5::solve_part2);
+        register!(solvers, (6, 1, None), day6::input_generator => day6::solve_part1?);
+        register!(solvers, (6, 2, None), day6::input_generator => day6::solve_part2?);
+        register!(solvers, (7, 1, None), day7::input_generator => day7::solve_1);
+        register!(solvers, (7, 2, None), day7::input_generator => day7::solve_2);
+        register!(solvers, (8, 1, None), day8::input_generator => day8::solve_part1);
+        register!(solvers, (8, 2, None), day8::input_generator => day8::solve_part2);
+        register!(solvers, (8, 1, Some("fast")), day8::input_generator => day8::solve_part1_fast);
+        register!(solvers, (8, 2, Some("fast")), day8::input_generator => day8::solve_part2_fast);
+        register!(solvers, (9, 1, None), day9::input_generator => day9::solve_part1);
+        register!(solvers, (9, 2, None), day9::input_generator => day9::solve_part2);
+        register!(solvers, (10, 1, None), day10::input_generator => day10::solve_part1_within, budget);
+        register!(solvers, (10, 2, None), day10::input_generator => day10::solve_part2_within, budget);
+        register!(solvers, (11, 1, None), |i| day11::solve_part1(i)?);
+        register!(solvers, (11, 2, None), |i| day11::solve_part2(i)?);
+        register!(solvers, (12, 1, None), day12::input_generator => day12::solve_part1);
+        register!(solvers, (12, 2, None), day12::input_generator => day12::solve_part2_within, budget);
+        register!(solvers, (13, 1, None), day13::input_generator => day13::solve_part1);
+        register!(solvers, (13, 2, None), day13::input_generator => day13::solve_part2);
+        register!(solvers, (14, 1, None), day14::input_generator => day14::solve_part1);
+        register!(solvers, (14, 2, None), day14::input_generator => day14::solve_part2);
+        register!(solvers, (15, 1, None), day15::input_generator => day15::solve_part1);
+        register!(solvers, (15, 2, None), day15::input_generator => day15::solve_part2);
+        register!(solvers, (16, 1, None), day16::input_generator => day16::solve_part1);
+        register!(solvers, (16, 2, None), day16::input_generator => day16::solve_part2_within, budget);
+        register!(solvers, (17, 1, None), day17::input_generator => day17::solve_part1);
+        register!(solvers, (17, 2, None), day17::input_generator => day17::solve_part2);
+        register!(solvers, (18, 1, None), day18::input_generator => day18::solve_part1);
+        register!(solvers, (18, 2, None), day18::input_generator => day18::solve_part2_within, budget);
+        register!(solvers, (19, 1, None), day19::input_generator => day19::solve_part1);
+        register!(solvers, (19, 2, None), day19::input_generator => day19::solve_part2?);
+        register!(solvers, (20, 1, None), day20::input_generator => day20::solve_part1);
+        register!(solvers, (20, 2, None), day20::input_generator => day20::solve_part2);
+        register!(solvers, (21, 1, None), day21::input_generator => day21::solve_part1?);
+        register!(solvers, (21, 2, Some("slow")), day21::input_generator => day21::solve_part2_within, budget);
+        register!(solvers, (22, 1, None), day22::input_generator => day22::solve_part1);
+        register!(solvers, (22, 2, None), day22::input_generator => day22::solve_part2?);
+        register!(solvers, (23, 1, None), day23::input_generator => day23::solve_part1?);
+        register!(solvers, (23, 2, None), day23::input_generator => day23::solve_part2_within, budget);
+        register!(solvers, (24, 1, None), day24::input_generator => day24::solve_part1);
+        register!(solvers, (24, 2, None), day24::input_generator => day24::solve_part2_within, budget);
+        register!(solvers, (25, 1, None), day25::input_generator => day25::solve_part1);
+
+        let mut readers: BTreeMap<SolverKey, Reader> = BTreeMap::new();
+        readers.insert(
+            (1, 1, None),
+            Box::new(|input: &mut dyn BufRead| {
+                // nothing to keep but the running total
+                let sum = day1::read_changes(input).sum::<Result<i32, _>>()?;
+                Ok(Box::new(move || Ok(Answer::from(sum))) as Solution)
+            }),
+        );
+        register_reader!(readers, (1, 2, None), day1::read_input => day1::solve_part2?);
+        register_reader!(readers, (3, 1, None), day3::read_input => day3::solve_part1);
+        register_reader!(readers, (3, 2, None), day3::read_input => day3::solve_part2?);
+        register_reader!(readers, (23, 1, None), day23::read_input => day23::solve_part1?);
+        register_reader!(readers, (23, 2, None), day23::read_input => day23::solve_part2_within, budget);
+        register_reader!(readers, (25, 1, None), day25::read_input => day25::solve_part1);
+        Self { solvers, readers }
+    }
+
+    /// All registered (day, part, variant) keys, in day and part order.
+    pub fn keys(&self) -> impl Iterator<Item = &SolverKey> {
+        self.solvers.keys()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&SolverKey, &Solver)> {
+        self.solvers.iter()
+    }
+
+    /// Looks up a solver. Asking for no variant falls back to the only variant of a part,
+    /// as with day21's `slow` part two.
+    pub fn get(&self, day: u8, part: u8, variant: Option<&str>) -> Option<&Solver> {
+        let mut candidates = self
+            .solvers
+            .range((day, part, None)..)
+            .take_while(|((d, p, _), _)| *d == day && *p == part);
+        match variant {
+            Some(name) => candidates
+                .find(|((_, _, v), _)| *v == Some(name))
+                .map(|(_, solver)| solver),
+            None => {
+                let candidates: Vec<_> = candidates.collect();
+                match candidates.as_slice() {
+                    [(_, solver)] => Some(*solver),
+                    _ => candidates
+                        .into_iter()
+                        .find(|((_, _, v), _)| v.is_none())
+                        .map(|(_, solver)| solver),
+                }
+            }
+        }
+    }
+
+    pub fn solve(
+        &self,
+        day: u8,
+        part: u8,
+        variant: Option<&str>,
+        input: &str,
+    ) -> Result<Answer, Box<dyn Error>> {
+        let solver = self.get(day, part, variant).ok_or_else(|| UnknownSolver {
+            day,
+            part,
+            variant: variant.map(str::to_owned),
+        })?;
+        solver(input)?()
+    }
+
+    /// Like `solve`, reading the input from `input`. Days 1, 3, 23 and 25 parse it a line at
+    /// a time as it arrives; the rest read it all first.
+    pub fn solve_reader(
+        &self,
+        day: u8,
+        part: u8,
+        variant: Option<&str>,
+        input: &mut dyn BufRead,
+    ) -> Result<Answer, Box<dyn Error>> {
+        // the days with readers have no variants, so naming one falls through to `solve`
+        if let Some(reader) = self
+            .readers
+            .get(&(day, part, None))
+            .filter(|_| variant.is_none())
+        {
+            return reader(input)?();
+        }
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        self.solve(day, part, variant, &text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::*;
+
+    #[test]
+    fn every_day() {
+        let registry = Registry::new();
+        for day in 1..=25 {
+            assert!(registry.get(day, 1, None).is_some());
+            assert_eq!(registry.get(day, 2, None).is_some(), day != 25);
+        }
+        assert_eq!(registry.keys().count(), 52);
+    }
+
+    #[test]
+    fn variants() {
+        let registry = Registry::new();
+        let input = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2\n";
+        assert_eq!(
+            registry.solve(8, 2, None, input).unwrap(),
+            Answer::Number(66)
+        );
+        assert_eq!(
+            registry.solve(8, 2, Some("fast"), input).unwrap(),
+            Answer::Number(66)
+        );
+        assert!(registry.solve(8, 2, Some("slow"), input).is_err());
+        assert!(registry.solve(25, 2, None, input).is_err());
+        assert_eq!(
+            registry.solve(11, 1, None, "42").unwrap(),
+            Answer::Text("21,61".to_owned())
+        );
+    }
+
+    #[test]
+    fn answers() {
+        assert_eq!(Answer::try_from(7_usize), Ok(Answer::Number(7)));
+        assert_eq!(Answer::from(-7_i32), Answer::Number(-7));
+        assert_eq!(
+            Answer::try_from(u64::MAX).unwrap_err().to_string(),
+            "answer 18446744073709551615 doesn't fit an i64"
+        );
+    }
+
+    #[test]
+    fn budget() {
+        let registry = Registry::with_budget(Budget::steps(2));
+        assert_eq!(
+            registry
+                .solve(1, 2, Some("walk"), "+3\n+3\n+4\n-2\n-4")
+                .unwrap(),
+            Answer::Number(10)
+        );
+        assert!(registry
+            .solve(1, 2, Some("walk"), "+7\n+7\n-2\n-7\n-4")
+            .is_err());
+        // the default needs no budget, working the repeat out from the first pass
+        assert_eq!(
+            registry.solve(1, 2, None, "+7\n+7\n-2\n-7\n-4").unwrap(),
+            Answer::Number(14)
+        );
+    }
+
+    #[test]
+    fn readers() {
+        let registry = Registry::with_budget(Budget::steps(2));
+        let solve = |day, part, input: &str| {
+            registry
+                .solve_reader(day, part, None, &mut input.as_bytes())
+                .map_err(|error| error.to_string())
+        };
+        assert_eq!(solve(1, 1, "+1\n-2\n+3\n+1\n"), Ok(Answer::Number(3)));
+        assert_eq!(solve(1, 2, "+3\n+3\n+4\n-2\n-4"), Ok(Answer::Number(10)));
+        assert_eq!(solve(1, 2, "+7\n+7\n-2\n-7\n-4"), Ok(Answer::Number(14)));
+        assert!(solve(1, 2, "+1\n+1").is_err());
+        let claims = "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n";
+        assert_eq!(solve(3, 1, claims), Ok(Answer::Number(4)));
+        assert_eq!(solve(3, 2, claims), Ok(Answer::Number(3)));
+        assert_eq!(
+            solve(3, 1, "#1 @ 1,3: 4x4\n#2 @ 3,x: 4x4"),
+            Err("day3 line 2 column 1: line does not match the expected format in \"#2 @ 3,x: 4x4\"".to_owned())
+        );
+        assert_eq!(
+            solve(8, 2, "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2\n"),
+            Ok(Answer::Number(66))
+        );
+    }
+}
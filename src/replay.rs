@@ -0,0 +1,266 @@
+//! Replaying the grid simulations frame by frame, for watching them in a terminal or drawing
+//! them as pictures.
+//!
+//! Frame 0 is the parsed input and frame `n` is the state after `n` steps. A replay steps its
+//! simulation only as far as it has been asked to, keeping a copy every `CHECKPOINT` frames,
+//! so seeking back means stepping forward from the nearest copy rather than holding every
+//! frame in memory. Day17's scans are thousands of rows tall, which makes that matter.
+use crate::simulation::{Battle, Carts, Flow, Lumber, Simulation, Sky};
+use crate::*;
+use std::error::Error;
+use std::fmt::Display;
+
+const CHECKPOINT: usize = 64;
+
+/// A replay of any day, for callers that pick the day at runtime.
+pub trait Frames {
+    /// Moves to `frame`, or to the last frame if the simulation finishes before it, and
+    /// returns the frame it moved to.
+    fn seek(&mut self, frame: usize) -> usize;
+
+    fn frame(&self) -> usize;
+
+    /// The number of the last frame, once the replay has got that far.
+    fn last(&self) -> Option<usize>;
+
+    /// The current frame as plain text.
+    fn render(&self) -> String;
+
+    /// The current frame's grid alone, without any notes beside it, for drawing as a picture.
+    fn cells(&self) -> String;
+
+    /// The current frame with ANSI colours.
+    fn render_ansi(&self) -> String;
+}
+
+/// A seekable run of one simulation.
+///
+/// `palette` gives the SGR colour code for a character of the rendered frame, if it has one,
+/// and `cells` draws the grid alone, which by default is the whole rendered frame.
+#[derive(Clone)]
+pub struct Replay<S> {
+    checkpoints: Vec<S>,
+    current: S,
+    frame: usize,
+    palette: fn(char) -> Option<&'static str>,
+    cells: fn(&S) -> String,
+}
+
+impl<S: Simulation + Clone + Display> Replay<S> {
+    pub fn new(simulation: S, palette: fn(char) -> Option<&'static str>) -> Self {
+        Self {
+            checkpoints: vec![simulation.clone()],
+            current: simulation,
+            frame: 0,
+            palette,
+            cells: |simulation| simulation.to_string(),
+        }
+    }
+
+    pub fn with_cells(mut self, cells: fn(&S) -> String) -> Self {
+        self.cells = cells;
+        self
+    }
+
+    pub const fn simulation(&self) -> &S {
+        &self.current
+    }
+}
+
+impl<S: Simulation + Clone + Display> Frames for Replay<S> {
+    fn seek(&mut self, frame: usize) -> usize {
+        if frame < self.frame {
+            let checkpoint = (frame / CHECKPOINT).min(self.checkpoints.len() - 1);
+            self.current = self.checkpoints[checkpoint].clone();
+            self.frame = checkpoint * CHECKPOINT;
+        }
+        while self.frame < frame && !self.current.is_finished() {
+            self.current.step();
+            self.frame += 1;
+            if self.frame == self.checkpoints.len() * CHECKPOINT {
+                self.checkpoints.push(self.current.clone());
+            }
+        }
+        self.frame
+    }
+
+    fn frame(&self) -> usize {
+        self.frame
+    }
+
+    fn last(&self) -> Option<usize> {
+        Some(self.frame).filter(|_| self.current.is_finished())
+    }
+
+    fn render(&self) -> String {
+        self.current.to_string()
+    }
+
+    fn cells(&self) -> String {
+        (self.cells)(&self.current)
+    }
+
+    fn render_ansi(&self) -> String {
+        colourise(&self.render(), self.palette)
+    }
+}
+
+/// Wraps each run of characters with the same colour in one SGR sequence.
+fn colourise(text: &str, palette: fn(char) -> Option<&'static str>) -> String {
+    let mut coloured = String::with_capacity(text.len() * 2);
+    let mut current = None;
+    for c in text.chars() {
+        let colour = if c == '\n' { None } else { palette(c) };
+        if colour != current {
+            if current.is_some() {
+                coloured.push_str("\x1b[0m");
+            }
+            if let Some(code) = colour {
+                coloured.push_str(&format!("\x1b[{}m", code));
+            }
+            current = colour;
+        }
+        coloured.push(c);
+    }
+    if current.is_some() {
+        coloured.push_str("\x1b[0m");
+    }
+    coloured
+}
+
+const fn sky_palette(c: char) -> Option<&'static str> {
+    match c {
+        '#' => Some("1;37"),
+        _ => None,
+    }
+}
+
+const fn carts_palette(c: char) -> Option<&'static str> {
+    match c {
+        '^' | '>' | 'v' | '<' => Some("1;33"),
+        'X' => Some("1;31"),
+        _ => Some("90"),
+    }
+}
+
+const fn battle_palette(c: char) -> Option<&'static str> {
+    match c {
+        '#' => Some("90"),
+        'G' => Some("1;31"),
+        'E' => Some("1;32"),
+        _ => None,
+    }
+}
+
+const fn water_palette(c: char) -> Option<&'static str> {
+    match c {
+        '#' => Some("33"),
+        '|' => Some("36"),
+        '~' => Some("1;34"),
+        '+' => Some("1;37"),
+        _ => Some("90"),
+    }
+}
+
+const fn lumber_palette(c: char) -> Option<&'static str> {
+    match c {
+        '|' => Some("32"),
+        '#' => Some("33"),
+        _ => Some("90"),
+    }
+}
+
+/// Parses `input` for `day` and starts a replay of its simulation.
+///
+/// Day10's sky is shrunk to fit 100 characters until the message appears, day13's carts are removed as they crash, day15's elves fight with their usual attack of
+/// 3, and day18's acres never finish.
+pub fn open(day: u8, input: &str) -> Result<Box<dyn Frames>, Box<dyn Error>> {
+    let input = input.trim_end_matches('\n');
+    let frames: Box<dyn Frames> = match day {
+        10 => {
+            let points = day10::input_generator(input)?;
+            Box::new(Replay::new(Sky::new(&points), sky_palette))
+        }
+        13 => {
+            let (carts, tiles) = day13::input_generator(input)?;
+            Box::new(Replay::new(Carts::new(&carts, &tiles, true), carts_palette))
+        }
+        15 => {
+            let game = day15::input_generator(input)?;
+            let battle = Battle::new(&game, 3, false);
+            Box::new(Replay::new(battle, battle_palette).with_cells(|b| b.snapshot().to_string()))
+        }
+        17 => {
+            let world = day17::input_generator(input)?;
+            Box::new(Replay::new(Flow::new(&world), water_palette))
+        }
+        18 => {
+            let acres = day18::input_generator(input)?;
+            Box::new(Replay::new(Lumber::new(&acres), lumber_palette))
+        }
+        _ => return Err(format!("no replay for day{}", day).into()),
+    };
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gen;
+    use crate::replay::*;
+
+    #[test]
+    fn seeking() {
+        let input = gen::generate(18, 3, 20).unwrap();
+        let mut replay = open(18, &input).unwrap();
+        let frames: Vec<String> = (0..150)
+            .map(|frame| {
+                assert_eq!(replay.seek(frame), frame);
+                replay.render()
+            })
+            .collect();
+        assert_eq!(replay.last(), None);
+        for &frame in &[149, 3, 130, 0, 64, 63, 100] {
+            assert_eq!(replay.seek(frame), frame);
+            assert_eq!(replay.render(), frames[frame]);
+        }
+    }
+
+    #[test]
+    fn last_frame() {
+        let input = "#######
+#.G...#
+#...EG#
+#.#.#G#
+#..G#E#
+#.....#
+#######";
+        let mut replay = open(15, input).unwrap();
+        assert_eq!(replay.seek(1000), 48);
+        assert_eq!(replay.last(), Some(48));
+        assert!(replay.render().starts_with("#######\n#G....#   G(200)"));
+        assert!(replay.cells().starts_with("#######\n#G....#\n"));
+        replay.seek(0);
+        assert_eq!(replay.last(), None);
+        assert!(open(12, input).is_err());
+
+        let mut sky = open(10, &gen::generate(10, 2, 20).unwrap()).unwrap();
+        let last = sky.seek(usize::MAX);
+        assert_eq!(sky.last(), Some(last));
+        assert!(sky.cells().lines().all(|line| line.len() <= 100));
+    }
+
+    #[test]
+    fn colours() {
+        assert_eq!(
+            colourise("#.E\nEE.", battle_palette),
+            "\x1b[90m#\x1b[0m.\x1b[1;32mE\x1b[0m\n\x1b[1;32mEE\x1b[0m."
+        );
+        let mut replay = open(18, ".|#").unwrap();
+        assert_eq!(
+            replay.render_ansi(),
+            "\x1b[90m.\x1b[0m\x1b[32m|\x1b[0m\x1b[33m#\x1b[0m"
+        );
+        replay.seek(1);
+        assert_eq!(replay.frame(), 1);
+    }
+}
@@ -0,0 +1,161 @@
+//! The days that are simulations, opened up so they can be run a step at a time.
+//!
+//! Each one wraps its puzzle state in a type implementing `Simulation`, and the solvers drive
+//! those types through a `Runner` like anyone else. Observers attached to a runner see the
+//! simulation before the first step and again after every step, which is enough to log,
+//! render or check the intermediate states without copying a day's loop.
+//!
+//! What a step is depends on the day: a second of day10's sky, a generation of day12's pots,
+//! a tick of day13's carts, a round of day15's battle, a drip of day17's water, a minute of
+//! day18's acres and a round of day24's fight. Day18 never finishes, and day12 only does once
+//! every pot is empty, so run them with `Runner::run_for`.
+pub use crate::day10::Sky;
+pub use crate::day12::Pots;
+pub use crate::day13::Carts;
+pub use crate::day15::{Battle, Outcome};
+pub use crate::day17::Flow;
+pub use crate::day18::Lumber;
+pub use crate::day24::Fight;
+
+pub trait Simulation {
+    /// A copy of the state, to keep once the simulation has moved on.
+    type Snapshot;
+
+    /// Advances the simulation by one step. Stepping a finished simulation does nothing.
+    fn step(&mut self);
+
+    fn is_finished(&self) -> bool;
+
+    fn snapshot(&self) -> Self::Snapshot;
+}
+
+type Observer<'a, S> = Box<dyn FnMut(usize, &S) + 'a>;
+
+/// Steps a simulation, calling every observer with the step count and the simulation before
+/// the first step and after each one.
+pub struct Runner<'a, S: Simulation> {
+    simulation: S,
+    steps: usize,
+    started: bool,
+    observers: Vec<Observer<'a, S>>,
+}
+
+impl<'a, S: Simulation> Runner<'a, S> {
+    pub fn new(simulation: S) -> Self {
+        Self {
+            simulation,
+            steps: 0,
+            started: false,
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn observe(mut self, observer: impl FnMut(usize, &S) + 'a) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    fn notify(&mut self) {
+        for observer in &mut self.observers {
+            observer(self.steps, &self.simulation);
+        }
+    }
+
+    fn start(&mut self) {
+        if !self.started {
+            self.started = true;
+            self.notify();
+        }
+    }
+
+    /// Takes one step, returning false without stepping once the simulation has finished.
+    pub fn step(&mut self) -> bool {
+        self.start();
+        if self.simulation.is_finished() {
+            return false;
+        }
+        self.simulation.step();
+        self.steps += 1;
+        self.notify();
+        true
+    }
+
+    /// Steps until the simulation finishes.
+    pub fn run(mut self) -> S {
+        while self.step() {}
+        self.simulation
+    }
+
+    /// Takes up to `steps` more steps, stopping early if the simulation finishes.
+    pub fn run_for(mut self, steps: usize) -> S {
+        self.start();
+        for _ in 0..steps {
+            if !self.step() {
+                break;
+            }
+        }
+        self.simulation
+    }
+
+    /// How many steps have been taken.
+    pub const fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub const fn simulation(&self) -> &S {
+        &self.simulation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::*;
+
+    struct Countdown(usize);
+
+    impl Simulation for Countdown {
+        type Snapshot = usize;
+
+        fn step(&mut self) {
+            self.0 = self.0.saturating_sub(1);
+        }
+
+        fn is_finished(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn snapshot(&self) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn observers() {
+        let mut seen = Vec::new();
+        let mut calls = 0;
+        let countdown = Runner::new(Countdown(3))
+            .observe(|step, countdown| seen.push((step, countdown.snapshot())))
+            .observe(|_, _| calls += 1)
+            .run();
+        assert_eq!(countdown.0, 0);
+        assert_eq!(seen, vec![(0, 3), (1, 2), (2, 1), (3, 0)]);
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn stepping() {
+        let mut runner = Runner::new(Countdown(2));
+        assert!(runner.step());
+        assert_eq!((runner.steps(), runner.simulation().0), (1, 1));
+        assert!(runner.step());
+        assert!(!runner.step());
+        assert_eq!(runner.steps(), 2);
+
+        let mut seen = Vec::new();
+        let countdown = Runner::new(Countdown(5))
+            .observe(|step, _| seen.push(step))
+            .run_for(2);
+        assert_eq!(countdown.0, 3);
+        assert_eq!(seen, vec![0, 1, 2]);
+    }
+}
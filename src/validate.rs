@@ -0,0 +1,207 @@
+//! Checking that an input means something, not just that it parses.
+//!
+//! Some inputs parse fine and still can't be solved: day4's guards waking up without falling
+//! asleep, day7's steps waiting on each other, day8's headers asking for more numbers than
+//! there are, day16's samples that no opcode explains or that leave an opcode number
+//! ambiguous, and day20's unbalanced parentheses.
+//! The solvers would answer those wrongly or panic, so `validate` finds them up front. Every
+//! other day is only checked for parsing.
+use crate::error::ParseError;
+use crate::*;
+use std::error::Error;
+
+/// Whether `validate` checks more of `day`'s input than that it parses.
+pub const fn checks(day: u8) -> bool {
+    matches!(day, 4 | 7 | 8 | 16 | 20)
+}
+
+fn parses<T>(generated: Result<T, ParseError>) -> Vec<ParseError> {
+    generated.err().into_iter().collect()
+}
+
+/// Every problem found with `input` for `day`, in line order. A day that can't be parsed
+/// reports only the first line that couldn't.
+pub fn validate(day: u8, input: &str) -> Result<Vec<ParseError>, Box<dyn Error>> {
+    let input = input.trim_end_matches('\n');
+    let mut errors = match day {
+        1 => parses(day1::input_generator(input)),
+        2 => Vec::new(),
+        3 => parses(day3::input_generator(input)),
+        4 => day4::validate(input),
+        5 => parses(day5::input_generator(input)),
+        6 => parses(day6::input_generator(input)),
+        7 => day7::validate(input),
+        8 => day8::validate(input),
+        9 => parses(day9::input_generator(input)),
+        10 => parses(day10::input_generator(input)),
+        11 => parses(day11::parse_serial(input)),
+        12 => parses(day12::input_generator(input)),
+        13 => parses(day13::input_generator(input)),
+        14 => parses(day14::input_generator(input)),
+        15 => parses(day15::input_generator(input)),
+        16 => day16::validate(input),
+        17 => parses(day17::input_generator(input)),
+        18 => parses(day18::input_generator(input)),
+        19 => parses(day19::input_generator(input)),
+        20 => day20::validate(input),
+        21 => parses(day21::input_generator(input)),
+        22 => parses(day22::input_generator(input)),
+        23 => parses(day23::input_generator(input)),
+        24 => parses(day24::input_generator(input)),
+        25 => parses(day25::input_generator(input)),
+        _ => return Err(format!("no day{}", day).into()),
+    };
+    errors.sort_by_key(|error| (error.line, error.column));
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate::*;
+
+    fn reasons(day: u8, input: &str) -> Vec<String> {
+        validate(day, input)
+            .unwrap()
+            .iter()
+            .map(|error| format!("{}:{} {}", error.line, error.column, error.reason))
+            .collect()
+    }
+
+    #[test]
+    fn cycles() {
+        let input = "Step C must be finished before step A can begin.
+Step A must be finished before step B can begin.
+Step B must be finished before step C can begin.
+Step C must be finished before step D can begin.
+Step E must be finished before step E can begin.";
+        assert_eq!(
+            reasons(7, input),
+            vec![
+                "1:1 steps wait on each other: A -> B -> C -> A",
+                "5:1 steps wait on each other: E -> E"
+            ]
+        );
+        assert!(reasons(7, &input[..input.find("\nStep B").unwrap()]).is_empty());
+    }
+
+    #[test]
+    fn naps() {
+        let input = "[1518-10-31 00:30] wakes up
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:26] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:45] falls asleep
+[1518-11-03 00:05] Guard #10 begins shift";
+        assert_eq!(
+            reasons(4, input),
+            vec![
+                "1:20 wakes up without having fallen asleep",
+                "5:20 wakes up without having fallen asleep",
+                "7:1 falls asleep until the next shift",
+                "8:20 falls asleep while already asleep"
+            ]
+        );
+        assert_eq!(
+            reasons(4, "[1518-11-01 00:05] falls asleep"),
+            vec!["1:20 falls asleep before any shift begins",]
+        );
+    }
+
+    #[test]
+    fn headers() {
+        assert!(reasons(8, "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").is_empty());
+        assert_eq!(
+            reasons(8, "2 3 0 3 10 11 12 1 1 0 1 99 2 1"),
+            vec!["1:1 header asks for 3 metadata entries but only 1 number is left"]
+        );
+        assert_eq!(
+            reasons(8, "1 1 0 1 99 2 7 7"),
+            vec!["1:14 2 numbers left over after the root node"]
+        );
+        assert_eq!(
+            reasons(8, "2 3 0 3 10 11 12"),
+            vec!["1:1 header asks for 2 children but the data ends after 1"]
+        );
+        assert_eq!(reasons(8, "").len(), 1);
+    }
+
+    #[test]
+    fn samples() {
+        let input = "Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [3, 2, 2, 1]
+
+Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [9, 9, 9, 9]
+
+Before: [0, 0, 0, 0]
+3 5 0 0
+After:  [5, 0, 0, 0]
+
+Before: [0, 0, 0, 1]
+3 3 3 0
+After:  [2, 0, 0, 1]
+
+Before: [0, 0, 0, 0]
+17 0 0 0
+After:  [0, 0, 0, 0]
+
+
+
+9 2 1 2
+4 0 0 5";
+        assert_eq!(
+            reasons(16, input),
+            vec![
+                "6:1 behaves like none of the sixteen opcodes",
+                "10:1 the samples of opcode 3 don't all behave like any one opcode",
+                "18:1 opcode 17 is not one of the sixteen",
+                "24:1 writes register 5 of only four"
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_samples() {
+        let input = "Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [3, 2, 2, 1]
+
+
+
+9 2 1 2";
+        assert_eq!(
+            reasons(16, input),
+            vec!["2:1 opcode 9 could be any of addi, mulr, seti"]
+        );
+        // a number the test program doesn't use needn't be worked out
+        let unused = "Before: [3, 2, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1]";
+        assert!(reasons(16, unused).is_empty());
+    }
+
+    #[test]
+    fn parentheses() {
+        assert!(reasons(20, "^ENWWW(NEEE|SSE(EE|N))$").is_empty());
+        assert_eq!(
+            reasons(20, "^N(E|W))S|(N$"),
+            vec![
+                "1:8 `)` without a matching `(`",
+                "1:10 `|` outside any parentheses",
+                "1:11 `(` is never closed"
+            ]
+        );
+        assert!(crate::day20::input_generator("^N(E|W$").is_err());
+    }
+
+    #[test]
+    fn parsing() {
+        assert!(reasons(1, "+1\n-2").is_empty());
+        assert_eq!(reasons(1, "+1\n-x\n+3"), vec!["2:1 expected i32"]);
+        assert!(reasons(2, "abcdef").is_empty());
+        assert!(validate(26, "").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Answers that come with what they were worked out from, so they can be checked rather than
+//! trusted.
+//!
+//! A witness is the thing a puzzle asks about before it's turned into a number: the guard and
+//! minute of day4, the unit day5 removes, day11's square, the path to day20's furthest room,
+//! day22's route to the target and the point day23 picks. Each day's `_with_witness` solvers
+//! return their own type; `solve_with_witness` puts them behind one signature.
+use crate::registry::Answer;
+use crate::*;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Witness {
+    Sleeper(day4::Sleeper),
+    Removal(day5::Removal),
+    Square(day11::Square),
+    Path(day20::Path),
+    Route(day22::Route),
+    Spot(day23::Spot),
+}
+
+impl fmt::Display for Witness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Sleeper(sleeper) => sleeper.fmt(f),
+            Self::Removal(removal) => removal.fmt(f),
+            Self::Square(square) => square.fmt(f),
+            Self::Path(path) => path.fmt(f),
+            Self::Route(route) => route.fmt(f),
+            Self::Spot(spot) => spot.fmt(f),
+        }
+    }
+}
+
+/// Whether `solve_with_witness` can explain `day`'s `part`.
+pub const fn explains(day: u8, part: u8) -> bool {
+    matches!(
+        (day, part),
+        (4, 1) | (4, 2) | (5, 2) | (11, 1) | (11, 2) | (20, 1) | (22, 2) | (23, 2)
+    )
+}
+
+/// Solves `day`'s `part` like `Registry::solve`, along with the witness the answer comes from.
+pub fn solve_with_witness(
+    day: u8,
+    part: u8,
+    input: &str,
+) -> Result<(Answer, Witness), Box<dyn Error>> {
+    let input = input.trim_end_matches('\n');
+    Ok(match (day, part) {
+        (4, 1) => {
+            let (answer, sleeper) = day4::solve_part1_with_witness(input)?;
+            (answer.into(), Witness::Sleeper(sleeper))
+        }
+        (4, 2) => {
+            let (answer, sleeper) = day4::solve_part2_with_witness(input)?;
+            (answer.into(), Witness::Sleeper(sleeper))
+        }
+        (5, 2) => {
+            let (answer, removal) = day5::solve_part2_with_witness(&day5::input_generator(input)?);
+            (Answer::try_from(answer)?, Witness::Removal(removal))
+        }
+        (11, 1) => {
+            let (answer, square) = day11::solve_part1_with_witness(input)?;
+            (answer.into(), Witness::Square(square))
+        }
+        (11, 2) => {
+            let (answer, square) = day11::solve_part2_with_witness(input)?;
+            (answer.into(), Witness::Square(square))
+        }
+        (20, 1) => {
+            let (answer, path) = day20::solve_part1_with_witness(&day20::input_generator(input)?);
+            (Answer::try_from(answer)?, Witness::Path(path))
+        }
+        (22, 2) => {
+            let (answer, route) = day22::solve_part2_with_witness(&day22::input_generator(input)?)?;
+            (Answer::try_from(answer)?, Witness::Route(route))
+        }
+        (23, 2) => {
+            let (answer, spot) = day23::solve_part2_with_witness(&day23::input_generator(input)?)?;
+            (Answer::try_from(answer)?, Witness::Spot(spot))
+        }
+        _ => return Err(format!("no witness for day{} part{}", day, part).into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::Registry;
+    use crate::witness::*;
+
+    #[test]
+    fn agrees_with_registry() {
+        let registry = Registry::new();
+        let inputs = [
+            (5, 2, "dabAcCaCBAcCcaDA\n"),
+            (11, 1, "18"),
+            (20, 1, "^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$"),
+            (22, 2, "depth: 510\ntarget: 10,10"),
+        ];
+        for &(day, part, input) in &inputs {
+            assert!(explains(day, part));
+            let (answer, _) = solve_with_witness(day, part, input).unwrap();
+            assert_eq!(answer, registry.solve(day, part, None, input).unwrap());
+        }
+        let (answer, witness) = solve_with_witness(11, 1, "18").unwrap();
+        assert_eq!(answer, Answer::Text("33,45".to_owned()));
+        assert_eq!(witness.to_string(), "the 3x3 square at 33,45 has 29 power");
+    }
+
+    #[test]
+    fn unexplained() {
+        assert!(!explains(5, 1));
+        assert!(solve_with_witness(5, 1, "aA").is_err());
+        assert!(solve_with_witness(4, 1, "[1518-11-01 00:00] Guard #x begins shift").is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Runs every registered solver against the known answers for the real puzzle inputs.
+//!
+//! The heavier simulations take minutes without optimisations, so this is ignored by default:
+//! cargo test --release --test answers -- --ignored --nocapture
+use adventofcode_2018::answers::{check, load_manifest, unchecked};
+use adventofcode_2018::registry::Registry;
+use adventofcode_2018::witness::{explains, solve_with_witness};
+use std::fs;
+use std::path::Path;
+
+#[test]
+#[ignore]
+fn known_answers() {
+    let registry = Registry::new();
+    let expectations = load_manifest(Path::new("input/2018/answers.txt")).unwrap();
+    let outcomes = check(&registry, &expectations);
+
+    let mut failures = Vec::new();
+    for outcome in &outcomes {
+        let e = &outcome.expectation;
+        let status = if outcome.passed() { "ok" } else { "FAILED" };
+        println!(
+            "day{:<2} part{} {:<5} {:>10.3}ms {}",
+            e.day,
+            e.part,
+            e.variant.as_deref().unwrap_or("-"),
+            outcome.elapsed.as_secs_f64() * 1000.0,
+            status
+        );
+        if !outcome.passed() {
+            failures.push(format!(
+                "day{} part{} {:?}: expected {:?}, got {:?}",
+                e.day, e.part, e.variant, e.answer, outcome.actual
+            ));
+        }
+    }
+    for (day, part, variant) in unchecked(&registry, &expectations) {
+        failures.push(format!(
+            "day{} part{} {:?}: no known answer",
+            day, part, variant
+        ));
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+#[ignore]
+fn witnessed_answers() {
+    let expectations = load_manifest(Path::new("input/2018/answers.txt")).unwrap();
+    let mut failures = Vec::new();
+    for e in &expectations {
+        if e.variant.is_some() || !explains(e.day, e.part) {
+            continue;
+        }
+        let input = fs::read_to_string(&e.input).unwrap();
+        let (answer, witness) = solve_with_witness(e.day, e.part, &input).unwrap();
+        println!("day{:<2} part{} {}", e.day, e.part, witness);
+        if answer.to_string() != e.answer {
+            failures.push(format!(
+                "day{} part{}: expected {:?}, got {:?}",
+                e.day, e.part, e.answer, answer
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
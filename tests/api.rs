@@ -0,0 +1,81 @@
+//! Uses the days' engines the way another crate would, through the public modules only.
+use adventofcode_2018::day15::{self, Battle, Outcome};
+use adventofcode_2018::day17;
+use adventofcode_2018::day20;
+use adventofcode_2018::day24::{self, ArmyType, Fight};
+use adventofcode_2018::elfcode::{parse_program, Machine};
+use adventofcode_2018::simulation::Runner;
+
+#[test]
+fn combat() {
+    let game = day15::input_generator(
+        "#######
+#.G...#
+#...EG#
+#.#.#G#
+#..G#E#
+#.....#
+#######",
+    )
+    .unwrap();
+    assert_eq!(game.units().len(), 6);
+    let battle = Runner::new(Battle::new(&game, 3, false)).run();
+    assert_eq!(battle.outcome(), Some(Outcome::Won(27730)));
+    let survivors: Vec<_> = battle
+        .game()
+        .units()
+        .into_iter()
+        .map(|unit| unit.2)
+        .collect();
+    assert_eq!(survivors, vec![200, 131, 59, 200]);
+}
+
+#[test]
+fn regex_explorer() {
+    let doors = day20::doors("^N(E|W)N$").unwrap();
+    assert_eq!(doors[&(0, 0)].iter().collect::<Vec<_>>(), vec![&(0, -1)]);
+    let distances = day20::distances(&doors);
+    assert_eq!(distances[&(1, -2)], ((1, -1), 3));
+    assert_eq!(distances.len(), 5);
+    assert!(day20::doors("^N(E|W$").is_err());
+}
+
+#[test]
+fn water() {
+    let world = day17::input_generator("x=499, y=2..3\ny=3, x=499..501\nx=501, y=2..3").unwrap();
+    assert_eq!(world.tile(500, 0), Some('+'));
+    assert_eq!(world.tile(499, 3), Some('#'));
+    let flow = Runner::new(day17::Flow::new(&world)).run();
+    assert_eq!(flow.world().tile(500, 2), Some('~'));
+    assert_eq!(flow.world().count(b"~"), 1);
+}
+
+#[test]
+fn armies() {
+    let groups = day24::input_generator(
+        "Immune System:
+17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+
+Infection:
+801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1",
+    )
+    .unwrap();
+    assert_eq!(groups[0].army(), &ArmyType::ImmuneSystem);
+    assert_eq!((groups[1].units(), groups[1].hit_points()), (801, 4706));
+    assert_eq!(groups[0].weaknesses().len(), 2);
+    let fight = Runner::new(Fight::new(&groups, 0)).run();
+    assert!(fight.units(ArmyType::ImmuneSystem) == 0 || fight.units(ArmyType::Infection) == 0);
+}
+
+#[test]
+fn elfcode() {
+    let program = parse_program(
+        19,
+        "#ip 0\nseti 5 0 1\nseti 6 0 2\naddi 0 1 0\naddr 1 2 3\nsetr 1 1 0\nseti 8 0 4\nseti 9 0 5",
+        6,
+    )
+    .unwrap();
+    let mut machine = Machine::with_program(6, &program);
+    machine.run();
+    assert_eq!(machine.registers, vec![6, 5, 6, 0, 0, 9]);
+}